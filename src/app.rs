@@ -1,310 +1,2164 @@
-use crate::calculator::{calculate, format_number, CalculatorMode, Operation};
+use crate::business::{BusinessSettings, RateKey};
+use crate::calculator::{
+    self, calculate, condition_of, format_with, full_precision, AngleUnit, CalculatorMode, Condition,
+    DisplayFormat, NumericOptions, NumericPolicy, Operation,
+};
+use crate::calculus::{Calculus, CalculusSettings, Estimate};
+use crate::complex::{self, Complex, ComplexForm};
+use crate::currency::{Rate, RateTable};
+use crate::dates::{self, Date, DateSettings};
+use crate::distribution::{Distribution, Function};
+use crate::entry::{FieldKind, NumberEntry};
+use crate::expression::{self, Context, Expr};
+use crate::finance::{self, CashFlowMeasure, FinanceSettings, FinanceTable, TvmVariable};
+use crate::graph::Graph;
+use crate::integer::{self, IntegerFunction, IntegerSettings};
+use crate::locale::NumberLocale;
+use crate::matrix::{Matrix, MatrixOp, MatrixOutcome, NamedMatrix};
+use crate::notation;
+use crate::regression::{self, Fit, RegressionModel};
+use crate::solver::{self, SolverSettings};
+use crate::statistics::{self, DataPoint, Statistic};
+use crate::tape::{Tape, TapeLine, TapeTotals};
 use crate::ui;
+use crate::uncertainty::{Measured, Precision, PrecisionMode};
+use crate::units::Conversion;
+use crate::value::{self, Value};
 use eframe::egui;
 use std::collections::VecDeque;
 
 const MAX_HISTORY_SIZE: usize = 100;
 
+/// The operand currently shown and edited.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+enum Operand {
+    /// A number being typed on the keypad.
+    Entry(NumberEntry),
+    /// A computed, recalled or pasted value; typing starts a new entry.
+    Value(Complex),
+    /// A vector or a quantity with units from a pasted expression. Unary
+    /// keys apply to it; binary arithmetic needs an expression.
+    Compound(Value),
+    /// The last calculation failed. Digits, decimal, EXP and the clear keys
+    /// start over; operations and memory keys are ignored until then.
+    Error,
+}
+
+impl Operand {
+    fn value(&self) -> Option<Complex> {
+        match self {
+            Operand::Entry(entry) => Some(entry.value()),
+            Operand::Value(value) => Some(*value),
+            Operand::Compound(_) | Operand::Error => None,
+        }
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct CalcsPlus {
-    display: String,
-    current_value: f64,
-    previous_value: f64,
+    operand: Operand,
+    previous_value: Complex,
+    /// The first operand as a measurement, when it carries a precision.
+    previous_measured: Option<Measured>,
     operation: Option<Operation>,
     mode: CalculatorMode,
     history: VecDeque<String>,
-    memory: f64,
-    should_clear_display: bool,
+    memory: Complex,
     error_message: Option<String>,
+    notice: Option<String>,
+    numeric_policy: NumericPolicy,
+    precision_mode: PrecisionMode,
+    display_format: DisplayFormat,
+    /// Format to return to from a fraction or DMS display toggle.
+    #[serde(skip)]
+    toggled_format: Option<DisplayFormat>,
+    locale: NumberLocale,
+    angle_unit: AngleUnit,
+    complex_mode: bool,
+    complex_form: ComplexForm,
+    data: Vec<DataPoint>,
+    regression_model: RegressionModel,
+    distribution: Distribution,
+    graph: Graph,
+    solver: SolverSettings,
+    calculus: CalculusSettings,
+    matrices: Vec<NamedMatrix>,
+    /// Indices of the A and B operands in `matrices`.
+    matrix_operands: (usize, usize),
+    conversion: Conversion,
+    currency: RateTable,
+    /// Currencies converted from and to in the currency panel.
+    currency_pair: (String, String),
+    dates: DateSettings,
+    finance: FinanceSettings,
+    business: BusinessSettings,
+    tape: Tape,
+    integer: IntegerSettings,
+    /// Seed the random keys restart from; `None` leaves them seeded from
+    /// the clock.
+    random_seed: Option<u64>,
+    /// Exact text of the number theory panel's last result.
+    #[serde(skip)]
+    integer_result: Option<String>,
+    /// First line of the calculation being entered, recorded once it
+    /// has a step.
+    #[serde(skip)]
+    tape_start: Option<TapeLine>,
+    #[serde(skip)]
+    finance_table: Option<FinanceTable>,
+    #[serde(skip)]
+    date_results: Vec<String>,
+    #[serde(skip)]
+    currency_import: Option<String>,
+    #[serde(skip)]
+    matrix_outcome: Option<MatrixOutcome>,
+    #[serde(skip)]
+    roots: Vec<Complex>,
+    #[serde(skip)]
+    pending_x: Option<f64>,
     #[serde(skip)]
     show_history: bool,
-    equation_display: String,
+    #[serde(skip)]
+    show_settings: bool,
+    #[serde(skip)]
+    show_distributions: bool,
+    #[serde(skip)]
+    show_graph: bool,
+    #[serde(skip)]
+    show_solver: bool,
+    #[serde(skip)]
+    show_integer: bool,
+    #[serde(skip)]
+    show_calculus: bool,
+    #[serde(skip)]
+    show_more_functions: bool,
+    #[serde(skip)]
+    import_text: Option<String>,
+    /// Whether the second operand of `operation` has been started.
     in_equation: bool,
 }
 
 impl Default for CalcsPlus {
     fn default() -> Self {
         Self {
-            display: "0".to_string(),
-            current_value: 0.0,
-            previous_value: 0.0,
+            operand: Operand::Entry(NumberEntry::default()),
+            previous_value: Complex::ZERO,
+            previous_measured: None,
             operation: None,
             mode: CalculatorMode::Standard,
             history: VecDeque::new(),
-            memory: 0.0,
-            should_clear_display: false,
+            memory: Complex::ZERO,
             error_message: None,
+            notice: None,
+            numeric_policy: NumericPolicy::default(),
+            precision_mode: PrecisionMode::default(),
+            display_format: DisplayFormat::default(),
+            toggled_format: None,
+            locale: NumberLocale::default(),
+            angle_unit: AngleUnit::default(),
+            complex_mode: false,
+            complex_form: ComplexForm::default(),
+            data: Vec::new(),
+            regression_model: RegressionModel::default(),
+            distribution: Distribution::default(),
+            graph: Graph::default(),
+            solver: SolverSettings::default(),
+            calculus: CalculusSettings::default(),
+            matrices: vec![
+                NamedMatrix {
+                    name: "A".to_string(),
+                    matrix: Matrix::from_rows(&[&[2.0, 1.0], &[1.0, 3.0]]),
+                },
+                NamedMatrix {
+                    name: "B".to_string(),
+                    matrix: Matrix::from_rows(&[&[1.0], &[2.0]]),
+                },
+            ],
+            matrix_operands: (0, 1),
+            conversion: Conversion::default(),
+            currency: RateTable::default(),
+            currency_pair: ("USD".to_string(), "EUR".to_string()),
+            currency_import: None,
+            dates: DateSettings::default(),
+            date_results: Vec::new(),
+            finance: FinanceSettings::default(),
+            business: BusinessSettings::default(),
+            tape: Tape::default(),
+            integer: IntegerSettings::default(),
+            random_seed: None,
+            integer_result: None,
+            tape_start: None,
+            finance_table: None,
+            matrix_outcome: None,
+            roots: Vec::new(),
+            pending_x: None,
             show_history: false,
-            equation_display: String::new(),
+            show_settings: false,
+            show_distributions: false,
+            show_graph: false,
+            show_solver: false,
+            show_integer: false,
+            show_calculus: false,
+            show_more_functions: false,
+            import_text: None,
             in_equation: false,
         }
     }
 }
 
+/// Storage key of the exchange-rate table.
+const RATE_TABLE_KEY: &str = "currency";
+/// Storage key of the tax and discount rates.
+const BUSINESS_KEY: &str = "business";
+/// Storage key of the adding-machine tape.
+const TAPE_KEY: &str = "tape";
+
 impl CalcsPlus {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Default::default()
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self::restore(cc.storage)
     }
 
-    pub fn input_digit(&mut self, digit: char) {
-        if self.should_clear_display {
-            self.display.clear();
-            self.should_clear_display = false;
+    /// A fresh calculator with the exchange, tax and discount rates and the
+    /// tape saved by a previous run.
+    fn restore(storage: Option<&dyn eframe::Storage>) -> Self {
+        let mut app = Self::default();
+        if let Some(currency) = storage.and_then(|storage| eframe::get_value(storage, RATE_TABLE_KEY)) {
+            app.currency = currency;
         }
-        
-        // If we're showing an operation, start entering the second number
-        if self.operation.is_some() && !self.in_equation {
-            self.display.clear();
-            self.in_equation = true;
+        if let Some(business) = storage.and_then(|storage| eframe::get_value(storage, BUSINESS_KEY)) {
+            app.business = business;
         }
-        
-        if self.display == "0" && digit != '.' {
-            self.display = digit.to_string();
-        } else {
-            self.display.push(digit);
+        if let Some(tape) = storage.and_then(|storage| eframe::get_value(storage, TAPE_KEY)) {
+            app.tape = tape;
         }
-        
-        // Update equation display
-        if self.in_equation && self.operation.is_some() {
-            self.equation_display = format!("{}{}{}",
-                format_number(self.previous_value),
-                self.operation.unwrap().to_symbol(),
-                self.display
-            );
-        } else {
-            self.equation_display = self.display.clone();
+        app
+    }
+
+    /// The entry being typed, starting a fresh one when a result, an error
+    /// or the first operand of a pending operation is shown.
+    fn editable_entry(&mut self) -> &mut NumberEntry {
+        let starting_second_operand = self.operation.is_some() && !self.in_equation;
+        if starting_second_operand || !matches!(self.operand, Operand::Entry(_)) {
+            if matches!(self.operand, Operand::Error) {
+                self.operation = None;
+            }
+            self.in_equation = self.operation.is_some();
+            self.operand = Operand::Entry(NumberEntry::default());
         }
-        
         self.error_message = None;
+        self.notice = None;
+        match &mut self.operand {
+            Operand::Entry(entry) => entry,
+            _ => unreachable!("operand was just replaced with an entry"),
+        }
+    }
+
+    pub fn input_digit(&mut self, digit: char) {
+        self.editable_entry().push_digit(digit);
     }
 
     pub fn input_decimal(&mut self) {
-        if self.should_clear_display {
-            self.display = "0.".to_string();
-            self.should_clear_display = false;
+        self.editable_entry().push_decimal();
+    }
+
+    /// EXP key: starts entering a power-of-ten exponent.
+    pub fn input_exponent(&mut self) {
+        self.editable_entry().start_exponent();
+    }
+
+    /// `°'"` key: starts the minutes, then the seconds, of an angle
+    /// entered as `12°34'56"`.
+    pub fn input_dms(&mut self) {
+        self.editable_entry().next_field(FieldKind::Dms);
+    }
+
+    /// `a b/c` key: starts the denominator of a fraction entered as `3/4`;
+    /// pressed twice, the first number is the whole part, as in `1 3/4`.
+    pub fn input_fraction(&mut self) {
+        self.editable_entry().next_field(FieldKind::Fraction);
+    }
+
+    /// ± key in uncertainty mode: the number typed so far is a
+    /// measurement whose uncertainty follows, as in `5.2 ± 0.1`. On a
+    /// shown result it starts a ± operation instead.
+    pub fn input_uncertainty(&mut self) {
+        if self.is_showing_result() {
+            self.set_operation(Operation::PlusMinus);
         } else {
-            // If we're showing an operation, start entering the second number
-            if self.operation.is_some() && !self.in_equation {
-                self.display = "0.".to_string();
-                self.in_equation = true;
-            } else if !self.display.contains('.') {
-                self.display.push('.');
-            }
+            self.editable_entry().start_uncertainty();
         }
-        
-        // Update equation display
-        if self.in_equation && self.operation.is_some() {
-            self.equation_display = format!("{}{}{}",
-                format_number(self.previous_value),
-                self.operation.unwrap().to_symbol(),
-                self.display
-            );
-        } else {
-            self.equation_display = self.display.clone();
+    }
+
+    /// `i` key: marks the entry as imaginary. Only available in complex mode.
+    pub fn input_imaginary(&mut self) {
+        if self.complex_mode {
+            self.editable_entry().toggle_imaginary();
+        }
+    }
+
+    /// ± key: flips the sign of the entry (or of its exponent while one is
+    /// being typed) or negates a shown result.
+    pub fn toggle_sign(&mut self) {
+        match &mut self.operand {
+            Operand::Entry(entry) => entry.toggle_sign(),
+            Operand::Value(value) => {
+                *value = -*value;
+                self.in_equation = self.operation.is_some();
+            }
+            Operand::Compound(value) => match value.negate() {
+                Ok(negated) => *value = negated,
+                Err(err) => self.error_message = Some(err),
+            },
+            Operand::Error => {}
         }
-        
-        self.error_message = None;
     }
 
     pub fn clear(&mut self) {
-        self.display = "0".to_string();
-        self.equation_display = "0".to_string();
-        self.current_value = 0.0;
-        self.previous_value = 0.0;
+        self.operand = Operand::Entry(NumberEntry::default());
+        self.previous_value = Complex::ZERO;
+        self.previous_measured = None;
         self.operation = None;
-        self.should_clear_display = false;
         self.error_message = None;
+        self.notice = None;
         self.in_equation = false;
+        self.pending_x = None;
     }
 
     pub fn clear_entry(&mut self) {
-        self.display = "0".to_string();
-        
-        // Update equation display based on current state
-        if self.operation.is_some() && !self.in_equation {
-            self.equation_display = format!("{}{}",
-                format_number(self.previous_value),
-                self.operation.unwrap().to_symbol()
-            );
-        } else {
-            self.equation_display = "0".to_string();
+        if matches!(self.operand, Operand::Error) {
+            self.operation = None;
+            self.in_equation = false;
         }
-        
+        self.operand = Operand::Entry(NumberEntry::default());
         self.error_message = None;
+        self.notice = None;
     }
 
     pub fn backspace(&mut self) {
-        if self.display.len() > 1 {
-            self.display.pop();
-        } else {
-            self.display = "0".to_string();
+        match &mut self.operand {
+            Operand::Entry(entry) => entry.backspace(),
+            Operand::Value(_) | Operand::Compound(_) => {}
+            Operand::Error => self.clear_entry(),
         }
-        self.error_message = None;
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self.operand, Operand::Error)
     }
 
     pub fn set_operation(&mut self, op: Operation) {
-        if let Ok(value) = self.display.parse::<f64>() {
-            if self.operation.is_some() && self.in_equation {
-                // Complete the current operation first
-                self.calculate_result();
-                self.previous_value = self.current_value;
-            } else {
-                self.previous_value = value;
+        let measured = self.measured_operand();
+        if matches!(self.operand, Operand::Compound(_)) && measured.is_none() {
+            self.error_message =
+                Some("Use an expression for vector or unit arithmetic, e.g. [1, 2] + [3, 4] or 5 km + 300 m".to_string());
+            return;
+        }
+        let Some(value) = self.operand.value().or(measured.map(|m| Complex::from(m.value))) else {
+            return;
+        };
+        if self.operation.is_some() && self.in_equation {
+            // Complete the current operation first
+            if !self.evaluate_pending() {
+                return;
             }
-            
-            self.operation = Some(op);
-            self.in_equation = false;
-            
-            // Update equation display to show the operation
-            self.equation_display = format!("{}{}",
-                format_number(self.previous_value),
-                op.to_symbol()
-            );
+            self.previous_measured = self.measured_operand();
+            match self.operand.value().or(self.previous_measured.map(|m| Complex::from(m.value))) {
+                Some(result) => self.previous_value = result,
+                None => return,
+            }
+        } else {
+            self.previous_value = value;
+            self.previous_measured = measured;
+            self.tape_start = self.tape_start_for(value);
+        }
+        
+        self.operand = match self.previous_measured {
+            Some(measured) => Operand::Compound(Value::Measured(measured)),
+            None => Operand::Value(self.previous_value),
+        };
+        self.operation = Some(op);
+        self.in_equation = false;
+    }
+
+    /// The operand as a measurement: a measured result, a number typed
+    /// with an uncertainty, or in significant-figure mode a typed number
+    /// with the figures it was typed with.
+    fn measured_operand(&self) -> Option<Measured> {
+        match &self.operand {
+            Operand::Compound(Value::Measured(measured)) => Some(*measured),
+            Operand::Entry(entry) if entry.uncertainty().is_some() => Some(Measured {
+                value: entry.value().re,
+                precision: Precision::Uncertainty(entry.uncertainty()?),
+            }),
+            Operand::Entry(entry) if self.precision_mode == PrecisionMode::SignificantFigures => {
+                let figures = entry.significant_figures()?;
+                Some(Measured { value: entry.value().re, precision: Precision::Figures(figures) })
+            }
+            _ => None,
         }
     }
 
     pub fn perform_unary_operation(&mut self, op: Operation) {
-        if let Ok(value) = self.display.parse::<f64>() {
-            match calculate(op, value, None) {
+        let compound = match &self.operand {
+            Operand::Compound(value) => Some(value.clone()),
+            _ => self.measured_operand().map(Value::Measured),
+        };
+        if let Some(operand) = &compound {
+            match value::calculate(op, operand, None, self.options()) {
                 Ok(result) => {
-                    let operation_str = match op {
-                        Operation::SquareRoot => format!("√({})", value),
-                        Operation::Square => format!("({})²", value),
-                        Operation::Reciprocal => format!("1/({})", value),
-                        Operation::Percentage => format!("{}%", value),
-                        Operation::Sin => format!("sin({})", value),
-                        Operation::Cos => format!("cos({})", value),
-                        Operation::Tan => format!("tan({})", value),
-                        Operation::Log => format!("log({})", value),
-                        Operation::Ln => format!("ln({})", value),
-                        Operation::Factorial => format!("{}!", value),
-                        _ => format!("{:?}({})", op, value),
-                    };
-                    
-                    self.add_to_history(&format!("{} = {}", operation_str, format_number(result)));
-                    self.display = format_number(result);
-                    self.current_value = result;
-                    self.should_clear_display = true;
-                    self.error_message = None;
-                }
-                Err(err) => {
-                    self.error_message = Some(err);
-                    self.display = "Error".to_string();
+                    let operation_str = unary_description(op, &self.fmt_result(operand));
+                    self.add_to_history(&format!("{} = {}", operation_str, self.fmt_result(&result)));
+                    self.enter_result(result);
                 }
+                Err(err) => self.fail(err),
+            }
+            return;
+        }
+        let Some(value) = self.operand.value() else {
+            return;
+        };
+        match self.apply(op, value, None) {
+            Ok((result, condition)) => {
+                let operation_str = unary_description(op, &self.fmt_value(value));
+                let annotation = self.note_condition(condition);
+                self.add_to_history(&format!("{} = {}{}", operation_str, self.fmt_value(result), annotation));
+                // The result replaces the operand it was applied to
+                self.in_equation = self.operation.is_some();
+                self.operand = Operand::Value(result);
+                self.error_message = None;
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Applies a function of the selected distribution to the displayed
+    /// value, which is x for pdf/pmf and cdf and the probability for inv.
+    pub fn apply_distribution(&mut self, function: Function) {
+        let Some(x) = self.real_operand("Distribution argument") else {
+            return;
+        };
+        let distribution = self.distribution;
+        match distribution.evaluate(function, x) {
+            Ok(result) => {
+                let separator = if self.locale.decimal_separator == ',' { "; " } else { ", " };
+                let args: Vec<String> = std::iter::once(x)
+                    .chain(distribution.params.iter().copied())
+                    .take(1 + distribution.family.parameters().len())
+                    .map(|value| self.fmt(value))
+                    .collect();
+                self.add_to_history(&format!(
+                    "{}({}) = {}",
+                    distribution.function_name(function),
+                    args.join(separator),
+                    self.fmt(result)
+                ));
+                self.in_equation = self.operation.is_some();
+                self.operand = Operand::Value(Complex::from(result));
+                self.error_message = None;
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Solves the solver panel's equation and shows the root.
+    pub fn solve_equation(&mut self) {
+        let settings = self.solver.clone();
+        match solver::solve(&settings.equation, &self.locale, &Context::new(self.options()), settings.method, settings.start) {
+            Ok(solution) => {
+                let detail = format!(
+                    "{}, {} iteration{}",
+                    solution.method.label(),
+                    solution.iterations,
+                    if solution.iterations == 1 { "" } else { "s" }
+                );
+                self.add_to_history(&format!(
+                    "{}: x = {} ({})",
+                    settings.equation.trim(),
+                    self.fmt(solution.root),
+                    detail
+                ));
+                self.enter_value(Complex::from(solution.root));
+                self.notice = Some(detail);
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Finds every root of the solver panel's polynomial; they are listed
+    /// in the panel and recorded in the history.
+    pub fn solve_polynomial(&mut self) {
+        let ctx = Context::new(self.options());
+        let roots = solver::parse_coefficients(&self.solver.coefficients, &self.locale, &ctx)
+            .and_then(|coefficients| solver::polynomial_roots(&coefficients));
+        match roots {
+            Ok(roots) => {
+                let separator = if self.locale.decimal_separator == ',' { "; " } else { ", " };
+                let listed: Vec<String> = roots.iter().map(|&root| self.fmt_value(root)).collect();
+                self.add_to_history(&format!(
+                    "roots({}) = {}",
+                    self.solver.coefficients.trim(),
+                    listed.join(separator)
+                ));
+                self.roots = roots;
             }
+            Err(err) => {
+                self.roots.clear();
+                self.fail(err);
+            }
+        }
+    }
+
+    /// Evaluates the calculus panel's derivative, integral, Σ, Π or limit
+    /// and shows the result; the error estimate goes to the history and
+    /// the notice line.
+    pub fn apply_calculus(&mut self) {
+        let settings = self.calculus.clone();
+        let operation = settings.operation;
+        let variable = settings.variable.trim().to_lowercase();
+        match self.calculus_estimate(&settings, &variable) {
+            Ok((bounds, estimate)) => {
+                let body = settings.expression.trim();
+                let description = match operation {
+                    Calculus::Derivative => format!("d/d{} {} at {} = {}", variable, body, variable, self.fmt(bounds[0])),
+                    Calculus::Integral => format!(
+                        "∫[{}, {}] {} d{}",
+                        self.fmt(bounds[0]),
+                        self.fmt(bounds[1]),
+                        body,
+                        variable
+                    ),
+                    Calculus::Sum | Calculus::Product => format!(
+                        "{}[{} = {}..{}] {}",
+                        operation.symbol(),
+                        variable,
+                        self.fmt(bounds[0]),
+                        self.fmt(bounds[1]),
+                        body
+                    ),
+                    Calculus::Limit => format!("lim {}→{} {}", variable, self.fmt(bounds[0]), body),
+                };
+                let error = format!("± {:.1e}", estimate.error);
+                self.add_to_history(&format!("{} = {} ({})", description, self.fmt(estimate.value), error));
+                self.enter_value(Complex::from(estimate.value));
+                self.notice = Some(format!("Estimated error {}", error));
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Parses the calculus panel's inputs and evaluates them, returning the
+    /// bounds as numbers along with the estimate.
+    fn calculus_estimate(&self, settings: &CalculusSettings, variable: &str) -> Result<(Vec<f64>, Estimate), String> {
+        if !matches!(expression::parse(variable, &self.locale)?, Expr::Variable(_)) {
+            return Err(format!("'{}' is not a variable name", variable));
+        }
+        let ctx = Context::new(self.options());
+        let body = expression::parse(&settings.expression, &self.locale)?;
+        let mut bounds = vec![expression::evaluate(&settings.lower, &self.locale, &ctx)?];
+        if settings.operation.bounds() == 2 {
+            bounds.push(expression::evaluate(&settings.upper, &self.locale, &ctx)?);
+        }
+        let estimate = settings.operation.evaluate(&body, variable, &bounds, &ctx)?;
+        Ok((bounds, estimate))
+    }
+
+    pub fn get_roots(&self) -> &[Complex] {
+        &self.roots
+    }
+
+    /// Shows one of the polynomial roots; complex roots need complex mode
+    /// for further calculation.
+    pub fn push_root(&mut self, index: usize) {
+        if let Some(&root) = self.roots.get(index) {
+            self.enter_value(root);
         }
     }
 
+    /// `=` key; with the tape recording, the result is added as an item.
     pub fn calculate_result(&mut self) {
-        if let Some(op) = self.operation {
-            if let Ok(current) = self.display.parse::<f64>() {
-                match calculate(op, self.previous_value, Some(current)) {
-                    Ok(result) => {
-                        let operation_str = format!("{} {} {} = {}",
-                            format_number(self.previous_value),
-                            op.to_symbol().trim(),
-                            format_number(current),
-                            format_number(result)
-                        );
-                        
-                        self.add_to_history(&operation_str);
-                        
-                        self.current_value = result;
-                        self.display = format_number(result);
-                        self.equation_display = format_number(result);
-                        self.operation = None;
-                        self.in_equation = false;
-                        self.should_clear_display = true;
-                        self.error_message = None;
+        if self.evaluate_pending() && self.tape.recording {
+            self.tape.lines.push(TapeLine::Total);
+        }
+    }
+
+    /// Completes the pending operation and reports whether it succeeded.
+    fn evaluate_pending(&mut self) -> bool {
+        let Some(op) = self.operation else {
+            return false;
+        };
+        let measured = self.measured_operand();
+        if op == Operation::PlusMinus || self.previous_measured.is_some() || measured.is_some() {
+            return self.evaluate_measured(op, measured);
+        }
+        let Some(current) = self.operand.value() else {
+            return false;
+        };
+        match self.apply(op, self.previous_value, Some(current)) {
+            Ok((result, condition)) => {
+                let operation_str = format!("{} {} {} = {}{}",
+                    self.fmt_value(self.previous_value),
+                    op.to_symbol().trim(),
+                    self.fmt_value(current),
+                    self.fmt_value(result),
+                    self.note_condition(condition)
+                );
+                
+                self.add_to_history(&operation_str);
+                if op == Operation::RandomInteger {
+                    // A draw cannot be replayed, so the tape keeps the number drawn
+                    self.tape_start = None;
+                    if self.tape.recording && result.is_real() {
+                        self.tape.lines.push(TapeLine::Start(result.re));
                     }
-                    Err(err) => {
-                        self.error_message = Some(err);
-                        self.equation_display = "Error".to_string();
-                        self.operation = None;
-                        self.in_equation = false;
+                } else {
+                    self.record_step(op, current, false);
+                }
+                
+                self.operand = Operand::Value(result);
+                self.operation = None;
+                self.in_equation = false;
+                self.error_message = None;
+                true
+            }
+            Err(err) => {
+                self.fail(err);
+                false
+            }
+        }
+    }
+
+    /// Completes a pending operation on measured values, where plain
+    /// numbers count as exact.
+    fn evaluate_measured(&mut self, op: Operation, measured: Option<Measured>) -> bool {
+        let current = measured.or_else(|| self.operand.value().filter(|v| v.is_real()).map(|v| Measured::exact(v.re)));
+        let previous = self.previous_measured.or_else(|| {
+            Some(self.previous_value).filter(|v| v.is_real()).map(|v| Measured::exact(v.re))
+        });
+        let (Some(previous), Some(current)) = (previous, current) else {
+            self.fail("Uncertainties and significant figures need real numbers".to_string());
+            return false;
+        };
+        let operands = (Value::Measured(previous), Value::Measured(current));
+        match value::calculate(op, &operands.0, Some(&operands.1), self.options()) {
+            Ok(result) => {
+                self.add_to_history(&format!(
+                    "{} {} {} = {}",
+                    self.fmt_result(&operands.0),
+                    op.to_symbol().trim(),
+                    self.fmt_result(&operands.1),
+                    self.fmt_result(&result)
+                ));
+                self.record_step(op, Complex::from(current.value), false);
+                self.operand = match result {
+                    Value::Measured(Measured { value, precision: Precision::Exact }) => {
+                        Operand::Value(Complex::from(value))
                     }
+                    result => Operand::Compound(result),
+                };
+                self.operation = None;
+                self.in_equation = false;
+                self.error_message = None;
+                self.notice = None;
+                true
+            }
+            Err(err) => {
+                self.fail(err);
+                false
+            }
+        }
+    }
+
+    /// % key. After + − × ÷ and a second number it works like a desk
+    /// calculator: `200 + 10 %` adds 10% (220), `200 − 10 %` takes it off
+    /// (180), `200 × 10 %` is 10% of 200 (20) and `50 ÷ 200 %` is 50 as a
+    /// percentage of 200 (25). Δ% and MU already take a percentage and just
+    /// complete. Otherwise the displayed number is divided by 100.
+    pub fn percent(&mut self) {
+        let Some(op) = self.operation.filter(|_| self.in_equation) else {
+            self.perform_unary_operation(Operation::Percentage);
+            return;
+        };
+        if matches!(op, Operation::PercentChange | Operation::Margin) {
+            // Already percentages, so % is just =
+            self.calculate_result();
+            return;
+        }
+        if !matches!(op, Operation::Add | Operation::Subtract | Operation::Multiply | Operation::Divide) {
+            self.perform_unary_operation(Operation::Percentage);
+            return;
+        }
+        let Some(rate) = self.operand.value() else {
+            return;
+        };
+        let base = self.previous_value;
+        match self.business_percent(op, base, rate) {
+            Ok((result, condition)) => {
+                let operation_str = format!("{} {} {}% = {}{}",
+                    self.fmt_value(base),
+                    op.to_symbol().trim(),
+                    self.fmt_value(rate),
+                    self.fmt_value(result),
+                    self.note_condition(condition)
+                );
+                self.add_to_history(&operation_str);
+                self.record_step(op, rate, true);
+                if self.tape.recording {
+                    self.tape.lines.push(TapeLine::Total);
                 }
+                self.operand = Operand::Value(result);
+                self.operation = None;
+                self.in_equation = false;
+                self.error_message = None;
             }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// `base op rate %` for the four arithmetic keys.
+    fn business_percent(
+        &self,
+        op: Operation,
+        base: Complex,
+        rate: Complex,
+    ) -> Result<(Complex, Option<Condition>), String> {
+        if op == Operation::Divide {
+            let (ratio, first) = self.apply(Operation::Divide, base, Some(rate))?;
+            let (result, second) = self.apply(Operation::Multiply, ratio, Some(Complex::from(100.0)))?;
+            return Ok((result, second.or(first)));
+        }
+        let (fraction, first) = self.apply(Operation::Percentage, rate, None)?;
+        let (portion, second) = self.apply(Operation::Multiply, base, Some(fraction))?;
+        if op == Operation::Multiply {
+            return Ok((portion, second.or(first)));
         }
+        let (result, third) = self.apply(op, base, Some(portion))?;
+        Ok((result, third.or(second).or(first)))
     }
 
-    pub fn toggle_mode(&mut self) {
-        self.mode = match self.mode {
-            CalculatorMode::Standard => CalculatorMode::Scientific,
-            CalculatorMode::Scientific => CalculatorMode::Standard,
+    /// TAX+, TAX− and DISC: applies the selected tax rate or the discount
+    /// rate to the displayed amount. The notice shows the tax or discount.
+    pub fn apply_rate_key(&mut self, key: RateKey) {
+        let Some(amount) = self.real_operand(key.label()) else {
+            return;
         };
+        let (name, rate) = match key {
+            RateKey::Discount => ("Discount".to_string(), self.business.discount_rate),
+            RateKey::AddTax | RateKey::RemoveTax => match self.business.tax() {
+                Some(tax) => (tax.name.clone(), tax.rate),
+                None => {
+                    self.error_message = Some("No tax rate configured".to_string());
+                    return;
+                }
+            },
+        };
+        let (result, part) = key.apply(amount, rate);
+        self.push_result(&format!("{} {} {}%", self.fmt(amount), key.label(), self.fmt(rate)), result);
+        self.notice = Some(format!("{} {}", name, self.fmt(part)));
     }
 
-    pub fn memory_clear(&mut self) {
-        self.memory = 0.0;
+    /// How a calculation starting from `value` goes on the tape: from the
+    /// previous result when `value` is still that result.
+    fn tape_start_for(&self, value: Complex) -> Option<TapeLine> {
+        if !self.tape.recording || !value.is_real() {
+            return None;
+        }
+        let carried = matches!(self.operand, Operand::Value(_))
+            && self.tape_totals().last_total(&self.tape) == Some(value.re);
+        Some(if carried { TapeLine::Carry } else { TapeLine::Start(value.re) })
     }
 
-    pub fn memory_recall(&mut self) {
-        self.display = format_number(self.memory);
-        self.should_clear_display = true;
+    /// Records a completed step of the calculation being entered.
+    fn record_step(&mut self, op: Operation, value: Complex, percent: bool) {
+        if !self.tape.recording || !value.is_real() {
+            return;
+        }
+        if let Some(start) = self.tape_start.take() {
+            self.tape.lines.push(start);
+        }
+        self.tape.lines.push(TapeLine::Step { op, value: value.re, percent });
     }
 
-    pub fn memory_store(&mut self) {
-        if let Ok(value) = self.display.parse::<f64>() {
-            self.memory = value;
+    /// One recomputed tape step, with the same rules as the keypad.
+    fn tape_step(&self, op: Operation, running: f64, value: f64, percent: bool) -> Result<f64, String> {
+        let (running, value) = (Complex::from(running), Complex::from(value));
+        let (result, _) = if percent {
+            self.business_percent(op, running, value)?
+        } else {
+            self.apply(op, running, Some(value))?
+        };
+        if result.is_real() {
+            Ok(result.re)
+        } else {
+            Err("Complex result".to_string())
         }
     }
 
-    pub fn memory_add(&mut self) {
-        if let Ok(value) = self.display.parse::<f64>() {
-            self.memory += value;
+    pub fn get_tape(&self) -> &Tape {
+        &self.tape
+    }
+
+    pub fn tape_mut(&mut self) -> &mut Tape {
+        &mut self.tape
+    }
+
+    /// The tape's results, subtotals and grand total, recomputed from its
+    /// steps.
+    pub fn tape_totals(&self) -> TapeTotals {
+        self.tape.recompute(|op, running, value, percent| self.tape_step(op, running, value, percent))
+    }
+
+    pub fn toggle_tape(&mut self) {
+        self.tape.recording = !self.tape.recording;
+        self.tape_start = None;
+    }
+
+    /// GT key: shows the grand total of the tape's items.
+    pub fn recall_grand_total(&mut self) {
+        let totals = self.tape_totals();
+        let label = format!("GT ({} item{})", totals.item_count, if totals.item_count == 1 { "" } else { "s" });
+        self.push_result(&label, totals.grand_total);
+    }
+
+    /// ◇ key: marks a subtotal of the items since the previous one and
+    /// shows it.
+    pub fn mark_subtotal(&mut self) {
+        if !self.tape.recording {
+            return;
+        }
+        self.tape.lines.push(TapeLine::Subtotal);
+        if let Some(Ok(subtotal)) = self.tape_totals().values.last() {
+            self.push_result("◇ Subtotal", *subtotal);
         }
     }
 
-    pub fn memory_subtract(&mut self) {
-        if let Ok(value) = self.display.parse::<f64>() {
-            self.memory -= value;
+    pub fn clear_tape(&mut self) {
+        self.tape.lines.clear();
+        self.tape_start = None;
+    }
+
+    pub fn get_random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets the seed and restarts the random sequence from it.
+    pub fn set_random_seed(&mut self, seed: Option<u64>) {
+        self.random_seed = seed;
+        self.restart_random();
+    }
+
+    /// Restarts the random keys from the seed, so that the same draws
+    /// repeat.
+    pub fn restart_random(&mut self) {
+        if let Some(seed) = self.random_seed {
+            calculator::seed_random(seed);
         }
     }
 
-    fn add_to_history(&mut self, entry: &str) {
-        self.history.push_front(entry.to_string());
-        if self.history.len() > MAX_HISTORY_SIZE {
-            self.history.pop_back();
+    pub fn integer_mut(&mut self) -> &mut IntegerSettings {
+        &mut self.integer
+    }
+
+    pub fn get_integer_result(&self) -> Option<&str> {
+        self.integer_result.as_deref()
+    }
+
+    /// Applies a number-theory function to the panel's fields as exact big
+    /// integers. Integer results go to the display, with the exact digits
+    /// in the panel; primality and factorizations are shown as text.
+    pub fn apply_integer(&mut self, function: IntegerFunction) {
+        match self.integer_call(function) {
+            Ok((call, result)) => {
+                self.add_to_history(&format!("{} = {}", call, result));
+                self.integer_result = Some(format!("{} = {}", call, result));
+            }
+            Err(err) => {
+                self.integer_result = None;
+                self.fail(err);
+            }
         }
     }
 
-    pub fn toggle_history(&mut self) {
-        self.show_history = !self.show_history;
+    /// The call as written and its result text, entering numeric results.
+    fn integer_call(&mut self, function: IntegerFunction) -> Result<(String, String), String> {
+        let ctx = Context::new(self.options());
+        let mut texts = Vec::new();
+        let mut args = Vec::new();
+        for field in function.fields() {
+            let text = match *field {
+                "a" => self.integer.a.trim(),
+                "b" => self.integer.b.trim(),
+                _ => self.integer.modulus.trim(),
+            };
+            let value = if text.is_empty() && *field == "a" {
+                match self.operand.value() {
+                    Some(value) if value.is_real() => integer::from_float(value.re)?,
+                    _ => return Err("Enter an integer for a".to_string()),
+                }
+            } else if text.is_empty() {
+                return Err(format!("Enter an integer for {}", field));
+            } else {
+                integer::exact(&expression::parse(text, &self.locale)?, &ctx)?
+            };
+            texts.push(self.locale.localize(&value.to_string()));
+            args.push(value);
+        }
+        let separator = if self.locale.decimal_separator == ',' { "; " } else { ", " };
+        let function = match function {
+            IntegerFunction::Mod(_) => IntegerFunction::Mod(self.integer.convention),
+            IntegerFunction::Div(_) => IntegerFunction::Div(self.integer.convention),
+            other => other,
+        };
+        let call = format!("{}({})", function.name(), texts.join(separator));
+        let n = &args[0];
+        let text = match function {
+            IntegerFunction::IsPrime => match (integer::is_prime(n), integer::is_certain(n)) {
+                (true, true) => "prime".to_string(),
+                (true, false) => "probably prime (Miller–Rabin)".to_string(),
+                (false, _) => "composite".to_string(),
+            },
+            IntegerFunction::Factor => integer::format_factors(n, &integer::factorize(n)?),
+            _ => {
+                let result = function.apply(&args)?;
+                let exact = self.locale.localize(&result.to_string());
+                let approximate = integer::to_f64(&result);
+                self.enter_value(Complex::from(approximate));
+                if integer::from_float(approximate).ok().as_ref() != Some(&result) {
+                    self.notice = Some(format!("Exact: {}", exact));
+                }
+                exact
+            }
+        };
+        Ok((call, text))
     }
 
-    pub fn get_display(&self) -> &str {
-        if self.equation_display.is_empty() {
-            &self.display
-        } else {
-            &self.equation_display
+    pub fn get_business(&self) -> &BusinessSettings {
+        &self.business
+    }
+
+    pub fn business_mut(&mut self) -> &mut BusinessSettings {
+        &mut self.business
+    }
+
+    /// Evaluates an operation in the active number domain and reports its
+    /// IEEE conditions. Real mode rejects operands with an imaginary part.
+    fn apply(
+        &self,
+        op: Operation,
+        val1: Complex,
+        val2: Option<Complex>,
+    ) -> Result<(Complex, Option<Condition>), String> {
+        let options = self.options();
+        if self.complex_mode {
+            let result = complex::calculate(op, val1, val2, options)?;
+            return Ok((result, complex::condition_of(op, val1, val2, result)));
+        }
+        if !val1.is_real() || val2.is_some_and(|v| !v.is_real()) {
+            return Err("Complex operand: switch to complex mode".to_string());
         }
+        let val2 = val2.map(|v| v.re);
+        let result = calculate(op, val1.re, val2, options)?;
+        Ok((Complex::from(result), condition_of(op, val1.re, val2, result)))
     }
 
-    pub fn get_mode(&self) -> CalculatorMode {
-        self.mode
+    fn options(&self) -> NumericOptions {
+        NumericOptions {
+            policy: self.numeric_policy,
+            angle: self.angle_unit,
+        }
     }
 
-    pub fn get_error(&self) -> Option<&str> {
-        self.error_message.as_deref()
+    /// Enters the error state; the pending operation is abandoned.
+    fn fail(&mut self, err: String) {
+        self.error_message = Some(err);
+        self.notice = None;
+        self.operand = Operand::Error;
+        self.operation = None;
+        self.in_equation = false;
     }
 
-    pub fn has_memory(&self) -> bool {
-        self.memory != 0.0
+    pub fn get_matrices(&self) -> &[NamedMatrix] {
+        &self.matrices
     }
 
-    pub fn get_history(&self) -> &VecDeque<String> {
-        &self.history
+    pub fn matrices_mut(&mut self) -> &mut Vec<NamedMatrix> {
+        &mut self.matrices
     }
 
-    pub fn is_showing_history(&self) -> bool {
-        self.show_history
+    /// First single-letter name not yet taken, or `M` followed by a number.
+    fn free_matrix_name(&self) -> String {
+        let taken = |name: &str| self.matrices.iter().any(|m| m.name == name);
+        ('A'..='Z')
+            .map(|letter| letter.to_string())
+            .chain((1..).map(|n| format!("M{}", n)))
+            .find(|name| !taken(name))
+            .unwrap_or_default()
     }
-}
 
-impl eframe::App for CalcsPlus {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        ui::draw_calculator(ctx, self);
+    pub fn add_matrix(&mut self) {
+        let name = self.free_matrix_name();
+        self.matrices.push(NamedMatrix { name, matrix: Matrix::zeros(2, 2) });
+    }
+
+    pub fn remove_matrix(&mut self, index: usize) {
+        if index < self.matrices.len() {
+            self.matrices.remove(index);
+            let fix = |i: usize| if i > index { i - 1 } else { i };
+            self.matrix_operands = (fix(self.matrix_operands.0), fix(self.matrix_operands.1));
+        }
+    }
+
+    pub fn get_matrix_operands(&self) -> (usize, usize) {
+        self.matrix_operands
+    }
+
+    pub fn set_matrix_operands(&mut self, operands: (usize, usize)) {
+        self.matrix_operands = operands;
+    }
+
+    /// Applies a matrix operation to the selected operands. Scalars go to
+    /// the display; matrices and eigenvalues are kept for viewing.
+    pub fn apply_matrix_op(&mut self, op: MatrixOp) {
+        let Some(a) = self.matrices.get(self.matrix_operands.0) else {
+            self.fail("Select a matrix".to_string());
+            return;
+        };
+        let b = if op.is_binary() { self.matrices.get(self.matrix_operands.1) } else { None };
+        match op.apply(a, b) {
+            Ok(MatrixOutcome::Scalar(label, value)) => {
+                self.matrix_outcome = None;
+                self.push_result(&label, value);
+            }
+            Ok(outcome) => {
+                match &outcome {
+                    MatrixOutcome::Matrices(results) => {
+                        for (name, matrix) in results {
+                            self.add_to_history(&format!("{} = {}", name, self.format_matrix(matrix)));
+                        }
+                    }
+                    MatrixOutcome::Values(label, values) => {
+                        let separator = if self.locale.decimal_separator == ',' { "; " } else { ", " };
+                        let listed: Vec<String> = values.iter().map(|&value| self.fmt_value(value)).collect();
+                        self.add_to_history(&format!("{} = {}", label, listed.join(separator)));
+                    }
+                    MatrixOutcome::Scalar(..) => {}
+                }
+                self.matrix_outcome = Some(outcome);
+                self.error_message = None;
+            }
+            Err(err) => {
+                self.matrix_outcome = None;
+                self.fail(err);
+            }
+        }
+    }
+
+    /// A matrix on one line: entries separated by spaces, rows by `;`.
+    fn format_matrix(&self, matrix: &Matrix) -> String {
+        let rows: Vec<String> = (0..matrix.rows())
+            .map(|i| {
+                let entries: Vec<String> = (0..matrix.cols()).map(|j| self.fmt(matrix[(i, j)])).collect();
+                entries.join(" ")
+            })
+            .collect();
+        format!("[{}]", rows.join("; "))
+    }
+
+    pub fn get_matrix_outcome(&self) -> Option<&MatrixOutcome> {
+        self.matrix_outcome.as_ref()
+    }
+
+    /// Stores a matrix from the last result under the next free name.
+    pub fn store_matrix_result(&mut self, index: usize) {
+        if let Some(MatrixOutcome::Matrices(results)) = &self.matrix_outcome {
+            if let Some((_, matrix)) = results.get(index) {
+                let matrix = matrix.clone();
+                let name = self.free_matrix_name();
+                self.notice = Some(format!("Stored as {}", name));
+                self.matrices.push(NamedMatrix { name, matrix });
+            }
+        }
+    }
+
+    /// Shows one of the listed eigenvalues.
+    pub fn push_matrix_value(&mut self, index: usize) {
+        if let Some(MatrixOutcome::Values(_, values)) = &self.matrix_outcome {
+            if let Some(&value) = values.get(index) {
+                self.enter_value(value);
+            }
+        }
+    }
+
+    pub fn conversion_mut(&mut self) -> &mut Conversion {
+        &mut self.conversion
+    }
+
+    /// ⇄ button: exchanges the units converted from and to.
+    pub fn swap_conversion_units(&mut self) {
+        let conversion = &mut self.conversion;
+        std::mem::swap(&mut conversion.from, &mut conversion.to);
+    }
+
+    /// Converts the current operand between the selected units. The
+    /// display keeps the plain number so it can be used further.
+    pub fn convert_units(&mut self) {
+        let Some(value) = self.real_operand("Value to convert") else {
+            return;
+        };
+        match self.conversion.convert(value, self.options()) {
+            Ok(quantity) => {
+                let result = quantity.shown_value();
+                self.add_to_history(&format!(
+                    "{} {} = {} {}",
+                    self.fmt(value),
+                    self.conversion.from,
+                    self.fmt(result),
+                    quantity.unit_text()
+                ));
+                self.enter_value(Complex::from(result));
+                self.notice = Some(quantity.unit_text().to_string());
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    pub fn get_rate_table(&self) -> &RateTable {
+        &self.currency
+    }
+
+    pub fn rate_table_mut(&mut self) -> &mut RateTable {
+        &mut self.currency
+    }
+
+    pub fn add_rate(&mut self) {
+        self.currency.rates.push(Rate {
+            code: String::new(),
+            per_base: 1.0,
+            updated: dates::now(),
+        });
+    }
+
+    pub fn remove_rate(&mut self, index: usize) {
+        if index < self.currency.rates.len() {
+            self.currency.rates.remove(index);
+        }
+    }
+
+    pub fn get_currency_pair(&self) -> (String, String) {
+        self.currency_pair.clone()
+    }
+
+    pub fn set_currency_pair(&mut self, pair: (String, String)) {
+        self.currency_pair = pair;
+    }
+
+    /// Text typed or pasted into the rate import box, while it is open.
+    pub fn currency_import_mut(&mut self) -> Option<&mut String> {
+        self.currency_import.as_mut()
+    }
+
+    pub fn toggle_currency_import(&mut self) {
+        self.currency_import = match self.currency_import {
+            Some(_) => None,
+            None => Some(String::new()),
+        };
+    }
+
+    /// Imports the rate table in the import box (CSV or JSON) and closes
+    /// the box; on error it stays open for correction.
+    pub fn finish_currency_import(&mut self) {
+        let Some(text) = &self.currency_import else {
+            return;
+        };
+        match self.currency.import(text, dates::now()) {
+            Ok(count) => {
+                self.currency_import = None;
+                self.error_message = None;
+                self.notice = Some(format!("Imported {} exchange rates", count));
+            }
+            Err(err) => self.error_message = Some(err),
+        }
+    }
+
+    /// Converts the current operand between the selected currencies,
+    /// warning when either rate is stale.
+    pub fn convert_currency(&mut self) {
+        let Some(amount) = self.real_operand("Amount") else {
+            return;
+        };
+        let (from, to) = self.currency_pair.clone();
+        match self.currency.convert(amount, &from, &to) {
+            Ok(result) => {
+                self.add_to_history(&format!("{} {} = {} {}", self.fmt(amount), from, self.fmt(result), to));
+                self.enter_value(Complex::from(result));
+                self.notice = Some(
+                    self.currency
+                        .staleness_warning([from.as_str(), to.as_str()], dates::now())
+                        .unwrap_or(to),
+                );
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    pub fn dates_mut(&mut self) -> &mut DateSettings {
+        &mut self.dates
+    }
+
+    /// Lines describing the last date calculation.
+    pub fn get_date_results(&self) -> &[String] {
+        &self.date_results
+    }
+
+    /// Shows a date calculation's lines, records the first in the history
+    /// and puts `value` in the display.
+    fn show_date_results(&mut self, results: Vec<String>, value: f64) {
+        self.add_to_history(&results[0]);
+        self.date_results = results;
+        self.enter_value(Complex::from(value));
+    }
+
+    fn date_fail(&mut self, err: String) {
+        self.date_results.clear();
+        self.fail(err);
+    }
+
+    /// Difference between the start and end dates in days, weeks, calendar
+    /// units and business days; the display shows the days.
+    pub fn date_difference(&mut self) {
+        let (start, end, holidays) = match self.date_range() {
+            Ok(inputs) => inputs,
+            Err(err) => return self.date_fail(err),
+        };
+        let days = end.days() - start.days();
+        let (years, months, rest) = dates::calendar_difference(start, end);
+        let plural = |n: i64, unit: &str| format!("{} {}{}", n, unit, if n.abs() == 1 { "" } else { "s" });
+        let results = vec![
+            format!("{} → {}: {}", start, end, plural(days, "day")),
+            format!("{}, {}", plural(days / 7, "week"), plural(days % 7, "day")),
+            format!("{}, {}, {}", plural(years, "year"), plural(months, "month"), plural(rest, "day")),
+            format!("{} (both ends counted)", plural(dates::business_days(start, end, &holidays), "business day")),
+        ];
+        self.show_date_results(results, days as f64);
+    }
+
+    /// The start and end dates and the holiday list.
+    fn date_range(&self) -> Result<(Date, Date, Vec<Date>), String> {
+        let start = Date::parse(&self.dates.start)?;
+        let end = Date::parse(&self.dates.end)?;
+        Ok((start, end, dates::parse_holidays(&self.dates.holidays)?))
+    }
+
+    /// Adds (`sign` 1) or subtracts (`sign` -1) the duration to the start
+    /// date and puts the result in the end date.
+    pub fn date_shift(&mut self, sign: i64) {
+        let settings = &self.dates;
+        let result = Date::parse(&settings.start).and_then(|start| {
+            let holidays = dates::parse_holidays(&settings.holidays)?;
+            Ok((start, settings.unit.shift(start, sign * settings.amount, &holidays)?))
+        });
+        match result {
+            Ok((start, date)) => {
+                let operator = if sign < 0 { "−" } else { "+" };
+                let (amount, unit) = (self.dates.amount, self.dates.unit.label());
+                let results = vec![
+                    format!("{} {} {} {} = {}", start, operator, amount, unit, date),
+                    date.weekday_name().to_string(),
+                ];
+                self.dates.end = date.to_string();
+                self.show_date_results(results, (date.days() - start.days()) as f64);
+            }
+            Err(err) => self.date_fail(err),
+        }
+    }
+
+    /// Weekday, ISO week, day of year and Unix time of the start date
+    /// (which may include a time); the display shows the Unix time.
+    pub fn describe_date(&mut self) {
+        match dates::parse_datetime(&self.dates.start) {
+            Ok(timestamp) => {
+                let date = Date::from_timestamp(timestamp);
+                let (week_year, week) = date.iso_week();
+                let results = vec![
+                    format!("{} is a {}", dates::format_datetime(timestamp), date.weekday_name()),
+                    format!("ISO week {}-W{:02}", week_year, week),
+                    format!("Day {} of {}", date.day_of_year(), date.days_in_year()),
+                    format!("Unix time {}", timestamp),
+                ];
+                self.show_date_results(results, timestamp as f64);
+            }
+            Err(err) => self.date_fail(err),
+        }
+    }
+
+    /// Converts the Unix timestamp field to a UTC date and time.
+    pub fn timestamp_to_date(&mut self) {
+        let text = self.dates.timestamp.trim();
+        let Ok(timestamp) = text.parse::<i64>() else {
+            return self.date_fail(format!("'{}' is not a Unix timestamp in seconds", text));
+        };
+        if Date::new(Date::from_timestamp(timestamp).year, 1, 1).is_none() {
+            return self.date_fail("Timestamp is out of range (years 0–9999)".to_string());
+        }
+        let date = Date::from_timestamp(timestamp);
+        let results = vec![
+            format!("Unix time {} = {} UTC", timestamp, dates::format_datetime(timestamp)),
+            date.weekday_name().to_string(),
+        ];
+        self.show_date_results(results, timestamp as f64);
+    }
+
+    pub fn finance_mut(&mut self) -> &mut FinanceSettings {
+        &mut self.finance
+    }
+
+    fn tvm_slot(&mut self, variable: TvmVariable) -> &mut f64 {
+        let tvm = &mut self.finance.tvm;
+        match variable {
+            TvmVariable::Periods => &mut tvm.periods,
+            TvmVariable::Rate => &mut tvm.rate,
+            TvmVariable::PresentValue => &mut tvm.present_value,
+            TvmVariable::Payment => &mut tvm.payment,
+            TvmVariable::FutureValue => &mut tvm.future_value,
+        }
+    }
+
+    /// Stores the displayed number in a TVM variable, like a financial
+    /// calculator's N, I/Y, PV, PMT and FV keys.
+    pub fn store_tvm(&mut self, variable: TvmVariable) {
+        if let Some(value) = self.real_operand(variable.label()) {
+            *self.tvm_slot(variable) = value;
+            self.notice = Some(format!("{} = {}", variable.label(), self.fmt(value)));
+        }
+    }
+
+    /// Solves a TVM variable from the other four and shows it.
+    pub fn solve_tvm(&mut self, variable: TvmVariable) {
+        match self.finance.tvm.solve(variable) {
+            Ok(value) => {
+                *self.tvm_slot(variable) = value;
+                self.push_result(variable.label(), value);
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Builds the amortization schedule for the TVM worksheet; the display
+    /// shows the total interest.
+    pub fn amortize(&mut self) {
+        match self.finance.tvm.amortization() {
+            Ok(rows) => {
+                let interest: f64 = rows.iter().map(|row| row.interest).sum();
+                self.finance_table = Some(FinanceTable::amortization(&rows));
+                self.push_result(&format!("Total interest over {} periods", rows.len()), interest);
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// NPV, IRR or MIRR of the cash-flow list.
+    pub fn cash_flow_measure(&mut self, measure: CashFlowMeasure) {
+        let ctx = Context::new(self.options());
+        let settings = &self.finance;
+        let result = solver::parse_coefficients(&settings.cash_flows, &self.locale, &ctx).and_then(|flows| match measure {
+            CashFlowMeasure::Npv => Ok(finance::npv(settings.discount_rate, &flows)),
+            CashFlowMeasure::Irr => finance::irr(&flows),
+            CashFlowMeasure::Mirr => finance::mirr(&flows, settings.finance_rate, settings.reinvest_rate),
+        });
+        let label = match measure {
+            CashFlowMeasure::Npv => format!("NPV at {}%", self.fmt(settings.discount_rate)),
+            _ => format!("{} %", measure.label()),
+        };
+        match result {
+            Ok(value) => self.push_result(&label, value),
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Builds the depreciation schedule; the display shows the first
+    /// year's expense.
+    pub fn depreciate(&mut self) {
+        let settings = &self.finance;
+        let method = settings.depreciation;
+        match method.schedule(settings.cost, settings.salvage, settings.life, settings.declining_factor) {
+            Ok(rows) => {
+                self.finance_table = Some(FinanceTable::depreciation(method, &rows));
+                self.push_result(&format!("{}, year 1", method.label()), rows[0].0);
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Compound interest on the principal; the notice gives the effective
+    /// annual rate.
+    pub fn compound_interest(&mut self) {
+        let settings = &self.finance;
+        let (value, effective) =
+            finance::compound(settings.principal, settings.annual_rate, settings.compounding, settings.years);
+        if value.is_finite() {
+            let label = format!("{} at {}% for {} years", self.fmt(settings.principal), self.fmt(settings.annual_rate), self.fmt(settings.years));
+            self.push_result(&label, value);
+            self.notice = Some(format!("Effective annual rate {}%", self.fmt(effective)));
+        } else {
+            self.fail("Result is out of range".to_string());
+        }
+    }
+
+    pub fn get_finance_table(&self) -> Option<&FinanceTable> {
+        self.finance_table.as_ref()
+    }
+
+    pub fn close_finance_table(&mut self) {
+        self.finance_table = None;
+    }
+
+    /// The shown amortization or depreciation table as CSV.
+    pub fn finance_table_csv(&self) -> Option<String> {
+        self.finance_table.as_ref().map(|table| table.to_csv(&self.locale))
+    }
+
+    /// Switches the keypad mode; this also leaves the graph tab.
+    pub fn set_mode(&mut self, mode: CalculatorMode) {
+        self.mode = mode;
+        self.show_graph = false;
+    }
+
+    /// The current operand as a real number, or an error for complex values.
+    fn real_operand(&mut self, purpose: &str) -> Option<f64> {
+        let value = self.operand.value()?;
+        if value.is_real() {
+            Some(value.re)
+        } else {
+            self.error_message = Some(format!("{} must be real", purpose));
+            None
+        }
+    }
+
+    /// Σ+ key: appends the current operand to the statistics data set, as
+    /// the y value when an x value is pending from the x,y key.
+    pub fn data_add(&mut self) {
+        let Some(value) = self.real_operand("Statistics data") else {
+            return;
+        };
+        let point = match self.pending_x.take() {
+            Some(x) => DataPoint { x, y: Some(value) },
+            None => DataPoint { x: value, y: None },
+        };
+        self.data.push(point);
+        self.operand = Operand::Entry(NumberEntry::default());
+        self.error_message = None;
+        self.notice = None;
+    }
+
+    /// x,y key: holds the current operand as the x value of a pair.
+    pub fn data_pair(&mut self) {
+        let Some(x) = self.real_operand("Statistics data") else {
+            return;
+        };
+        self.pending_x = Some(x);
+        self.operand = Operand::Entry(NumberEntry::default());
+        self.notice = Some(format!("x = {}; enter y, then Σ+", self.fmt(x)));
+    }
+
+    pub fn data_remove(&mut self, index: usize) {
+        if index < self.data.len() {
+            self.data.remove(index);
+        }
+    }
+
+    pub fn data_set(&mut self, index: usize, point: DataPoint) {
+        if let Some(slot) = self.data.get_mut(index) {
+            *slot = point;
+        }
+    }
+
+    pub fn data_clear(&mut self) {
+        self.data.clear();
+        self.pending_x = None;
+    }
+
+    /// Adds the data found in `text` (CSV, one value or x,y pair per line,
+    /// ...) to the data set and returns how many points were added.
+    pub fn data_import(&mut self, text: &str) -> usize {
+        let points = statistics::parse_points(text, &self.locale);
+        self.data.extend_from_slice(&points);
+        points.len()
+    }
+
+    /// Text typed or pasted into the CSV import box, while it is open.
+    pub fn import_text_mut(&mut self) -> Option<&mut String> {
+        self.import_text.as_mut()
+    }
+
+    pub fn toggle_import(&mut self) {
+        self.import_text = match self.import_text {
+            Some(_) => None,
+            None => Some(String::new()),
+        };
+    }
+
+    /// Imports the contents of the CSV import box and closes it.
+    pub fn finish_import(&mut self) {
+        if let Some(text) = self.import_text.take() {
+            let count = self.data_import(&text);
+            self.notice = Some(format!("Imported {} data points", count));
+        }
+    }
+
+    pub fn get_data(&self) -> &[DataPoint] {
+        &self.data
+    }
+
+    /// Computes a statistic over the x values and pushes it into the display.
+    /// `Percentile` takes its rank from the current operand.
+    pub fn push_statistic(&mut self, stat: Statistic) {
+        let stat = match stat {
+            Statistic::Percentile(_) => match self.real_operand("Percentile rank") {
+                Some(rank) => Statistic::Percentile(rank),
+                None => return,
+            },
+            other => other,
+        };
+        let xs: Vec<f64> = self.data.iter().map(|point| point.x).collect();
+        match statistics::compute(stat, &xs) {
+            Ok(result) => self.push_result(&stat.label(), result),
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Records `label = value` in the history and shows the value.
+    pub fn push_result(&mut self, label: &str, value: f64) {
+        self.add_to_history(&format!("{} = {}", label, self.fmt(value)));
+        self.enter_value(Complex::from(value));
+    }
+
+    pub fn get_regression_model(&self) -> RegressionModel {
+        self.regression_model
+    }
+
+    pub fn set_regression_model(&mut self, model: RegressionModel) {
+        self.regression_model = model;
+    }
+
+    /// Fits the selected regression model to the paired data.
+    pub fn get_fit(&self) -> Result<Fit, String> {
+        let pairs: Option<Vec<(f64, f64)>> = self
+            .data
+            .iter()
+            .map(|point| point.y.map(|y| (point.x, y)))
+            .collect();
+        match pairs {
+            Some(pairs) => regression::fit(self.regression_model, &pairs),
+            None => Err("Regression needs an (x, y) pair for every data point".to_string()),
+        }
+    }
+
+    /// ŷ key: predicts y for the displayed x with the current fit.
+    pub fn push_prediction(&mut self) {
+        let Some(x) = self.real_operand("x") else {
+            return;
+        };
+        match self.get_fit() {
+            Ok(fit) => {
+                let label = format!("ŷ({})", self.fmt(x));
+                self.push_result(&label, fit.predict(x));
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Pushes a coefficient (0 = a, 1 = b, ...) or r of the current fit.
+    pub fn push_fit_value(&mut self, label: &str, pick: impl Fn(&Fit) -> Option<f64>) {
+        match self.get_fit() {
+            Ok(fit) => match pick(&fit) {
+                Some(value) => self.push_result(label, value),
+                None => self.fail(format!("{} is not defined for this model", label)),
+            },
+            Err(err) => self.fail(err),
+        }
+    }
+
+    pub fn memory_clear(&mut self) {
+        self.memory = Complex::ZERO;
+    }
+
+    pub fn memory_recall(&mut self) {
+        if !self.is_error() {
+            self.enter_value(self.memory);
+        }
+    }
+
+    pub fn memory_store(&mut self) {
+        if let Some(value) = self.operand.value() {
+            self.memory = value;
+        }
+    }
+
+    pub fn memory_add(&mut self) {
+        if let Some(value) = self.operand.value() {
+            self.memory = self.memory + value;
+        }
+    }
+
+    pub fn memory_subtract(&mut self) {
+        if let Some(value) = self.operand.value() {
+            self.memory = self.memory - value;
+        }
+    }
+
+    /// Formats a value with the configured display format and locale.
+    fn fmt(&self, value: f64) -> String {
+        self.locale.localize(&format_with(value, self.display_format))
+    }
+
+    /// Formats a real number with the current display format and locale.
+    pub fn format_number(&self, value: f64) -> String {
+        self.fmt(value)
+    }
+
+    /// A vector as `[1, 2, 3]`, with `;` between components under a
+    /// decimal comma.
+    fn fmt_vector(&self, components: &[f64]) -> String {
+        let separator = if self.locale.decimal_separator == ',' { "; " } else { ", " };
+        let components: Vec<String> = components.iter().map(|&x| self.fmt(x)).collect();
+        format!("[{}]", components.join(separator))
+    }
+
+    fn fmt_result(&self, value: &Value) -> String {
+        match value {
+            Value::Scalar(x) => self.fmt(*x),
+            Value::Vector(components) => self.fmt_vector(components),
+            Value::Quantity(quantity) => format!("{} {}", self.fmt(quantity.shown_value()), quantity.unit_text()),
+            Value::Measured(measured) if measured.precision == Precision::Exact => self.fmt(measured.value),
+            Value::Measured(measured) => self.locale.localize(&measured.to_string()),
+        }
+    }
+
+    /// Formats a possibly complex value with the current display settings.
+    pub fn format_value(&self, value: Complex) -> String {
+        self.fmt_value(value)
+    }
+
+    /// Formats a possibly complex value in rectangular or polar form.
+    fn fmt_value(&self, value: Complex) -> String {
+        self.format_complex(value, |x| self.fmt(x))
+    }
+
+    fn format_complex(&self, value: Complex, fmt: impl Fn(f64) -> String) -> String {
+        if value.is_real() {
+            return fmt(value.re);
+        }
+        match self.complex_form {
+            ComplexForm::Rectangular if value.re == 0.0 => format!("{}i", fmt(value.im)),
+            ComplexForm::Rectangular => {
+                let sign = if value.im.is_sign_negative() { '−' } else { '+' };
+                format!("{} {} {}i", fmt(value.re), sign, fmt(value.im.abs()))
+            }
+            ComplexForm::Polar => format!(
+                "{}∠{}{}",
+                fmt(value.abs()),
+                fmt(self.angle_unit.radians_to_unit(value.arg())),
+                self.angle_unit.suffix()
+            ),
+        }
+    }
+
+    /// Records an IEEE condition as a display notice and returns the
+    /// annotation to append to the history entry.
+    fn note_condition(&mut self, condition: Option<Condition>) -> String {
+        match condition {
+            Some(condition) => {
+                self.notice = Some(format!("IEEE-754 {}", condition.description()));
+                format!(" ({})", condition.description())
+            }
+            None => {
+                self.notice = None;
+                String::new()
+            }
+        }
+    }
+
+    fn add_to_history(&mut self, entry: &str) {
+        self.history.push_front(entry.to_string());
+        if self.history.len() > MAX_HISTORY_SIZE {
+            self.history.pop_back();
+        }
+    }
+
+    /// Text of the current expression, or the last completed calculation
+    /// when a result is shown.
+    pub fn get_expression(&self) -> String {
+        match self.history.front() {
+            Some(entry) if self.is_showing_result() => entry.clone(),
+            _ => self.get_display(),
+        }
+    }
+
+    /// Pastes a number or an expression into the current entry. Plain
+    /// numbers may carry grouping, currency symbols or an exponent, and
+    /// may be written as `12°34'56"` or `1 3/4`; anything else is
+    /// evaluated as an expression.
+    pub fn paste(&mut self, text: &str) {
+        let text = text.trim();
+        let structured = notation::parse(&text.replace(self.locale.decimal_separator, "."));
+        let number = structured.or_else(|| self.locale.parse_lenient(text));
+        // A pasted list becomes statistics data; a single number or
+        // expression such as `1,234` or `1e5 + 2` goes to the entry line
+        let is_list = text.lines().count() > 1
+            || (number.is_none() && expression::parse(text, &self.locale).is_err());
+        if self.mode == CalculatorMode::Statistics
+            && is_list
+            && statistics::parse_points(text, &self.locale).len() > 1
+        {
+            self.data_import(text);
+            return;
+        }
+        if let Some(value) = number {
+            self.enter_value(Complex::from(value));
+            return;
+        }
+        
+        let ctx = Context::new(self.options()).with_currencies(self.currency.pairs());
+        match expression::evaluate_value(text, &self.locale, &ctx) {
+            Ok(value) => {
+                let exact = self.exact_integer(text, &value, &ctx);
+                let shown = exact.clone().unwrap_or_else(|| self.fmt_result(&value));
+                self.add_to_history(&format!("{} = {}", text, shown));
+                self.enter_result(value);
+                let words = text.split(|c: char| !c.is_ascii_alphabetic());
+                self.notice = self
+                    .currency
+                    .staleness_warning(words, dates::now())
+                    .or(exact.map(|digits| format!("Exact: {}", digits)));
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Cannot paste: {}", err));
+            }
+        }
+    }
+
+    /// The digits of an integer result too large for the display to show
+    /// exactly, when the expression can be evaluated in big integers.
+    fn exact_integer(&self, text: &str, value: &Value, ctx: &Context) -> Option<String> {
+        let Value::Scalar(x) = *value else {
+            return None;
+        };
+        if x.is_nan() || integer::from_float(x.trunc()).is_ok() {
+            return None;
+        }
+        let n = integer::exact(&expression::parse(text, &self.locale).ok()?, ctx).ok()?;
+        Some(self.locale.localize(&n.to_string()))
+    }
+
+    /// Replaces the current operand with `value` as a completed number.
+    fn enter_value(&mut self, value: Complex) {
+        self.enter_operand(Operand::Value(value));
+    }
+
+    /// Shows an expression result, which may be a vector or carry units.
+    fn enter_result(&mut self, value: Value) {
+        match value {
+            Value::Scalar(x) => self.enter_value(Complex::from(x)),
+            value => self.enter_operand(Operand::Compound(value)),
+        }
+    }
+
+    fn enter_operand(&mut self, operand: Operand) {
+        if matches!(self.operand, Operand::Error) {
+            self.operation = None;
+        }
+        self.in_equation = self.operation.is_some();
+        self.operand = operand;
+        self.error_message = None;
+        self.notice = None;
+    }
+
+    fn is_showing_result(&self) -> bool {
+        matches!(self.operand, Operand::Value(_) | Operand::Compound(_)) && self.operation.is_none()
+    }
+
+    /// History entries as plain text, most recent last.
+    pub fn export_history(&self) -> String {
+        let mut text = String::new();
+        for entry in self.history.iter().rev() {
+            text.push_str(entry);
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.show_history = !self.show_history;
+    }
+
+    /// The display line: the pending operation followed by the operand.
+    pub fn get_display(&self) -> String {
+        let operand = match &self.operand {
+            Operand::Entry(entry) if entry.is_imaginary() => {
+                format!("{}i", self.locale.localize(&entry.text()))
+            }
+            Operand::Entry(entry) => self.locale.localize(&entry.text()),
+            Operand::Value(value) => self.fmt_value(*value),
+            Operand::Compound(value) => self.fmt_result(value),
+            Operand::Error => "Error".to_string(),
+        };
+        let previous = match self.previous_measured {
+            Some(measured) => self.fmt_result(&Value::Measured(measured)),
+            None => self.fmt_value(self.previous_value),
+        };
+        match self.operation {
+            Some(op) if self.in_equation => format!("{}{}{}", previous, op.to_symbol(), operand),
+            Some(op) => format!("{}{}", previous, op.to_symbol()),
+            None => operand,
+        }
+    }
+
+    pub fn get_mode(&self) -> CalculatorMode {
+        self.mode
+    }
+
+    pub fn get_error(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+
+    pub fn get_notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    pub fn get_numeric_policy(&self) -> NumericPolicy {
+        self.numeric_policy
+    }
+
+    pub fn set_numeric_policy(&mut self, policy: NumericPolicy) {
+        self.numeric_policy = policy;
+    }
+
+    pub fn get_precision_mode(&self) -> PrecisionMode {
+        self.precision_mode
+    }
+
+    pub fn set_precision_mode(&mut self, mode: PrecisionMode) {
+        self.precision_mode = mode;
+    }
+
+    pub fn get_display_format(&self) -> DisplayFormat {
+        self.display_format
+    }
+
+    pub fn set_display_format(&mut self, format: DisplayFormat) {
+        self.display_format = format;
+    }
+
+    /// F↔D and DMS↔D keys: switches the display to fractions or to
+    /// degrees, minutes and seconds, and back to the format used before.
+    pub fn toggle_display_format(&mut self, format: DisplayFormat) {
+        if self.display_format.same_kind(format) {
+            self.display_format = self.toggled_format.take().unwrap_or_default();
+            return;
+        }
+        if !matches!(self.display_format, DisplayFormat::Fraction(_) | DisplayFormat::Dms(_)) {
+            self.toggled_format = Some(self.display_format);
+        }
+        self.display_format = format;
+    }
+
+    pub fn get_locale(&self) -> NumberLocale {
+        self.locale
+    }
+
+    pub fn set_locale(&mut self, mut locale: NumberLocale) {
+        // A separator cannot serve both roles
+        if locale.group_separator == locale.decimal_separator {
+            locale.group_separator = if locale.decimal_separator == ',' { '.' } else { ',' };
+        }
+        self.locale = locale;
+    }
+
+    /// The current entry or result at full precision, if it is a number.
+    pub fn get_full_precision(&self) -> Option<String> {
+        self.operand
+            .value()
+            .map(|value| self.format_complex(value, |x| self.locale.localize(&full_precision(x))))
+    }
+
+    /// The memory value in the configured display format.
+    pub fn get_memory_display(&self) -> String {
+        self.fmt_value(self.memory)
+    }
+
+    pub fn get_angle_unit(&self) -> AngleUnit {
+        self.angle_unit
+    }
+
+    pub fn set_angle_unit(&mut self, unit: AngleUnit) {
+        self.angle_unit = unit;
+    }
+
+    pub fn is_complex_mode(&self) -> bool {
+        self.complex_mode
+    }
+
+    pub fn set_complex_mode(&mut self, enabled: bool) {
+        self.complex_mode = enabled;
+    }
+
+    pub fn get_complex_form(&self) -> ComplexForm {
+        self.complex_form
+    }
+
+    pub fn set_complex_form(&mut self, form: ComplexForm) {
+        self.complex_form = form;
+    }
+
+    pub fn get_distribution(&self) -> Distribution {
+        self.distribution
+    }
+
+    pub fn set_distribution(&mut self, distribution: Distribution) {
+        self.distribution = distribution;
+    }
+
+    pub fn toggle_distributions(&mut self) {
+        self.show_distributions = !self.show_distributions;
+    }
+
+    pub fn is_showing_distributions(&self) -> bool {
+        self.show_distributions
+    }
+
+    pub fn solver_mut(&mut self) -> &mut SolverSettings {
+        &mut self.solver
+    }
+
+    pub fn calculus_mut(&mut self) -> &mut CalculusSettings {
+        &mut self.calculus
+    }
+
+    pub fn toggle_calculus(&mut self) {
+        self.show_calculus = !self.show_calculus;
+    }
+
+    pub fn is_showing_calculus(&self) -> bool {
+        self.show_calculus
+    }
+
+    pub fn toggle_integer(&mut self) {
+        self.show_integer = !self.show_integer;
+    }
+
+    pub fn is_showing_integer(&self) -> bool {
+        self.show_integer
+    }
+
+    pub fn toggle_more_functions(&mut self) {
+        self.show_more_functions = !self.show_more_functions;
+    }
+
+    pub fn is_showing_more_functions(&self) -> bool {
+        self.show_more_functions
+    }
+
+    pub fn toggle_solver(&mut self) {
+        self.show_solver = !self.show_solver;
+    }
+
+    pub fn is_showing_solver(&self) -> bool {
+        self.show_solver
+    }
+
+    pub fn get_graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn graph_mut(&mut self) -> &mut Graph {
+        &mut self.graph
+    }
+
+    /// Evaluation context for graphed expressions, with the current angle
+    /// unit and numeric policy.
+    pub fn graph_context(&self) -> Context {
+        Context::new(self.options())
+    }
+
+    pub fn toggle_graph(&mut self) {
+        self.show_graph = !self.show_graph;
+    }
+
+    pub fn is_showing_graph(&self) -> bool {
+        self.show_graph
+    }
+
+    pub fn toggle_settings(&mut self) {
+        self.show_settings = !self.show_settings;
+    }
+
+    pub fn is_showing_settings(&self) -> bool {
+        self.show_settings
+    }
+
+    pub fn has_memory(&self) -> bool {
+        self.memory != Complex::ZERO
+    }
+
+    pub fn get_history(&self) -> &VecDeque<String> {
+        &self.history
+    }
+
+    pub fn is_showing_history(&self) -> bool {
+        self.show_history
+    }
+}
+
+/// History text for a unary operation applied to `operand`.
+fn unary_description(op: Operation, operand: &str) -> String {
+    match op {
+        Operation::SquareRoot => format!("√({})", operand),
+        Operation::Square => format!("({})²", operand),
+        Operation::Reciprocal => format!("1/({})", operand),
+        Operation::Percentage => format!("{}%", operand),
+        Operation::Sin => format!("sin({})", operand),
+        Operation::Cos => format!("cos({})", operand),
+        Operation::Tan => format!("tan({})", operand),
+        Operation::Log => format!("log({})", operand),
+        Operation::Ln => format!("ln({})", operand),
+        Operation::Factorial => format!("{}!", operand),
+        Operation::Abs => format!("|{}|", operand),
+        Operation::Floor
+        | Operation::Ceil
+        | Operation::Round
+        | Operation::Trunc
+        | Operation::Sign
+        | Operation::FractionalPart => format!("{}({})", op.to_symbol(), operand),
+        Operation::Random => "rand".to_string(),
+        _ => format!("{:?}({})", op, operand),
+    }
+}
+
+impl eframe::App for CalcsPlus {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        ui::draw_calculator(ctx, self);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, RATE_TABLE_KEY, &self.currency);
+        eframe::set_value(storage, BUSINESS_KEY, &self.business);
+        eframe::set_value(storage, TAPE_KEY, &self.tape);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    #[test]
+    fn exchange_rates_survive_a_restart() {
+        let day = dates::SECONDS_PER_DAY;
+        let now = 1_714_521_600; // 2024-05-01
+        let mut app = CalcsPlus::default();
+        app.rate_table_mut().base = "EUR".to_string();
+        app.rate_table_mut().stale_after_days = 3;
+        app.rate_table_mut().imported = Some(now - 10 * day);
+        app.rate_table_mut().rates = vec![
+            Rate { code: "USD".to_string(), per_base: 1.07, updated: now - 10 * day },
+            Rate { code: "GBP".to_string(), per_base: 0.86, updated: now - day },
+        ];
+
+        let mut storage = MemoryStorage::default();
+        eframe::App::save(&mut app, &mut storage);
+        let restored = CalcsPlus::restore(Some(&storage));
+
+        assert_eq!(restored.get_rate_table(), app.get_rate_table());
+        let table = restored.get_rate_table();
+        assert_eq!(table.convert(107.0, "USD", "GBP").unwrap(), 107.0 / 1.07 * 0.86);
+        // The update times come back too, so old rates are still flagged
+        assert_eq!(table.stale_age("USD", now), Some(10));
+        assert_eq!(table.stale_age("GBP", now), None);
+    }
+
+    #[test]
+    fn statistics_paste_keeps_single_numbers_on_the_entry_line() {
+        let mut app = CalcsPlus::default();
+        app.set_mode(CalculatorMode::Statistics);
+        let points = app.get_data().len();
+        let singles = [("1,234", "1234"), ("1,234,567", "1234567"), ("$1,234.50", "1234.5"), ("1e5 + 2", "100002")];
+        for (text, value) in singles {
+            app.paste(text);
+            assert_eq!(app.get_data().len(), points, "{}", text);
+            assert_eq!(app.get_display(), value, "{}", text);
+        }
+        app.paste("1, 2, 3");
+        assert_eq!(app.get_data().len(), points + 3);
+        app.paste("4\n5");
+        assert_eq!(app.get_data().len(), points + 5);
+    }
+
+    #[test]
+    fn tax_and_discount_rates_survive_a_restart() {
+        let mut app = CalcsPlus::default();
+        app.business_mut().add_tax_rate();
+        app.business_mut().tax_rates[1].rate = 19.0;
+        app.business_mut().discount_rate = 15.0;
+
+        let mut storage = MemoryStorage::default();
+        eframe::App::save(&mut app, &mut storage);
+        let restored = CalcsPlus::restore(Some(&storage));
+
+        assert_eq!(restored.get_business(), app.get_business());
+        assert_eq!(restored.get_business().tax().map(|tax| tax.rate), Some(19.0));
+    }
+
+    #[test]
+    fn tape_survives_a_restart() {
+        let mut app = CalcsPlus::default();
+        app.toggle_tape();
+        app.tape_mut().lines = vec![
+            TapeLine::Start(120.0),
+            TapeLine::Step { op: Operation::Add, value: 30.0, percent: false },
+            TapeLine::Total,
+            TapeLine::Subtotal,
+        ];
+
+        let mut storage = MemoryStorage::default();
+        eframe::App::save(&mut app, &mut storage);
+        let restored = CalcsPlus::restore(Some(&storage));
+
+        assert_eq!(restored.get_tape(), app.get_tape());
+        assert!(restored.get_tape().recording);
+        assert_eq!(restored.tape_totals().grand_total, 150.0);
+    }
+
+    #[test]
+    fn subtotal_key_leaves_the_tape_alone_when_not_recording() {
+        let mut app = CalcsPlus::default();
+        app.mark_subtotal();
+        assert!(app.get_tape().lines.is_empty());
+        app.toggle_tape();
+        app.mark_subtotal();
+        assert_eq!(app.get_tape().lines, vec![TapeLine::Subtotal]);
+    }
+
+    #[test]
+    fn missing_storage_starts_with_an_empty_table() {
+        assert_eq!(CalcsPlus::restore(None).get_rate_table(), &RateTable::default());
+        let restored = CalcsPlus::restore(Some(&MemoryStorage::default()));
+        assert_eq!(restored.get_rate_table(), &RateTable::default());
+        assert_eq!(restored.get_business(), &BusinessSettings::default());
+        assert_eq!(restored.get_tape(), &Tape::default());
     }
 }
@@ -0,0 +1,92 @@
+/// A named sales tax rate in percent.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TaxRate {
+    pub name: String,
+    pub rate: f64,
+}
+
+/// Rates used by the TAX+, TAX− and DISC keys, kept with the app state.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct BusinessSettings {
+    pub tax_rates: Vec<TaxRate>,
+    /// Index into `tax_rates` of the rate the tax keys use.
+    pub selected_tax: usize,
+    /// Discount in percent for the DISC key.
+    pub discount_rate: f64,
+}
+
+impl Default for BusinessSettings {
+    fn default() -> Self {
+        Self {
+            tax_rates: vec![TaxRate {
+                name: "Sales tax".to_string(),
+                rate: 8.0,
+            }],
+            selected_tax: 0,
+            discount_rate: 10.0,
+        }
+    }
+}
+
+impl BusinessSettings {
+    pub fn tax(&self) -> Option<&TaxRate> {
+        self.tax_rates.get(self.selected_tax)
+    }
+
+    /// Adds another rate and selects it.
+    pub fn add_tax_rate(&mut self) {
+        self.tax_rates.push(TaxRate {
+            name: format!("Tax {}", self.tax_rates.len() + 1),
+            rate: 0.0,
+        });
+        self.selected_tax = self.tax_rates.len() - 1;
+    }
+
+    pub fn remove_tax_rate(&mut self, index: usize) {
+        if index < self.tax_rates.len() {
+            self.tax_rates.remove(index);
+            if self.selected_tax >= index && self.selected_tax > 0 {
+                self.selected_tax -= 1;
+            }
+        }
+    }
+}
+
+/// The three keys that apply a configured rate to the displayed amount.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateKey {
+    /// Net amount to gross: adds the tax.
+    AddTax,
+    /// Gross amount to net: takes out the tax it includes.
+    RemoveTax,
+    Discount,
+}
+
+impl RateKey {
+    pub fn label(self) -> &'static str {
+        match self {
+            RateKey::AddTax => "TAX+",
+            RateKey::RemoveTax => "TAX−",
+            RateKey::Discount => "DISC",
+        }
+    }
+
+    /// The new amount and the tax or discount in it.
+    pub fn apply(self, amount: f64, rate: f64) -> (f64, f64) {
+        match self {
+            RateKey::AddTax => {
+                let tax = amount * rate / 100.0;
+                (amount + tax, tax)
+            }
+            RateKey::RemoveTax => {
+                let net = amount / (1.0 + rate / 100.0);
+                (net, amount - net)
+            }
+            RateKey::Discount => {
+                let discount = amount * rate / 100.0;
+                (amount - discount, discount)
+            }
+        }
+    }
+}
@@ -9,16 +9,61 @@ pub enum Operation {
     Square,
     Reciprocal,
     Percentage,
+    /// Percent change from the first operand to the second.
+    PercentChange,
+    /// Selling price of a cost (first operand) at a margin in percent.
+    Margin,
     Sin,
     Cos,
     Tan,
     Log,
     Ln,
     Factorial,
+    Floor,
+    Ceil,
+    /// Rounds half away from zero.
+    Round,
+    Trunc,
+    Abs,
+    /// −1, 0 or 1.
+    Sign,
+    /// `x − trunc(x)`, with the sign of x.
+    FractionalPart,
+    /// Rounds the first operand to the second operand's number of
+    /// decimal places; negative places round to tens, hundreds, …
+    RoundDigits,
+    /// Rounds the first operand to the second operand's number of
+    /// significant figures.
+    RoundSignificant,
+    /// Uniform random number in [0, 1); the operand is ignored.
+    Random,
+    /// Random integer between the two operands, inclusive.
+    RandomInteger,
+    /// A measurement: the first operand with the second as its
+    /// uncertainty. On plain numbers only the first operand remains.
+    PlusMinus,
 }
 
 impl Operation {
-    pub fn to_symbol(&self) -> &'static str {
+    /// Operations whose results are rounded operands or random draws.
+    pub fn is_rounding(self) -> bool {
+        matches!(
+            self,
+            Operation::Floor
+                | Operation::Ceil
+                | Operation::Round
+                | Operation::Trunc
+                | Operation::Abs
+                | Operation::Sign
+                | Operation::FractionalPart
+                | Operation::RoundDigits
+                | Operation::RoundSignificant
+                | Operation::Random
+                | Operation::RandomInteger
+        )
+    }
+
+    pub fn to_symbol(self) -> &'static str {
         match self {
             Operation::Add => " + ",
             Operation::Subtract => " - ",
@@ -29,12 +74,26 @@ impl Operation {
             Operation::Square => "²",
             Operation::Reciprocal => "1/",
             Operation::Percentage => "%",
+            Operation::PercentChange => " Δ% ",
+            Operation::Margin => " MU ",
             Operation::Sin => "sin",
             Operation::Cos => "cos",
             Operation::Tan => "tan",
             Operation::Log => "log",
             Operation::Ln => "ln",
             Operation::Factorial => "!",
+            Operation::Floor => "floor",
+            Operation::Ceil => "ceil",
+            Operation::Round => "round",
+            Operation::Trunc => "trunc",
+            Operation::Abs => "abs",
+            Operation::Sign => "sign",
+            Operation::FractionalPart => "frac",
+            Operation::RoundDigits => " rnd ",
+            Operation::RoundSignificant => " sig ",
+            Operation::Random => "rand",
+            Operation::RandomInteger => " randint ",
+            Operation::PlusMinus => " ± ",
         }
     }
 }
@@ -43,9 +102,213 @@ impl Operation {
 pub enum CalculatorMode {
     Standard,
     Scientific,
+    Statistics,
+    Matrix,
+    Units,
+    Dates,
+    Financial,
+}
+
+impl CalculatorMode {
+    pub const ALL: [CalculatorMode; 7] = [
+        CalculatorMode::Standard,
+        CalculatorMode::Scientific,
+        CalculatorMode::Statistics,
+        CalculatorMode::Matrix,
+        CalculatorMode::Units,
+        CalculatorMode::Dates,
+        CalculatorMode::Financial,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CalculatorMode::Standard => "Standard",
+            CalculatorMode::Scientific => "Scientific",
+            CalculatorMode::Statistics => "Statistics",
+            CalculatorMode::Matrix => "Matrix",
+            CalculatorMode::Units => "Units",
+            CalculatorMode::Dates => "Dates",
+            CalculatorMode::Financial => "Financial",
+        }
+    }
+}
+
+/// How results that leave the finite range of `f64` are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum NumericPolicy {
+    /// Overflow, underflow, division by zero and undefined results are errors.
+    #[default]
+    Strict,
+    /// Results follow IEEE-754: signed infinities, NaN and gradual underflow.
+    Ieee,
+}
+
+impl NumericPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            NumericPolicy::Strict => "Strict",
+            NumericPolicy::Ieee => "IEEE-754",
+        }
+    }
+}
+
+/// Unit for trigonometric arguments and polar angles.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+    Gradians,
+}
+
+impl AngleUnit {
+    pub const ALL: [AngleUnit; 3] = [AngleUnit::Degrees, AngleUnit::Radians, AngleUnit::Gradians];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AngleUnit::Degrees => "DEG",
+            AngleUnit::Radians => "RAD",
+            AngleUnit::Gradians => "GRAD",
+        }
+    }
+
+    /// Suffix shown after an angle, e.g. in polar form.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            AngleUnit::Degrees => "°",
+            AngleUnit::Radians => " rad",
+            AngleUnit::Gradians => " grad",
+        }
+    }
+
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleUnit::Degrees => angle.to_radians(),
+            AngleUnit::Radians => angle,
+            AngleUnit::Gradians => angle * std::f64::consts::PI / 200.0,
+        }
+    }
+
+    pub fn radians_to_unit(self, radians: f64) -> f64 {
+        match self {
+            AngleUnit::Degrees => radians.to_degrees(),
+            AngleUnit::Radians => radians,
+            AngleUnit::Gradians => radians * 200.0 / std::f64::consts::PI,
+        }
+    }
+
+    /// A right angle in this unit, when it is exactly representable.
+    fn right_angle(self) -> Option<f64> {
+        match self {
+            AngleUnit::Degrees => Some(90.0),
+            AngleUnit::Radians => None,
+            AngleUnit::Gradians => Some(100.0),
+        }
+    }
+}
+
+/// Settings that affect how `calculate` evaluates an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NumericOptions {
+    pub policy: NumericPolicy,
+    pub angle: AngleUnit,
+}
+
+/// Exceptional conditions raised by a single calculation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    Overflow,
+    Underflow,
+    DivisionByZero,
+    Undefined,
+}
+
+impl Condition {
+    pub fn description(self) -> &'static str {
+        match self {
+            Condition::Overflow => "overflow",
+            Condition::Underflow => "underflow",
+            Condition::DivisionByZero => "division by zero",
+            Condition::Undefined => "undefined",
+        }
+    }
+}
+
+pub fn calculate(
+    op: Operation,
+    val1: f64,
+    val2: Option<f64>,
+    options: NumericOptions,
+) -> Result<f64, String> {
+    let result = evaluate(op, val1, val2, options.angle)?;
+    match (options.policy, condition_of(op, val1, val2, result)) {
+        (NumericPolicy::Strict, Some(condition)) => Err(condition_error(op, condition)),
+        _ => Ok(result),
+    }
 }
 
-pub fn calculate(op: Operation, val1: f64, val2: Option<f64>) -> Result<f64, String> {
+/// Classifies a raw IEEE result. Conditions are only reported when the
+/// operands themselves were finite, so `∞ + 1` is not flagged again.
+pub fn condition_of(op: Operation, val1: f64, val2: Option<f64>, result: f64) -> Option<Condition> {
+    if !val1.is_finite() || val2.is_some_and(|v| !v.is_finite()) {
+        return None;
+    }
+    if result.is_nan() {
+        return Some(Condition::Undefined);
+    }
+    if result.is_infinite() {
+        let pole = match op {
+            Operation::Divide => val2 == Some(0.0),
+            Operation::Reciprocal | Operation::Log | Operation::Ln | Operation::PercentChange => val1 == 0.0,
+            Operation::Margin => val2 == Some(100.0),
+            Operation::Power => val1 == 0.0 && val2.is_some_and(|v| v < 0.0),
+            _ => false,
+        };
+        return Some(if pole { Condition::DivisionByZero } else { Condition::Overflow });
+    }
+    if op.is_rounding() {
+        // A subnormal result is the operand rounded, not an underflow
+        return None;
+    }
+    let exact_is_nonzero = match op {
+        Operation::Multiply => val1 != 0.0 && val2.is_some_and(|v| v != 0.0),
+        Operation::Divide | Operation::Square | Operation::Reciprocal | Operation::Percentage => {
+            val1 != 0.0
+        }
+        Operation::Power => val1 != 0.0,
+        _ => false,
+    };
+    if result.is_subnormal() || (result == 0.0 && exact_is_nonzero) {
+        return Some(Condition::Underflow);
+    }
+    None
+}
+
+pub fn condition_error(op: Operation, condition: Condition) -> String {
+    match (op, condition) {
+        (_, Condition::Overflow) => "Overflow: result is too large to represent".to_string(),
+        (_, Condition::Underflow) => "Underflow: result is too small to represent".to_string(),
+        (Operation::Divide, _) => "Cannot divide by zero".to_string(),
+        (Operation::Reciprocal, _) => "Cannot take reciprocal of zero".to_string(),
+        (Operation::PercentChange, _) => "Percent change from zero is undefined".to_string(),
+        (Operation::Margin, _) => "Margin must be below 100%".to_string(),
+        (Operation::SquareRoot, _) => "Cannot take square root of negative number".to_string(),
+        (Operation::Log, _) => "Cannot take logarithm of non-positive number".to_string(),
+        (Operation::Ln, _) => "Cannot take natural logarithm of non-positive number".to_string(),
+        (Operation::Factorial, _) => {
+            "Factorial is only defined for non-negative integers".to_string()
+        }
+        (Operation::Power, Condition::DivisionByZero) => {
+            "Cannot raise zero to a negative power".to_string()
+        }
+        (Operation::Power, _) => "Power of a negative number to a fractional exponent is undefined".to_string(),
+        (Operation::Tan, _) => "Tangent is undefined at this angle".to_string(),
+        _ => "Result is undefined".to_string(),
+    }
+}
+
+/// Computes the raw IEEE-754 result of an operation.
+fn evaluate(op: Operation, val1: f64, val2: Option<f64>, angle: AngleUnit) -> Result<f64, String> {
     match op {
         Operation::Add => {
             if let Some(v2) = val2 {
@@ -70,11 +333,7 @@ pub fn calculate(op: Operation, val1: f64, val2: Option<f64>) -> Result<f64, Str
         }
         Operation::Divide => {
             if let Some(v2) = val2 {
-                if v2 == 0.0 {
-                    Err("Cannot divide by zero".to_string())
-                } else {
-                    Ok(val1 / v2)
-                }
+                Ok(val1 / v2)
             } else {
                 Err("Division requires two operands".to_string())
             }
@@ -86,67 +345,361 @@ pub fn calculate(op: Operation, val1: f64, val2: Option<f64>) -> Result<f64, Str
                 Err("Power operation requires two operands".to_string())
             }
         }
-        Operation::SquareRoot => {
-            if val1 < 0.0 {
-                Err("Cannot take square root of negative number".to_string())
+        Operation::SquareRoot => Ok(val1.sqrt()),
+        Operation::Square => Ok(val1 * val1),
+        Operation::Reciprocal => Ok(1.0 / val1),
+        Operation::Percentage => Ok(val1 / 100.0),
+        Operation::PercentChange => {
+            if let Some(v2) = val2 {
+                Ok((v2 - val1) / val1 * 100.0)
             } else {
-                Ok(val1.sqrt())
+                Err("Percent change requires two operands".to_string())
             }
         }
-        Operation::Square => Ok(val1 * val1),
-        Operation::Reciprocal => {
-            if val1 == 0.0 {
-                Err("Cannot take reciprocal of zero".to_string())
+        Operation::Margin => {
+            if let Some(v2) = val2 {
+                Ok(val1 / (1.0 - v2 / 100.0))
             } else {
-                Ok(1.0 / val1)
+                Err("Margin requires two operands".to_string())
+            }
+        }
+        Operation::Sin => Ok(angle.to_radians(val1).sin()),
+        Operation::Cos => Ok(angle.to_radians(val1).cos()),
+        Operation::Tan => {
+            // tan(90°) only overflows by rounding; treat odd multiples of a right angle as poles
+            let quarters = angle.right_angle().map(|right| val1 / right);
+            if quarters.is_some_and(|q| q.fract() == 0.0 && q % 2.0 != 0.0) {
+                Ok(f64::NAN)
+            } else {
+                Ok(angle.to_radians(val1).tan())
             }
         }
-        Operation::Percentage => Ok(val1 / 100.0),
-        Operation::Sin => Ok(val1.to_radians().sin()),
-        Operation::Cos => Ok(val1.to_radians().cos()),
-        Operation::Tan => Ok(val1.to_radians().tan()),
         Operation::Log => {
-            if val1 <= 0.0 {
-                Err("Cannot take logarithm of non-positive number".to_string())
+            if val1 == 0.0 {
+                Ok(f64::NEG_INFINITY)
             } else {
                 Ok(val1.log10())
             }
         }
         Operation::Ln => {
-            if val1 <= 0.0 {
-                Err("Cannot take natural logarithm of non-positive number".to_string())
+            if val1 == 0.0 {
+                Ok(f64::NEG_INFINITY)
             } else {
                 Ok(val1.ln())
             }
         }
         Operation::Factorial => {
             if val1 < 0.0 || val1.fract() != 0.0 {
-                Err("Factorial is only defined for non-negative integers".to_string())
+                Ok(f64::NAN)
             } else {
-                let n = val1 as u64;
-                if n > 20 {
-                    Err("Factorial too large to compute".to_string())
-                } else {
-                    Ok(factorial(n) as f64)
-                }
+                Ok(factorial(val1))
             }
         }
+        Operation::Floor => Ok(val1.floor()),
+        Operation::Ceil => Ok(val1.ceil()),
+        Operation::Round => Ok(val1.round()),
+        Operation::Trunc => Ok(val1.trunc()),
+        Operation::Abs => Ok(val1.abs()),
+        Operation::Sign => Ok(if val1 == 0.0 || val1.is_nan() { val1 } else { val1.signum() }),
+        Operation::FractionalPart => Ok(if val1.is_infinite() { 0.0 } else { val1.fract() }),
+        Operation::RoundDigits => {
+            let digits = whole_number(val2, "Decimal places")?;
+            Ok(round_to(val1, digits))
+        }
+        Operation::RoundSignificant => {
+            let figures = whole_number(val2, "Significant figures")?;
+            if figures < 1 {
+                return Err("Round to at least 1 significant figure".to_string());
+            }
+            if val1 == 0.0 || !val1.is_finite() {
+                return Ok(val1);
+            }
+            Ok(round_to(val1, figures - 1 - decimal_exponent(val1)))
+        }
+        Operation::PlusMinus => match val2 {
+            Some(_) => Ok(val1),
+            None => Err("± requires two operands".to_string()),
+        },
+        Operation::Random => Ok(random_unit()),
+        Operation::RandomInteger => {
+            let low = whole_number(Some(val1), "Random integer bounds")?;
+            let high = whole_number(val2, "Random integer bounds")?;
+            let (low, high) = (low.min(high) as f64, low.max(high) as f64);
+            Ok((low + (random_unit() * (high - low + 1.0)).floor()).min(high))
+        }
     }
 }
 
-fn factorial(n: u64) -> u64 {
-    match n {
-        0 | 1 => 1,
-        _ => n * factorial(n - 1),
+/// The second operand of a rounding key as a whole number.
+fn whole_number(value: Option<f64>, name: &str) -> Result<i32, String> {
+    match value {
+        Some(value) if value.fract() == 0.0 && value.abs() <= 1e9 => Ok(value as i32),
+        Some(_) => Err(format!("{} must be a whole number", name)),
+        None => Err(format!("{} requires two operands", name)),
+    }
+}
+
+/// Rounds half away from zero to `digits` decimal places.
+fn round_to(x: f64, digits: i32) -> f64 {
+    if digits > 308 {
+        return x;
+    }
+    if digits < -308 {
+        // 10^309 overflows, and every finite number rounds to zero at that scale
+        return if x.is_finite() { 0.0f64.copysign(x) } else { x };
+    }
+    // Parsed rather than `powi` so that the scale is the nearest double
+    let scale: f64 = format!("1e{}", digits.abs()).parse().unwrap_or(f64::INFINITY);
+    if digits >= 0 {
+        let scaled = x * scale;
+        if scaled.is_finite() { scaled.round() / scale } else { x }
+    } else {
+        (x / scale).round() * scale
+    }
+}
+
+thread_local! {
+    /// SplitMix64 state behind the random operations, seeded from the
+    /// clock unless `seed_random` sets it.
+    static RANDOM_STATE: std::cell::Cell<u64> = std::cell::Cell::new(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0x853c_49e6_748f_ea9b, |elapsed| elapsed.as_nanos() as u64),
+    );
+}
+
+/// Restarts the random sequence so that it repeats for the same seed.
+pub fn seed_random(seed: u64) {
+    RANDOM_STATE.with(|state| state.set(seed));
+}
+
+/// Next uniform number in [0, 1) with 53 random bits.
+fn random_unit() -> f64 {
+    let mut z = RANDOM_STATE.with(|state| {
+        let next = state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state.set(next);
+        next
+    });
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// n! in floating point; overflows to infinity above 170!.
+fn factorial(n: f64) -> f64 {
+    if n > 170.0 {
+        return f64::INFINITY;
+    }
+    (2..=n as u64).fold(1.0, |acc, k| acc * k as f64)
+}
+
+/// Lanczos approximation parameters (g = 7, n = 9) shared by the real and
+/// complex gamma functions.
+pub const LANCZOS_G: f64 = 7.0;
+pub const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// Relative accuracy targeted by the series and continued fractions below.
+const SPECIAL_EPSILON: f64 = 1e-16;
+/// Guards the modified Lentz algorithm against division by zero.
+const TINY: f64 = 1e-300;
+const MAX_ITERATIONS: usize = 10_000;
+
+/// ln |Γ(x)| for real x, by the Lanczos approximation with reflection for
+/// x < ½. Infinite at the poles 0, −1, −2, …
+pub fn ln_gamma(x: f64) -> f64 {
+    if x <= 0.0 && x.fract() == 0.0 {
+        return f64::INFINITY;
+    }
+    if x < 0.5 {
+        // Γ(x)Γ(1−x) = π / sin(πx)
+        let sin = (std::f64::consts::PI * x).sin();
+        return (std::f64::consts::PI / sin.abs()).ln() - ln_gamma(1.0 - x);
+    }
+    let z = x - 1.0;
+    let sum = LANCZOS_COEFFICIENTS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(LANCZOS_COEFFICIENTS[0], |acc, (k, &c)| acc + c / (z + k as f64));
+    let t = z + LANCZOS_G + 0.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (z + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Error function erf(x) = 2/√π ∫₀ˣ e^(−t²) dt, via P(½, x²).
+pub fn erf(x: f64) -> f64 {
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        return x;
+    }
+    regularized_gamma_p(0.5, x * x).copysign(x)
+}
+
+/// Complementary error function 1 − erf(x), accurate in the upper tail
+/// where 1 − erf(x) would cancel.
+pub fn erfc(x: f64) -> f64 {
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    if x < 0.0 {
+        1.0 + regularized_gamma_p(0.5, x * x)
+    } else {
+        regularized_gamma_q(0.5, x * x)
+    }
+}
+
+/// Regularized lower incomplete gamma function P(a, x) = γ(a, x) / Γ(a)
+/// for a > 0, x ≥ 0; NaN outside that domain.
+pub fn regularized_gamma_p(a: f64, x: f64) -> f64 {
+    if !(a > 0.0 && x >= 0.0) {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        0.0
+    } else if x < a + 1.0 {
+        gamma_series(a, x)
+    } else {
+        1.0 - gamma_continued_fraction(a, x)
+    }
+}
+
+/// Regularized upper incomplete gamma function Q(a, x) = 1 − P(a, x).
+pub fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+    if !(a > 0.0 && x >= 0.0) {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        1.0
+    } else if x < a + 1.0 {
+        1.0 - gamma_series(a, x)
+    } else {
+        gamma_continued_fraction(a, x)
+    }
+}
+
+/// e^(−x) xᵃ / Γ(a), the common prefactor of the incomplete gamma expansions.
+fn gamma_prefactor(a: f64, x: f64) -> f64 {
+    (a * x.ln() - x - ln_gamma(a)).exp()
+}
+
+/// P(a, x) by its power series; converges quickly for x < a + 1.
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let mut term = 1.0 / a;
+    let mut sum = term;
+    let mut ap = a;
+    for _ in 0..MAX_ITERATIONS {
+        ap += 1.0;
+        term *= x / ap;
+        sum += term;
+        if term.abs() < sum.abs() * SPECIAL_EPSILON {
+            break;
+        }
+    }
+    sum * gamma_prefactor(a, x)
+}
+
+/// Q(a, x) by its continued fraction (modified Lentz); converges quickly
+/// for x ≥ a + 1.
+fn gamma_continued_fraction(a: f64, x: f64) -> f64 {
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..MAX_ITERATIONS {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = b + an / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < SPECIAL_EPSILON {
+            break;
+        }
+    }
+    gamma_prefactor(a, x) * h
+}
+
+/// Regularized incomplete beta function I_x(a, b) for a, b > 0 and
+/// 0 ≤ x ≤ 1; NaN outside that domain.
+pub fn regularized_beta(x: f64, a: f64, b: f64) -> f64 {
+    if !(a > 0.0 && b > 0.0 && (0.0..=1.0).contains(&x)) {
+        return f64::NAN;
+    }
+    if x == 0.0 || x == 1.0 {
+        return x;
+    }
+    let prefactor =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges fastest below the mean; use the
+    // symmetry I_x(a, b) = 1 − I_(1−x)(b, a) above it
+    if x < (a + 1.0) / (a + b + 2.0) {
+        prefactor * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - prefactor * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..MAX_ITERATIONS {
+        let m = m as f64;
+        // Even step
+        let aa = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + aa * d);
+        c = clamp(1.0 + aa / c);
+        h *= d * c;
+        // Odd step
+        let aa = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + aa * d);
+        c = clamp(1.0 + aa / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < SPECIAL_EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// Parses a number as rendered by `format_number`, including the
+/// special values shown under the IEEE policy.
+pub fn parse_number(text: &str) -> Option<f64> {
+    match text.trim() {
+        "∞" => Some(f64::INFINITY),
+        "-∞" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        other => other.parse::<f64>().ok(),
     }
 }
 
 pub fn format_number(num: f64) -> String {
     if num.is_infinite() {
-        return "∞".to_string();
+        return if num > 0.0 { "∞" } else { "-∞" }.to_string();
     }
     if num.is_nan() {
-        return "Error".to_string();
+        return "NaN".to_string();
     }
     
     // Remove trailing zeros and decimal point if not needed
@@ -160,3 +713,234 @@ pub fn format_number(num: f64) -> String {
         trimmed.to_string()
     }
 }
+
+/// How numeric results are rendered in the display, history and memory.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum DisplayFormat {
+    /// Up to 10 decimals, switching to scientific notation for extreme magnitudes.
+    #[default]
+    Normal,
+    /// Always the given number of decimals, in scientific notation from 1e15.
+    Fixed(u8),
+    /// Mantissa with the given number of decimals and a power-of-ten exponent.
+    Scientific(u8),
+    /// Like `Scientific`, but the exponent is a multiple of three.
+    Engineering(u8),
+    /// Rounded to the given number of significant figures.
+    Significant(u8),
+    /// Mixed fraction such as `1 3/4`, with denominators of up to the given
+    /// number of digits.
+    Fraction(u8),
+    /// Degrees, minutes and seconds with the given number of decimals on
+    /// the seconds.
+    Dms(u8),
+}
+
+impl DisplayFormat {
+    pub const ALL: [DisplayFormat; 7] = [
+        DisplayFormat::Normal,
+        DisplayFormat::Fixed(2),
+        DisplayFormat::Scientific(6),
+        DisplayFormat::Engineering(3),
+        DisplayFormat::Significant(6),
+        DisplayFormat::Fraction(3),
+        DisplayFormat::Dms(1),
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayFormat::Normal => "Normal",
+            DisplayFormat::Fixed(_) => "Fixed",
+            DisplayFormat::Scientific(_) => "Scientific",
+            DisplayFormat::Engineering(_) => "Engineering",
+            DisplayFormat::Significant(_) => "Significant figures",
+            DisplayFormat::Fraction(_) => "Fraction",
+            DisplayFormat::Dms(_) => "Degrees/minutes/seconds",
+        }
+    }
+
+    pub fn digits(self) -> Option<u8> {
+        match self {
+            DisplayFormat::Normal => None,
+            DisplayFormat::Fixed(n)
+            | DisplayFormat::Scientific(n)
+            | DisplayFormat::Engineering(n)
+            | DisplayFormat::Significant(n)
+            | DisplayFormat::Fraction(n)
+            | DisplayFormat::Dms(n) => Some(n),
+        }
+    }
+
+    pub fn with_digits(self, digits: u8) -> Self {
+        match self {
+            DisplayFormat::Normal => DisplayFormat::Normal,
+            DisplayFormat::Fixed(_) => DisplayFormat::Fixed(digits),
+            DisplayFormat::Scientific(_) => DisplayFormat::Scientific(digits),
+            DisplayFormat::Engineering(_) => DisplayFormat::Engineering(digits),
+            DisplayFormat::Significant(_) => DisplayFormat::Significant(digits.max(1)),
+            DisplayFormat::Fraction(_) => DisplayFormat::Fraction(digits.clamp(1, 9)),
+            DisplayFormat::Dms(_) => DisplayFormat::Dms(digits.min(9)),
+        }
+    }
+
+    pub fn same_kind(self, other: DisplayFormat) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+}
+
+pub fn format_with(num: f64, format: DisplayFormat) -> String {
+    if !num.is_finite() {
+        return format_number(num);
+    }
+    match format {
+        DisplayFormat::Normal => format_number(num),
+        // Beyond the range `format_number` shows in full, the digits would overflow the display
+        DisplayFormat::Fixed(decimals) if num.abs() >= 1e15 => format!("{:.*e}", decimals as usize, num),
+        DisplayFormat::Fixed(decimals) => format!("{:.*}", decimals as usize, num),
+        DisplayFormat::Scientific(decimals) => format!("{:.*e}", decimals as usize, num),
+        DisplayFormat::Engineering(decimals) => format_engineering(num, decimals as usize),
+        DisplayFormat::Significant(figures) => format_significant(num, figures.max(1) as usize),
+        DisplayFormat::Fraction(digits) => crate::notation::format_fraction(num, digits),
+        DisplayFormat::Dms(decimals) => crate::notation::format_dms(num, decimals),
+    }
+}
+
+/// Power of ten of the leading digit, e.g. 2 for 123 and −3 for 0.0012.
+pub fn decimal_exponent(num: f64) -> i32 {
+    let scientific = format!("{:e}", num);
+    scientific
+        .split_once('e')
+        .and_then(|(_, exp)| exp.parse().ok())
+        .unwrap_or(0)
+}
+
+fn format_engineering(num: f64, decimals: usize) -> String {
+    let mut exponent = decimal_exponent(num).div_euclid(3) * 3;
+    let mut mantissa = num / 10f64.powi(exponent);
+    // Rounding can push the mantissa to 1000, e.g. 999.96 with one decimal
+    if format!("{:.*}", decimals, mantissa.abs()).parse::<f64>().unwrap_or(0.0) >= 1000.0 {
+        exponent += 3;
+        mantissa /= 1000.0;
+    }
+    format!("{:.*}e{}", decimals, mantissa, exponent)
+}
+
+fn format_significant(num: f64, figures: usize) -> String {
+    let rounded = format!("{:.*e}", figures - 1, num);
+    let exponent = decimal_exponent(rounded.parse().unwrap_or(num));
+    if num == 0.0 || (-5..15).contains(&exponent) {
+        let decimals = (figures as i32 - 1 - exponent).max(0) as usize;
+        format!("{:.*}", decimals, rounded.parse::<f64>().unwrap_or(num))
+    } else {
+        rounded
+    }
+}
+
+/// Shortest representation that round-trips to the same `f64`.
+pub fn full_precision(num: f64) -> String {
+    if !num.is_finite() {
+        return format_number(num);
+    }
+    let repr = format!("{:?}", num);
+    repr.strip_suffix(".0").unwrap_or(&repr).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts `actual` agrees with `expected` to a relative tolerance.
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        let error = ((actual - expected) / expected).abs();
+        assert!(error <= tolerance, "{} vs {}: relative error {:e}", actual, expected, error);
+    }
+
+    // Abramowitz & Stegun table 7.1 and DLMF 7.2
+    #[test]
+    fn erf_matches_reference_table() {
+        let table = [
+            (0.1, 0.112_462_916_018_284_9),
+            (0.5, 0.520_499_877_813_046_5),
+            (1.0, 0.842_700_792_949_714_9),
+            (1.5, 0.966_105_146_475_310_7),
+            (2.0, 0.995_322_265_018_952_7),
+            (3.0, 0.999_977_909_503_001_4),
+        ];
+        for (x, expected) in table {
+            assert_close(erf(x), expected, 1e-14);
+            assert_close(erf(-x), -expected, 1e-14);
+        }
+        assert_eq!(erf(0.0), 0.0);
+    }
+
+    #[test]
+    fn erfc_keeps_relative_accuracy_in_the_tail() {
+        let table = [
+            (1.0, 0.157_299_207_050_285_1),
+            (3.0, 2.209_049_699_858_544e-5),
+            (5.0, 1.537_459_794_428_035e-12),
+            (10.0, 2.088_487_583_762_545e-45),
+        ];
+        for (x, expected) in table {
+            assert_close(erfc(x), expected, 1e-13);
+        }
+        assert_close(erfc(-1.0), 1.842_700_792_949_715, 1e-15);
+    }
+
+    #[test]
+    fn ln_gamma_matches_factorials_and_half_integers() {
+        assert_close(ln_gamma(0.5), 0.572_364_942_924_700_1, 1e-14);
+        assert_close(ln_gamma(10.0), 362_880f64.ln(), 1e-14);
+        assert_close(ln_gamma(171.0), 706.573_062_245_787_4, 1e-14);
+        assert_close(ln_gamma(-0.5), (2.0 * std::f64::consts::PI.sqrt()).ln(), 1e-14);
+        assert!(ln_gamma(-2.0).is_infinite());
+    }
+
+    #[test]
+    fn incomplete_gamma_matches_closed_forms() {
+        // P(1, x) = 1 − e^(−x); P(n, x) = 1 − e^(−x) Σ_{k<n} x^k / k!
+        for x in [0.1, 1.0, 5.0, 30.0] {
+            assert_close(regularized_gamma_p(1.0, x), 1.0 - (-x).exp(), 1e-14);
+            assert_close(regularized_gamma_q(1.0, x), (-x).exp(), 1e-13);
+        }
+        assert_close(regularized_gamma_p(3.0, 2.0), 1.0 - 5.0 * (-2.0f64).exp(), 1e-14);
+        let q = (-10.0f64).exp() * (1.0 + 10.0 + 50.0 + 1000.0 / 6.0 + 10000.0 / 24.0);
+        assert_close(regularized_gamma_q(5.0, 10.0), q, 1e-13);
+        assert_eq!(regularized_gamma_p(2.0, 0.0), 0.0);
+        assert!(regularized_gamma_p(-1.0, 1.0).is_nan());
+    }
+
+    #[test]
+    fn incomplete_beta_matches_closed_forms() {
+        // I_x(1, b) = 1 − (1 − x)^b, I_x(a, 1) = x^a, I_½(a, a) = ½
+        assert_close(regularized_beta(0.3, 1.0, 4.0), 1.0 - 0.7f64.powi(4), 1e-14);
+        assert_close(regularized_beta(0.3, 2.5, 1.0), 0.3f64.powf(2.5), 1e-14);
+        assert_close(regularized_beta(0.5, 7.5, 7.5), 0.5, 1e-14);
+        // Binomial tail: I_0.3(2, 3) = P(X ≥ 2), X ~ B(4, 0.3)
+        assert_close(regularized_beta(0.3, 2.0, 3.0), 0.3483, 1e-13);
+        assert_eq!(regularized_beta(1.0, 2.0, 3.0), 1.0);
+        assert!(regularized_beta(1.5, 2.0, 3.0).is_nan());
+    }
+
+    #[test]
+    fn fixed_format_switches_to_scientific_for_huge_numbers() {
+        assert_eq!(format_with(1234.5678, DisplayFormat::Fixed(2)), "1234.57");
+        assert_eq!(format_with(-1e14, DisplayFormat::Fixed(1)), "-100000000000000.0");
+        assert_eq!(format_with(1e300, DisplayFormat::Fixed(2)), "1.00e300");
+        assert_eq!(format_with(-2.5e15, DisplayFormat::Fixed(3)), "-2.500e15");
+    }
+
+    #[test]
+    fn rounding_to_digits_stays_defined_at_extreme_scales() {
+        let round = |x: f64, digits: f64| calculate(Operation::RoundDigits, x, Some(digits), NumericOptions::default());
+        assert_eq!(round(1234.5678, 2.0), Ok(1234.57));
+        assert_eq!(round(1234.5678, -2.0), Ok(1200.0));
+        assert_eq!(round(-2.5, 0.0), Ok(-3.0));
+        assert_eq!(round(1.2e308, -308.0), Ok(1e308));
+        // 10^309 is beyond f64, but the rounded value is still zero
+        assert_eq!(round(1.7e308, -309.0), Ok(0.0));
+        let negative = round(-12.0, -400.0).unwrap();
+        assert!(negative == 0.0 && negative.is_sign_negative());
+        assert_eq!(round(5e-324, 400.0), Ok(5e-324));
+    }
+}
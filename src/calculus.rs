@@ -0,0 +1,388 @@
+use crate::calculator::full_precision;
+use crate::expression::{Context, Expr};
+
+/// Most intervals the adaptive integrator may split the range into.
+const MAX_INTERVALS: usize = 500;
+/// Most terms of a Σ or Π.
+const MAX_TERMS: i64 = 1_000_000;
+
+/// Numerical calculus operations on an expression in one bound variable,
+/// available in scientific mode and as expression functions such as
+/// `integral(sin(x), x, 0, pi)`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Calculus {
+    /// d/dx f(x) at a point.
+    #[default]
+    Derivative,
+    /// Definite integral over [a, b].
+    Integral,
+    /// Σ over an integer range.
+    Sum,
+    /// Π over an integer range.
+    Product,
+    /// Two-sided limit as the variable approaches a point.
+    Limit,
+}
+
+impl Calculus {
+    pub const ALL: [Calculus; 5] = [
+        Calculus::Derivative,
+        Calculus::Integral,
+        Calculus::Sum,
+        Calculus::Product,
+        Calculus::Limit,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Calculus::Derivative => "d/dx",
+            Calculus::Integral => "∫",
+            Calculus::Sum => "Σ",
+            Calculus::Product => "Π",
+            Calculus::Limit => "lim",
+        }
+    }
+
+    /// Expression function name, e.g. `diff(x^2, x, 3)`.
+    pub fn function_name(self) -> &'static str {
+        match self {
+            Calculus::Derivative => "diff",
+            Calculus::Integral => "integral",
+            Calculus::Sum => "sum",
+            Calculus::Product => "product",
+            Calculus::Limit => "limit",
+        }
+    }
+
+    pub fn lookup(name: &str) -> Option<Calculus> {
+        match name {
+            "diff" | "derivative" => Some(Calculus::Derivative),
+            "integral" | "int" => Some(Calculus::Integral),
+            "sum" => Some(Calculus::Sum),
+            "product" | "prod" => Some(Calculus::Product),
+            "limit" | "lim" => Some(Calculus::Limit),
+            _ => None,
+        }
+    }
+
+    /// Number of bounds after the variable: a point, or a range.
+    pub fn bounds(self) -> usize {
+        match self {
+            Calculus::Derivative | Calculus::Limit => 1,
+            Calculus::Integral | Calculus::Sum | Calculus::Product => 2,
+        }
+    }
+
+    /// Evaluates `body` with `variable` bound as the operation requires.
+    pub fn evaluate(self, body: &Expr, variable: &str, bounds: &[f64], ctx: &Context) -> Result<Estimate, String> {
+        if bounds.len() != self.bounds() {
+            let usage = if self.bounds() == 1 { "a point" } else { "two bounds" };
+            return Err(format!("{} takes an expression, a variable and {}", self.function_name(), usage));
+        }
+        if bounds.iter().any(|bound| !bound.is_finite()) {
+            return Err(format!("{} bounds must be finite", self.symbol()));
+        }
+        let mut ctx = ctx.clone();
+        let mut f = |x: f64| {
+            ctx.set_variable(variable, x);
+            let value = body.eval(&ctx)?;
+            if value.is_finite() {
+                Ok(value)
+            } else {
+                Err(format!("The expression is undefined at {} = {}", variable, full_precision(x)))
+            }
+        };
+        match self {
+            Calculus::Derivative => derivative(&mut f, bounds[0]),
+            Calculus::Integral => integral(&mut f, bounds[0], bounds[1]),
+            Calculus::Sum => series(&mut f, bounds[0], bounds[1], false),
+            Calculus::Product => series(&mut f, bounds[0], bounds[1], true),
+            Calculus::Limit => limit(&mut f, bounds[0]),
+        }
+    }
+}
+
+/// A numerical result with an estimate of its absolute error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub error: f64,
+}
+
+/// Inputs of the calculus panel, kept with the app state. Bounds are
+/// expressions so that `pi` or `2e` may be entered.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CalculusSettings {
+    pub operation: Calculus,
+    pub expression: String,
+    pub variable: String,
+    pub lower: String,
+    pub upper: String,
+}
+
+impl Default for CalculusSettings {
+    fn default() -> Self {
+        Self {
+            operation: Calculus::default(),
+            expression: String::new(),
+            variable: "x".to_string(),
+            lower: "0".to_string(),
+            upper: "1".to_string(),
+        }
+    }
+}
+
+type Integrand<'a> = dyn FnMut(f64) -> Result<f64, String> + 'a;
+
+/// Central difference refined by Richardson extrapolation (Ridders' method).
+fn derivative(f: &mut Integrand, a: f64) -> Result<Estimate, String> {
+    let step = 0.1 * a.abs().max(1.0);
+    let estimate = extrapolate(|h| Ok((f(a + h)? - f(a - h)?) / (2.0 * h)), step, 2)?;
+    if estimate.error.is_nan() || estimate.error > 1e-6 * estimate.value.abs().max(1.0) {
+        return Err(format!("The derivative at {} did not converge; f may not be differentiable there", full_precision(a)));
+    }
+    Ok(estimate)
+}
+
+/// Two-sided limit from one-sided extrapolations toward `a`.
+fn limit(f: &mut Integrand, a: f64) -> Result<Estimate, String> {
+    let step = 0.1 * a.abs().max(1.0);
+    let left = extrapolate(|h| f(a - h), step, 1)?;
+    let right = extrapolate(|h| f(a + h), step, 1)?;
+    let scale = left.value.abs().max(right.value.abs()).max(1.0);
+    if left.error.is_nan() || right.error.is_nan() || left.error.max(right.error) > 1e-6 * scale {
+        return Err(format!("The limit at {} does not converge; it may be infinite", full_precision(a)));
+    }
+    if (left.value - right.value).abs() > 1e-6 * scale {
+        return Err(format!(
+            "The left and right limits differ ({} and {})",
+            full_precision(left.value),
+            full_precision(right.value)
+        ));
+    }
+    Ok(Estimate {
+        value: (left.value + right.value) / 2.0,
+        error: left.error.max(right.error).max((left.value - right.value).abs() / 2.0),
+    })
+}
+
+/// Extrapolates `g(h)` to h = 0 from steps shrinking by a constant factor,
+/// assuming an error series in powers of h^`order`. A Neville table keeps
+/// the entry with the smallest estimated error and stops once higher
+/// orders make it worse.
+fn extrapolate(mut g: impl FnMut(f64) -> Result<f64, String>, mut h: f64, order: i32) -> Result<Estimate, String> {
+    const SHRINK: f64 = 1.4;
+    const STEPS: usize = 10;
+    let ratio = SHRINK.powi(order);
+    let mut table = [[0.0; STEPS]; STEPS];
+    table[0][0] = g(h)?;
+    let mut best = Estimate { value: table[0][0], error: f64::INFINITY };
+    for i in 1..STEPS {
+        h /= SHRINK;
+        table[0][i] = g(h)?;
+        let mut factor = ratio;
+        for j in 1..=i {
+            table[j][i] = (table[j - 1][i] * factor - table[j - 1][i - 1]) / (factor - 1.0);
+            factor *= ratio;
+            let error = (table[j][i] - table[j - 1][i]).abs().max((table[j][i] - table[j - 1][i - 1]).abs());
+            if error <= best.error {
+                best = Estimate { value: table[j][i], error };
+            }
+        }
+        if (table[i][i] - table[i - 1][i - 1]).abs() >= 2.0 * best.error {
+            break;
+        }
+    }
+    Ok(best)
+}
+
+// 15-point Kronrod nodes on [0, 1] (symmetric about 0) with their weights;
+// every other node is also a 7-point Gauss node.
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_22,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_2,
+    0.140_653_259_715_525_9,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_8,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+/// Adaptive Gauss–Kronrod (G7/K15) quadrature: the interval with the
+/// largest error estimate is bisected until the total is within tolerance.
+fn integral(f: &mut Integrand, a: f64, b: f64) -> Result<Estimate, String> {
+    if a == b {
+        return Ok(Estimate { value: 0.0, error: 0.0 });
+    }
+    if a > b {
+        let estimate = integral(f, b, a)?;
+        return Ok(Estimate { value: -estimate.value, error: estimate.error });
+    }
+    let mut intervals = vec![kronrod(f, a, b)?];
+    loop {
+        let value: f64 = intervals.iter().map(|interval| interval.2.value).sum();
+        let error: f64 = intervals.iter().map(|interval| interval.2.error).sum();
+        if error <= 1e-12_f64.max(1e-10 * value.abs()) {
+            return Ok(Estimate { value, error });
+        }
+        if intervals.len() >= MAX_INTERVALS {
+            return Err(format!(
+                "The integral did not converge: estimated error {:.1e} after {} subdivisions",
+                error, MAX_INTERVALS
+            ));
+        }
+        let worst = intervals
+            .iter()
+            .enumerate()
+            .max_by(|x, y| x.1 .2.error.total_cmp(&y.1 .2.error))
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+        let (lo, hi, _) = intervals.swap_remove(worst);
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            return Err(format!("The integral did not converge near x = {}", full_precision(mid)));
+        }
+        intervals.push(kronrod(f, lo, mid)?);
+        intervals.push(kronrod(f, mid, hi)?);
+    }
+}
+
+/// One G7/K15 panel; the error estimate is the Gauss–Kronrod difference.
+fn kronrod(f: &mut Integrand, a: f64, b: f64) -> Result<(f64, f64, Estimate), String> {
+    let center = (a + b) / 2.0;
+    let half = (b - a) / 2.0;
+    let mid_value = f(center)?;
+    let mut kronrod = mid_value * KRONROD_WEIGHTS[7];
+    let mut gauss = mid_value * GAUSS_WEIGHTS[3];
+    for (i, (&node, &weight)) in KRONROD_NODES.iter().zip(KRONROD_WEIGHTS.iter()).take(7).enumerate() {
+        let pair = f(center - half * node)? + f(center + half * node)?;
+        kronrod += weight * pair;
+        if i % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[i / 2] * pair;
+        }
+    }
+    Ok((a, b, Estimate { value: kronrod * half, error: ((kronrod - gauss) * half).abs() }))
+}
+
+/// Σ or Π of the terms at each integer from `from` to `to`; an empty range
+/// gives 0 or 1. The error estimate bounds the accumulated rounding.
+fn series(f: &mut Integrand, from: f64, to: f64, product: bool) -> Result<Estimate, String> {
+    let symbol = if product { "Π" } else { "Σ" };
+    if from.fract() != 0.0 || to.fract() != 0.0 {
+        return Err(format!("{} bounds must be integers", symbol));
+    }
+    if to - from >= MAX_TERMS as f64 {
+        return Err(format!("{} is limited to {} terms", symbol, MAX_TERMS));
+    }
+    let (from, to) = (from as i64, to as i64);
+    let terms = (to - from + 1).max(0) as f64;
+    if product {
+        let mut value = 1.0;
+        for k in from..=to {
+            value *= f(k as f64)?;
+        }
+        Ok(Estimate { value, error: terms * f64::EPSILON * value.abs() })
+    } else {
+        // Neumaier's compensated summation
+        let (mut value, mut compensation, mut magnitude) = (0.0f64, 0.0, 0.0);
+        for k in from..=to {
+            let term = f(k as f64)?;
+            let total = value + term;
+            compensation += if value.abs() >= term.abs() {
+                (value - total) + term
+            } else {
+                (term - total) + value
+            };
+            value = total;
+            magnitude += term.abs();
+        }
+        Ok(Estimate { value: value + compensation, error: 2.0 * f64::EPSILON * magnitude })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::{AngleUnit, NumericOptions};
+    use crate::expression;
+    use crate::locale::NumberLocale;
+
+    fn eval(text: &str) -> Result<f64, String> {
+        let options = NumericOptions { angle: AngleUnit::Radians, ..NumericOptions::default() };
+        expression::evaluate(text, &NumberLocale::default(), &Context::new(options))
+    }
+
+    fn assert_close(actual: Result<f64, String>, expected: f64, tolerance: f64) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() <= tolerance, "expected {} but got {}", expected, actual);
+    }
+
+    #[test]
+    fn integrals_match_closed_forms() {
+        assert_close(eval("integral(sin(x), x, 0, pi)"), 2.0, 1e-12);
+        assert_close(eval("integral(sin(x), x, pi, 0)"), -2.0, 1e-12);
+        assert_close(eval("integral(x^2, x, 0, 3)"), 9.0, 1e-12);
+        assert_close(eval("integral(e^-(x^2), x, -10, 10)"), std::f64::consts::PI.sqrt(), 1e-10);
+        assert_close(eval("integral(sqrt(x), x, 0, 1)"), 2.0 / 3.0, 1e-9);
+        assert_eq!(eval("integral(x, x, 2, 2)"), Ok(0.0));
+    }
+
+    #[test]
+    fn derivatives_match_closed_forms() {
+        assert_close(eval("diff(x^3, x, 2)"), 12.0, 1e-8);
+        assert_close(eval("diff(sin(x), x, 0)"), 1.0, 1e-10);
+        assert_close(eval("diff(ln(x), x, 4)"), 0.25, 1e-10);
+        assert_close(eval("diff(e^x, x, 10)"), 10f64.exp(), 1e-6 * 10f64.exp());
+    }
+
+    #[test]
+    fn limits_approach_from_both_sides() {
+        assert_close(eval("limit(sin(x)/x, x, 0)"), 1.0, 1e-9);
+        assert_close(eval("limit((x^2 - 1)/(x - 1), x, 1)"), 2.0, 1e-9);
+        assert!(eval("limit(1/x, x, 0)").is_err());
+        assert_eq!(
+            eval("limit(abs(x)/x, x, 0)"),
+            Err("The left and right limits differ (-1 and 1)".to_string())
+        );
+    }
+
+    #[test]
+    fn sums_and_products_over_integer_ranges() {
+        assert_eq!(eval("sum(k, k, 1, 100)"), Ok(5050.0));
+        assert_eq!(eval("product(k, k, 1, 5)"), Ok(120.0));
+        assert_close(eval("sum(1/k^2, k, 1, 100000)"), std::f64::consts::PI.powi(2) / 6.0, 1e-5);
+        // An empty range is the empty sum or product
+        assert_eq!(eval("sum(k, k, 5, 1)"), Ok(0.0));
+        assert_eq!(eval("product(k, k, 5, 1)"), Ok(1.0));
+        assert_eq!(eval("sum(k, k, 0.5, 3)"), Err("Σ bounds must be integers".to_string()));
+        assert_eq!(eval("product(k, k, 1, 2.5)"), Err("Π bounds must be integers".to_string()));
+        assert_eq!(eval("sum(k, k, 1, 2000000)"), Err("Σ is limited to 1000000 terms".to_string()));
+        assert_eq!(eval("sum(k, k, 1, 1e400)"), Err("Σ bounds must be finite".to_string()));
+    }
+
+    #[test]
+    fn extrapolation_removes_the_leading_error_terms() {
+        let estimate = extrapolate(|h| Ok(1.0 + h * h + h.powi(4)), 0.5, 2).unwrap();
+        assert!((estimate.value - 1.0).abs() <= 1e-12, "{:?}", estimate);
+        let estimate = extrapolate(|h| Ok(h.sin() / h), 0.1, 1).unwrap();
+        assert!((estimate.value - 1.0).abs() <= 1e-10, "{:?}", estimate);
+        assert_eq!(extrapolate(|_| Err("boom".to_string()), 0.1, 1), Err("boom".to_string()));
+    }
+}
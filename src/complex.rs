@@ -0,0 +1,380 @@
+use crate::calculator::{condition_error, Condition, NumericOptions, NumericPolicy, Operation, LANCZOS_COEFFICIENTS, LANCZOS_G};
+use std::f64::consts::{LN_10, PI};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// How complex results are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ComplexForm {
+    /// `a + bi`
+    #[default]
+    Rectangular,
+    /// `r∠θ`, with θ in the configured angle unit
+    Polar,
+}
+
+impl ComplexForm {
+    pub fn label(self) -> &'static str {
+        match self {
+            ComplexForm::Rectangular => "a + bi",
+            ComplexForm::Polar => "r∠θ",
+        }
+    }
+}
+
+/// A complex number `re + im·i`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub const ZERO: Complex = Complex { re: 0.0, im: 0.0 };
+    pub const ONE: Complex = Complex { re: 1.0, im: 0.0 };
+
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    pub fn from_polar(r: f64, theta: f64) -> Self {
+        Self::new(r * theta.cos(), r * theta.sin())
+    }
+
+    pub fn is_real(self) -> bool {
+        self.im == 0.0
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.re.is_finite() && self.im.is_finite()
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Principal argument in (-π, π].
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    pub fn scale(self, k: f64) -> Self {
+        Self::new(self.re * k, self.im * k)
+    }
+
+    pub fn exp(self) -> Self {
+        Self::from_polar(self.re.exp(), self.im)
+    }
+
+    /// Principal branch: ln|z| + i·arg z.
+    pub fn ln(self) -> Self {
+        Self::new(self.abs().ln(), self.arg())
+    }
+
+    /// Principal square root, with the branch cut along the negative real axis.
+    pub fn sqrt(self) -> Self {
+        if self.im == 0.0 {
+            return if self.re >= 0.0 {
+                Self::new(self.re.sqrt(), 0.0)
+            } else {
+                Self::new(0.0, (-self.re).sqrt())
+            };
+        }
+        let r = self.abs();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt().copysign(self.im);
+        Self::new(re, im)
+    }
+
+    /// Principal power `exp(w · ln z)`; `0^w` is 0 for Re(w) > 0.
+    pub fn powc(self, w: Complex) -> Self {
+        if self == Self::ZERO {
+            return if w == Self::ZERO {
+                Self::ONE
+            } else if w.re > 0.0 {
+                Self::ZERO
+            } else {
+                Self::new(f64::INFINITY, 0.0)
+            };
+        }
+        if w.is_real() && w.re.fract() == 0.0 && w.re.abs() <= 64.0 {
+            return self.powi(w.re as i32);
+        }
+        (w * self.ln()).exp()
+    }
+
+    fn powi(self, n: i32) -> Self {
+        let mut result = Self::ONE;
+        let mut base = self;
+        let mut k = n.unsigned_abs();
+        while k > 0 {
+            if k & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            k >>= 1;
+        }
+        if n < 0 {
+            Self::ONE / result
+        } else {
+            result
+        }
+    }
+
+    pub fn sin(self) -> Self {
+        Self::new(self.re.sin() * self.im.cosh(), self.re.cos() * self.im.sinh())
+    }
+
+    pub fn cos(self) -> Self {
+        Self::new(self.re.cos() * self.im.cosh(), -self.re.sin() * self.im.sinh())
+    }
+
+    /// Γ(z) by the Lanczos approximation, with reflection for Re(z) < ½.
+    pub fn gamma(self) -> Self {
+        if self.re < 0.5 {
+            // Γ(z)Γ(1−z) = π / sin(πz)
+            let reflected = (Self::ONE - self).gamma();
+            return Self::new(PI, 0.0) / ((self.scale(PI)).sin() * reflected);
+        }
+        let z = self - Self::ONE;
+        let mut x = Self::new(LANCZOS_COEFFICIENTS[0], 0.0);
+        for (k, &c) in LANCZOS_COEFFICIENTS.iter().enumerate().skip(1) {
+            x = x + Self::new(c, 0.0) / (z + Self::new(k as f64, 0.0));
+        }
+        let t = z + Self::new(LANCZOS_G + 0.5, 0.0);
+        t.powc(z + Self::new(0.5, 0.0)) * (-t).exp() * x.scale((2.0 * PI).sqrt())
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, rhs: Complex) -> Complex {
+        if rhs.im == 0.0 {
+            return Complex::new(self.re / rhs.re, self.im / rhs.re);
+        }
+        let denominator = rhs.re * rhs.re + rhs.im * rhs.im;
+        Complex::new(
+            (self.re * rhs.re + self.im * rhs.im) / denominator,
+            (self.im * rhs.re - self.re * rhs.im) / denominator,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+/// Complex counterpart of `calculator::calculate`. Trigonometric arguments
+/// are in the configured angle unit; logarithms, roots and powers use
+/// principal branches.
+pub fn calculate(
+    op: Operation,
+    val1: Complex,
+    val2: Option<Complex>,
+    options: NumericOptions,
+) -> Result<Complex, String> {
+    let operand = |name: &str| val2.ok_or_else(|| format!("{} requires two operands", name));
+    let result = match op {
+        Operation::Add => val1 + operand("Addition")?,
+        Operation::Subtract => val1 - operand("Subtraction")?,
+        Operation::Multiply => val1 * operand("Multiplication")?,
+        Operation::Divide => val1 / operand("Division")?,
+        Operation::Power => val1.powc(operand("Power operation")?),
+        Operation::SquareRoot => val1.sqrt(),
+        Operation::Square => val1 * val1,
+        Operation::Reciprocal => Complex::ONE / val1,
+        Operation::Percentage => val1.scale(0.01),
+        Operation::PercentChange => ((operand("Percent change")? - val1) / val1).scale(100.0),
+        Operation::Margin => val1 / (Complex::ONE - operand("Margin")?.scale(0.01)),
+        Operation::Sin => to_radians(val1, options).sin(),
+        Operation::Cos => to_radians(val1, options).cos(),
+        Operation::Tan if val1.is_real() => {
+            // Real angles keep the exact poles of real mode
+            let ieee = NumericOptions { policy: NumericPolicy::Ieee, ..options };
+            Complex::from(crate::calculator::calculate(op, val1.re, None, ieee)?)
+        }
+        Operation::Tan => {
+            let z = to_radians(val1, options);
+            z.sin() / z.cos()
+        }
+        Operation::Log => val1.ln().scale(1.0 / LN_10),
+        Operation::Ln => val1.ln(),
+        Operation::Factorial => {
+            if val1.is_real() && val1.re >= 0.0 && val1.re.fract() == 0.0 {
+                Complex::from(crate::calculator::calculate(op, val1.re, None, options)?)
+            } else if val1.is_real() && val1.re.fract() == 0.0 {
+                // Poles of Γ at the non-positive integers
+                Complex::new(f64::NAN, f64::NAN)
+            } else {
+                (val1 + Complex::ONE).gamma()
+            }
+        }
+        Operation::Floor
+        | Operation::Ceil
+        | Operation::Round
+        | Operation::Trunc
+        | Operation::FractionalPart => {
+            let part = |x| crate::calculator::calculate(op, x, None, options);
+            Complex::new(part(val1.re)?, part(val1.im)?)
+        }
+        Operation::RoundDigits | Operation::RoundSignificant => {
+            let places = operand("Rounding")?;
+            if !places.is_real() {
+                return Err("Rounding needs a real number of digits".to_string());
+            }
+            if op == Operation::RoundSignificant && !val1.is_real() {
+                return Err("Significant figures need a real number".to_string());
+            }
+            let part = |x| crate::calculator::calculate(op, x, Some(places.re), options);
+            Complex::new(part(val1.re)?, part(val1.im)?)
+        }
+        Operation::PlusMinus => {
+            operand("±")?;
+            val1
+        }
+        Operation::Abs => Complex::from(val1.abs()),
+        Operation::Sign => {
+            if val1 == Complex::ZERO {
+                Complex::ZERO
+            } else {
+                val1.scale(1.0 / val1.abs())
+            }
+        }
+        Operation::Random => Complex::from(crate::calculator::calculate(op, 0.0, None, options)?),
+        Operation::RandomInteger => {
+            let high = operand("Random integer")?;
+            if !val1.is_real() || !high.is_real() {
+                return Err("Random integer bounds must be real".to_string());
+            }
+            Complex::from(crate::calculator::calculate(op, val1.re, Some(high.re), options)?)
+        }
+    };
+
+    match (options.policy, condition_of(op, val1, val2, result)) {
+        (NumericPolicy::Strict, Some(condition)) => Err(condition_error(op, condition)),
+        _ => Ok(result),
+    }
+}
+
+/// Classifies a raw complex result with the same conditions, and for real
+/// operands and results the same rules, as real mode.
+pub fn condition_of(op: Operation, val1: Complex, val2: Option<Complex>, result: Complex) -> Option<Condition> {
+    if val1.is_real() && val2.is_none_or(Complex::is_real) && result.is_real() {
+        return crate::calculator::condition_of(op, val1.re, val2.map(|v| v.re), result.re);
+    }
+    if !val1.is_finite() || val2.is_some_and(|v| !v.is_finite()) {
+        return None;
+    }
+    if result.is_finite() {
+        let underflow = !op.is_rounding() && result.abs().is_subnormal();
+        return underflow.then_some(Condition::Underflow);
+    }
+    let pole = match op {
+        Operation::Divide => val2 == Some(Complex::ZERO),
+        Operation::Reciprocal | Operation::Log | Operation::Ln | Operation::PercentChange => val1 == Complex::ZERO,
+        Operation::Margin => val2 == Some(Complex::from(100.0)),
+        Operation::Power => val1 == Complex::ZERO && val2.is_some_and(|v| v.re < 0.0),
+        _ => false,
+    };
+    // An overflowing component can turn the other into NaN, as in ∞ − ∞
+    Some(if pole {
+        Condition::DivisionByZero
+    } else if result.re.is_infinite() || result.im.is_infinite() {
+        Condition::Overflow
+    } else {
+        Condition::Undefined
+    })
+}
+
+fn to_radians(z: Complex, options: NumericOptions) -> Complex {
+    z.scale(options.angle.to_radians(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::AngleUnit;
+
+    fn options(policy: NumericPolicy) -> NumericOptions {
+        NumericOptions { policy, angle: AngleUnit::Degrees }
+    }
+
+    #[test]
+    fn strict_errors_match_real_mode() {
+        let strict = options(NumericPolicy::Strict);
+        let real = |op, a, b| crate::calculator::calculate(op, a, b, strict).unwrap_err();
+        let complex = |op, a, b: Option<f64>| calculate(op, Complex::from(a), b.map(Complex::from), strict).unwrap_err();
+        for (op, a, b) in [
+            (Operation::Divide, 1.0, Some(0.0)),
+            (Operation::Reciprocal, 0.0, None),
+            (Operation::Ln, 0.0, None),
+            (Operation::Multiply, 1e200, Some(1e200)),
+            (Operation::Multiply, 1e-200, Some(1e-200)),
+            (Operation::Tan, 90.0, None),
+            (Operation::Tan, -270.0, None),
+        ] {
+            assert_eq!(complex(op, a, b), real(op, a, b), "{:?}", op);
+        }
+        let huge = Complex::new(1e200, 1e200);
+        assert_eq!(
+            calculate(Operation::Multiply, huge, Some(huge), strict).unwrap_err(),
+            "Overflow: result is too large to represent"
+        );
+        assert_eq!(
+            calculate(Operation::Divide, Complex::new(1.0, 1.0), Some(Complex::ZERO), strict).unwrap_err(),
+            "Cannot divide by zero"
+        );
+    }
+
+    #[test]
+    fn ieee_results_report_conditions() {
+        let ieee = options(NumericPolicy::Ieee);
+        let huge = Complex::new(1e200, 1e200);
+        let product = calculate(Operation::Multiply, huge, Some(huge), ieee).unwrap();
+        assert_eq!(condition_of(Operation::Multiply, huge, Some(huge), product), Some(Condition::Overflow));
+
+        let tan = calculate(Operation::Tan, Complex::from(90.0), None, ieee).unwrap();
+        assert!(tan.re.is_nan());
+        assert_eq!(condition_of(Operation::Tan, Complex::from(90.0), None, tan), Some(Condition::Undefined));
+
+        let root = calculate(Operation::SquareRoot, Complex::from(-4.0), None, ieee).unwrap();
+        assert_eq!(root, Complex::new(0.0, 2.0));
+        assert_eq!(condition_of(Operation::SquareRoot, Complex::from(-4.0), None, root), None);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::dates::{self, Date, SECONDS_PER_DAY};
+
+/// One exchange rate against the table's base currency.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Rate {
+    /// Currency code, e.g. `EUR`.
+    pub code: String,
+    /// Units of this currency per one unit of the base currency.
+    pub per_base: f64,
+    /// When the rate was last set, as Unix seconds.
+    pub updated: i64,
+}
+
+/// Exchange rates maintained by the user, saved in the app's storage
+/// between runs so that conversion works offline.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RateTable {
+    pub base: String,
+    pub rates: Vec<Rate>,
+    /// Rates older than this many days get a warning.
+    pub stale_after_days: u32,
+    /// When rates were last imported, as Unix seconds.
+    pub imported: Option<i64>,
+}
+
+impl Default for RateTable {
+    fn default() -> Self {
+        Self {
+            base: "USD".to_string(),
+            rates: Vec::new(),
+            stale_after_days: 7,
+            imported: None,
+        }
+    }
+}
+
+/// Parses an ISO date (`2024-05-01`, optionally with a time) or Unix
+/// seconds.
+fn parse_timestamp(text: &str) -> Option<i64> {
+    let text = text.trim();
+    text.parse::<i64>().ok().or_else(|| dates::parse_datetime(text).ok())
+}
+
+/// Currency codes are three ASCII letters.
+fn valid_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+impl RateTable {
+    /// Units of `code` per base unit; the base currency itself is 1.
+    pub fn rate(&self, code: &str) -> Option<f64> {
+        if code.eq_ignore_ascii_case(&self.base) {
+            return Some(1.0);
+        }
+        self.rates
+            .iter()
+            .find(|rate| rate.code.eq_ignore_ascii_case(code))
+            .map(|rate| rate.per_base)
+            .filter(|&rate| rate > 0.0 && rate.is_finite())
+    }
+
+    /// All currencies with a usable rate, including the base.
+    pub fn codes(&self) -> Vec<String> {
+        std::iter::once(self.base.clone())
+            .chain(self.rates.iter().filter(|rate| self.rate(&rate.code).is_some()).map(|rate| rate.code.clone()))
+            .collect()
+    }
+
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Result<f64, String> {
+        let rate = |code: &str| self.rate(code).ok_or_else(|| format!("No exchange rate for {}", code));
+        Ok(amount / rate(from)? * rate(to)?)
+    }
+
+    /// Age in whole days of a rate older than the staleness limit.
+    pub fn stale_age(&self, code: &str, now: i64) -> Option<i64> {
+        let rate = self.rates.iter().find(|rate| rate.code.eq_ignore_ascii_case(code))?;
+        let age = (now - rate.updated) / SECONDS_PER_DAY;
+        (age > i64::from(self.stale_after_days)).then_some(age)
+    }
+
+    /// A warning for the stale rates among `codes`, if any.
+    pub fn staleness_warning<'a>(&self, codes: impl IntoIterator<Item = &'a str>, now: i64) -> Option<String> {
+        let stale: Vec<String> = codes
+            .into_iter()
+            .filter_map(|code| self.stale_age(code, now).map(|age| format!("{} ({} days)", code.to_uppercase(), age)))
+            .collect();
+        (!stale.is_empty()).then(|| format!("Stale exchange rate: {}", stale.join(", ")))
+    }
+
+    /// Rates as `(code, units per base)` pairs, for expression evaluation.
+    pub fn pairs(&self) -> Vec<(String, f64)> {
+        self.codes()
+            .into_iter()
+            .filter_map(|code| self.rate(&code).map(|rate| (code, rate)))
+            .collect()
+    }
+
+    /// Merges rates from a JSON object or CSV text and returns how many
+    /// were read. JSON looks like `{"base": "USD", "date": "2024-05-01",
+    /// "rates": {"EUR": 0.92}}`, where a rate may also be an object with
+    /// its own `rate` and `updated`. CSV has `code,rate[,updated]` rows
+    /// and an optional `base,USD` row. A different base replaces the
+    /// table; rates without a date are stamped `now`.
+    pub fn import(&mut self, text: &str, now: i64) -> Result<usize, String> {
+        let (base, rates) = if text.trim_start().starts_with('{') {
+            parse_json(text, now)?
+        } else {
+            parse_csv(text, now)?
+        };
+        if rates.is_empty() {
+            return Err("No exchange rates found".to_string());
+        }
+        if let Some(base) = base {
+            if !valid_code(&base) {
+                return Err(format!("Base '{}' is not a currency code", base));
+            }
+            if !base.eq_ignore_ascii_case(&self.base) {
+                self.base = base.to_uppercase();
+                self.rates.clear();
+            }
+        }
+        let count = rates.len();
+        for rate in rates {
+            if rate.code.eq_ignore_ascii_case(&self.base) {
+                continue;
+            }
+            match self.rates.iter_mut().find(|r| r.code.eq_ignore_ascii_case(&rate.code)) {
+                Some(existing) => *existing = rate,
+                None => self.rates.push(rate),
+            }
+        }
+        self.imported = Some(now);
+        Ok(count)
+    }
+
+    /// The table as CSV that `import` reads back.
+    pub fn export_csv(&self) -> String {
+        let mut csv = format!("base,{}\ncode,rate,updated\n", self.base);
+        for rate in &self.rates {
+            csv.push_str(&format!("{},{},{}\n", rate.code, rate.per_base, Date::from_timestamp(rate.updated)));
+        }
+        csv
+    }
+}
+
+fn parse_json(text: &str, now: i64) -> Result<(Option<String>, Vec<Rate>), String> {
+    use serde_json::Value;
+    let json: Value = serde_json::from_str(text).map_err(|err| format!("Invalid JSON: {}", err))?;
+    let timestamp = |value: Option<&Value>| match value {
+        Some(Value::Number(seconds)) => seconds.as_i64(),
+        Some(Value::String(date)) => parse_timestamp(date),
+        _ => None,
+    };
+    let updated = timestamp(json.get("timestamp").or_else(|| json.get("date"))).unwrap_or(now);
+    let base = json.get("base").and_then(Value::as_str).map(str::to_string);
+    let Some(Value::Object(entries)) = json.get("rates") else {
+        return Err("JSON rates need a \"rates\" object".to_string());
+    };
+    let mut rates = Vec::new();
+    for (code, value) in entries {
+        let (per_base, updated) = match value {
+            Value::Object(_) => (
+                value.get("rate").and_then(Value::as_f64),
+                timestamp(value.get("updated")).unwrap_or(updated),
+            ),
+            _ => (value.as_f64(), updated),
+        };
+        let per_base = per_base.ok_or_else(|| format!("Invalid rate for {}", code))?;
+        rates.push(checked_rate(code, per_base, updated)?);
+    }
+    Ok((base, rates))
+}
+
+fn parse_csv(text: &str, now: i64) -> Result<(Option<String>, Vec<Rate>), String> {
+    let mut base = None;
+    let mut rates = Vec::new();
+    let mut first_row = true;
+    for (number, line) in text.lines().enumerate() {
+        let fields: Vec<&str> = line.split([',', ';', '\t']).map(str::trim).collect();
+        match fields.as_slice() {
+            [""] => {}
+            [label, code, ..] if label.eq_ignore_ascii_case("base") => base = Some(code.to_string()),
+            [code, rate, rest @ ..] => match rate.parse::<f64>() {
+                Ok(per_base) => {
+                    first_row = false;
+                    let updated = match rest.first() {
+                        Some(date) if !date.is_empty() => {
+                            parse_timestamp(date).ok_or_else(|| format!("Line {}: invalid date '{}'", number + 1, date))?
+                        }
+                        _ => now,
+                    };
+                    rates.push(checked_rate(code, per_base, updated).map_err(|err| format!("Line {}: {}", number + 1, err))?);
+                }
+                // A header row, before any rate but possibly after the base row
+                Err(_) if std::mem::replace(&mut first_row, false) => {}
+                Err(_) => return Err(format!("Line {}: invalid rate '{}'", number + 1, rate)),
+            },
+            _ => return Err(format!("Line {}: expected code,rate", number + 1)),
+        }
+    }
+    Ok((base, rates))
+}
+
+fn checked_rate(code: &str, per_base: f64, updated: i64) -> Result<Rate, String> {
+    if !valid_code(code) {
+        return Err(format!("'{}' is not a currency code", code));
+    }
+    if !(per_base > 0.0 && per_base.is_finite()) {
+        return Err(format!("Rate for {} must be positive", code));
+    }
+    Ok(Rate {
+        code: code.to_uppercase(),
+        per_base,
+        updated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAY_1: i64 = 1_714_521_600;
+    const DAY: i64 = SECONDS_PER_DAY;
+
+    fn rate(table: &RateTable, code: &str) -> Option<(f64, i64)> {
+        table.rates.iter().find(|rate| rate.code == code).map(|rate| (rate.per_base, rate.updated))
+    }
+
+    #[test]
+    fn csv_import_reads_headers_bases_and_dates() {
+        let mut table = RateTable::default();
+        let csv = "base,EUR\ncode;rate;updated\nusd;1.07;2024-04-20\nGBP;0.86\n\n";
+        assert_eq!(table.import(csv, MAY_1), Ok(2));
+        assert_eq!(table.base, "EUR");
+        assert_eq!(rate(&table, "USD"), Some((1.07, MAY_1 - 11 * DAY)));
+        assert_eq!(rate(&table, "GBP"), Some((0.86, MAY_1)));
+        assert_eq!(table.imported, Some(MAY_1));
+
+        // The same base merges; a new base replaces the table
+        assert_eq!(table.import("JPY,165.2\nEUR,1", MAY_1), Ok(2));
+        assert_eq!(table.codes(), vec!["EUR", "USD", "GBP", "JPY"]);
+        table.import("base,USD\nEUR,0.93", MAY_1).unwrap();
+        assert_eq!(table.codes(), vec!["USD", "EUR"]);
+    }
+
+    #[test]
+    fn csv_import_rejects_bad_rows_after_the_header() {
+        let mut table = RateTable::default();
+        assert_eq!(
+            table.import("code,rate\nEUR,0.9x\nGBP,0.8", MAY_1),
+            Err("Line 2: invalid rate '0.9x'".to_string())
+        );
+        assert_eq!(
+            table.import("base,EUR\ncode,rate\nUSD,1.07\nGBP,O.86", MAY_1),
+            Err("Line 4: invalid rate 'O.86'".to_string())
+        );
+        assert_eq!(table.import("EUR,0.9,May 1", MAY_1), Err("Line 1: invalid date 'May 1'".to_string()));
+        assert_eq!(table.import("EURO,0.9", MAY_1), Err("Line 1: 'EURO' is not a currency code".to_string()));
+        assert_eq!(table.import("EUR,-0.9", MAY_1), Err("Line 1: Rate for EUR must be positive".to_string()));
+        assert_eq!(table.import("EUR", MAY_1), Err("Line 1: expected code,rate".to_string()));
+        assert_eq!(table.import("code,rate\n", MAY_1), Err("No exchange rates found".to_string()));
+        assert_eq!(
+            table.import("base,US Dollar\nEUR,0.9", MAY_1),
+            Err("Base 'US Dollar' is not a currency code".to_string())
+        );
+        assert_eq!(table, RateTable::default());
+    }
+
+    #[test]
+    fn exported_csv_imports_unchanged() {
+        let mut table = RateTable::default();
+        table.import("base,EUR\nUSD,1.07,2024-04-20\nGBP,0.86,2024-05-01", MAY_1).unwrap();
+        let mut copy = RateTable::default();
+        assert_eq!(copy.import(&table.export_csv(), MAY_1), Ok(2));
+        assert_eq!(copy, table);
+    }
+
+    #[test]
+    fn json_import_reads_plain_and_dated_rates() {
+        let mut table = RateTable::default();
+        let json = r#"{"base": "eur", "date": "2024-05-01",
+            "rates": {"USD": 1.07, "GBP": {"rate": 0.86, "updated": "2024-04-20"}, "JPY": {"rate": 165.2}}}"#;
+        assert_eq!(table.import(json, MAY_1 + DAY), Ok(3));
+        assert_eq!(table.base, "EUR");
+        assert_eq!(rate(&table, "USD"), Some((1.07, MAY_1)));
+        assert_eq!(rate(&table, "GBP"), Some((0.86, MAY_1 - 11 * DAY)));
+        assert_eq!(rate(&table, "JPY"), Some((165.2, MAY_1)));
+
+        let seconds = r#"{"timestamp": 1714521600, "rates": {"CHF": 0.91}}"#;
+        table.import(seconds, 0).unwrap();
+        assert_eq!(rate(&table, "CHF"), Some((0.91, MAY_1)));
+    }
+
+    #[test]
+    fn json_import_rejects_malformed_rates() {
+        let mut table = RateTable::default();
+        assert!(table.import("{\"rates\": ", MAY_1).unwrap_err().starts_with("Invalid JSON"));
+        assert_eq!(
+            table.import(r#"{"base": "USD"}"#, MAY_1),
+            Err("JSON rates need a \"rates\" object".to_string())
+        );
+        assert_eq!(table.import(r#"{"rates": {"EUR": "0.9"}}"#, MAY_1), Err("Invalid rate for EUR".to_string()));
+        assert_eq!(
+            table.import(r#"{"base": "EURO", "rates": {"USD": 1.07}}"#, MAY_1),
+            Err("Base 'EURO' is not a currency code".to_string())
+        );
+        assert_eq!(table.import(r#"{"rates": {}}"#, MAY_1), Err("No exchange rates found".to_string()));
+    }
+
+    #[test]
+    fn old_rates_are_flagged_as_stale() {
+        let mut table = RateTable::default();
+        table.import("EUR,0.93,2024-04-21\nGBP,0.8,2024-04-24\nJPY,155", MAY_1).unwrap();
+        assert_eq!(table.stale_age("EUR", MAY_1), Some(10));
+        // Exactly at the limit is still fresh
+        assert_eq!(table.stale_age("GBP", MAY_1), None);
+        assert_eq!(table.stale_age("JPY", MAY_1), None);
+        assert_eq!(table.stale_age("USD", MAY_1), None);
+        assert_eq!(
+            table.staleness_warning(["eur", "gbp", "usd"], MAY_1),
+            Some("Stale exchange rate: EUR (10 days)".to_string())
+        );
+        table.stale_after_days = 30;
+        assert_eq!(table.staleness_warning(["EUR"], MAY_1), None);
+    }
+}
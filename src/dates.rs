@@ -0,0 +1,415 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Largest shift or range handled, in days (about 2700 years).
+const MAX_DAYS: i64 = 1_000_000;
+
+pub const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// Current time as Unix seconds.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A day in the proleptic Gregorian calendar (UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Date {
+    pub fn new(year: i64, month: u32, day: u32) -> Option<Date> {
+        let valid = (0..=9999).contains(&year) && (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day);
+        valid.then_some(Date { year, month, day })
+    }
+
+    pub fn today() -> Date {
+        Date::from_timestamp(now())
+    }
+
+    /// The UTC day of a Unix timestamp.
+    pub fn from_timestamp(timestamp: i64) -> Date {
+        Date::from_days(timestamp.div_euclid(SECONDS_PER_DAY))
+    }
+
+    /// The date a number of days after 1970-01-01.
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        Date {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// Days since 1970-01-01.
+    pub fn days(self) -> i64 {
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = if month <= 2 { self.year - 1 } else { self.year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Parses `YYYY-MM-DD` (or with `/`), or `today`.
+    pub fn parse(text: &str) -> Result<Date, String> {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("today") {
+            return Ok(Date::today());
+        }
+        if text.is_empty() {
+            return Err("Enter a date as YYYY-MM-DD".to_string());
+        }
+        let invalid = || format!("'{}' is not a date (YYYY-MM-DD)", text);
+        let parts: Vec<&str> = text.split(['-', '/']).collect();
+        let [year, month, day] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        let (Ok(year), Ok(month), Ok(day)) = (year.parse(), month.parse(), day.parse()) else {
+            return Err(invalid());
+        };
+        Date::new(year, month, day).ok_or_else(invalid)
+    }
+
+    /// Index into `WEEKDAYS`, Monday first.
+    pub fn weekday(self) -> usize {
+        // 1970-01-01 was a Thursday
+        (self.days() + 3).rem_euclid(7) as usize
+    }
+
+    pub fn weekday_name(self) -> &'static str {
+        WEEKDAYS[self.weekday()]
+    }
+
+    /// ISO 8601 week-numbering year and week. Weeks start on Monday and
+    /// week 1 holds the year's first Thursday.
+    pub fn iso_week(self) -> (i64, i64) {
+        let thursday = self.days() - self.weekday() as i64 + 3;
+        let year = Date::from_days(thursday).year;
+        let first = Date { year, month: 1, day: 1 }.days();
+        (year, (thursday - first) / 7 + 1)
+    }
+
+    /// 1 for January 1st.
+    pub fn day_of_year(self) -> i64 {
+        self.days() - Date { year: self.year, month: 1, day: 1 }.days() + 1
+    }
+
+    pub fn days_in_year(self) -> i64 {
+        if is_leap_year(self.year) { 366 } else { 365 }
+    }
+
+    /// Adds calendar months, clamping to the end of shorter months
+    /// (January 31st plus one month is February 28th or 29th).
+    pub fn add_months(self, months: i64) -> Result<Date, String> {
+        let index = self.year * 12 + i64::from(self.month) - 1 + months;
+        let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+        let day = self.day.min(days_in_month(year, month));
+        Date::new(year, month, day).ok_or_else(|| "Date is out of range (years 0–9999)".to_string())
+    }
+
+    pub fn add_days(self, days: i64) -> Result<Date, String> {
+        let date = Date::from_days(self.days() + days);
+        Date::new(date.year, date.month, date.day).ok_or_else(|| "Date is out of range (years 0–9999)".to_string())
+    }
+}
+
+/// Parses a date with an optional UTC time, `2024-05-01 13:45[:30]` or
+/// `2024-05-01T13:45:30Z`, or `now`, into Unix seconds.
+pub fn parse_datetime(text: &str) -> Result<i64, String> {
+    let text = text.trim().trim_end_matches(['Z', 'z']);
+    if text.eq_ignore_ascii_case("now") {
+        return Ok(now());
+    }
+    let (date, time) = match text.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (text, None),
+    };
+    let date = Date::parse(date)?;
+    let seconds = match time {
+        None | Some("") => 0,
+        Some(time) => {
+            let invalid = || format!("'{}' is not a time (HH:MM or HH:MM:SS)", time);
+            let parts = time.split(':').map(|part| part.parse::<i64>().map_err(|_| invalid())).collect::<Result<Vec<_>, _>>()?;
+            let (hours, minutes, seconds) = match parts.as_slice() {
+                [hours, minutes] => (*hours, *minutes, 0),
+                [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+                _ => return Err(invalid()),
+            };
+            if !(0..24).contains(&hours) || !(0..60).contains(&minutes) || !(0..60).contains(&seconds) {
+                return Err(invalid());
+            }
+            hours * 3600 + minutes * 60 + seconds
+        }
+    };
+    Ok(date.days() * SECONDS_PER_DAY + seconds)
+}
+
+/// Unix seconds as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub fn format_datetime(timestamp: i64) -> String {
+    let date = Date::from_timestamp(timestamp);
+    let seconds = timestamp.rem_euclid(SECONDS_PER_DAY);
+    format!("{} {:02}:{:02}:{:02}", date, seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Whole years, months and days from `start` to `end`, all negative when
+/// `end` comes first.
+pub fn calendar_difference(start: Date, end: Date) -> (i64, i64, i64) {
+    if end < start {
+        let (years, months, days) = calendar_difference(end, start);
+        return (-years, -months, -days);
+    }
+    let mut months = (end.year - start.year) * 12 + i64::from(end.month) - i64::from(start.month);
+    // Anchoring on the start keeps month-end clamping consistent with
+    // add_months, so January 31st to February 29th is one month
+    let mut anchor = start.add_months(months).unwrap_or(start);
+    if anchor > end {
+        months -= 1;
+        anchor = start.add_months(months).unwrap_or(start);
+    }
+    (months / 12, months % 12, end.days() - anchor.days())
+}
+
+/// Holiday dates, one per line; `#` starts a comment.
+pub fn parse_holidays(text: &str) -> Result<Vec<Date>, String> {
+    let mut holidays = text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(Date::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    holidays.sort();
+    holidays.dedup();
+    Ok(holidays)
+}
+
+pub fn is_business_day(date: Date, holidays: &[Date]) -> bool {
+    date.weekday() < 5 && holidays.binary_search(&date).is_err()
+}
+
+/// Business days from `start` to `end`, counting both ends, negative when
+/// `end` comes first (like a spreadsheet's NETWORKDAYS).
+pub fn business_days(start: Date, end: Date, holidays: &[Date]) -> i64 {
+    if end < start {
+        return -business_days(end, start, holidays);
+    }
+    let (first, last) = (start.days(), end.days());
+    let span = last - first + 1;
+    let mut count = span / 7 * 5;
+    for day in first + span / 7 * 7..=last {
+        if Date::from_days(day).weekday() < 5 {
+            count += 1;
+        }
+    }
+    let observed = holidays
+        .iter()
+        .filter(|holiday| (start..=end).contains(holiday) && holiday.weekday() < 5)
+        .count();
+    count - observed as i64
+}
+
+/// The date `count` business days after `start` (before it when negative),
+/// skipping weekends and holidays like a spreadsheet's WORKDAY.
+pub fn add_business_days(start: Date, count: i64, holidays: &[Date]) -> Result<Date, String> {
+    if count.abs() > MAX_DAYS {
+        return Err("Too many business days".to_string());
+    }
+    let step = count.signum();
+    let mut date = start;
+    let mut remaining = count.abs();
+    while remaining > 0 {
+        date = date.add_days(step)?;
+        if is_business_day(date, holidays) {
+            remaining -= 1;
+        }
+    }
+    Ok(date)
+}
+
+/// Units for adding to or subtracting from a date.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum DurationUnit {
+    #[default]
+    Days,
+    Weeks,
+    Months,
+    Years,
+    BusinessDays,
+}
+
+impl DurationUnit {
+    pub const ALL: [DurationUnit; 5] = [
+        DurationUnit::Days,
+        DurationUnit::Weeks,
+        DurationUnit::Months,
+        DurationUnit::Years,
+        DurationUnit::BusinessDays,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DurationUnit::Days => "days",
+            DurationUnit::Weeks => "weeks",
+            DurationUnit::Months => "months",
+            DurationUnit::Years => "years",
+            DurationUnit::BusinessDays => "business days",
+        }
+    }
+
+    /// `date` moved by `amount` of this unit.
+    pub fn shift(self, date: Date, amount: i64, holidays: &[Date]) -> Result<Date, String> {
+        match self {
+            DurationUnit::Days if amount.abs() <= MAX_DAYS => date.add_days(amount),
+            DurationUnit::Weeks if amount.abs() <= MAX_DAYS / 7 => date.add_days(amount * 7),
+            DurationUnit::Months if amount.abs() <= MAX_DAYS / 28 => date.add_months(amount),
+            DurationUnit::Years if amount.abs() <= MAX_DAYS / 365 => date.add_months(amount * 12),
+            DurationUnit::BusinessDays => add_business_days(date, amount, holidays),
+            _ => Err("Date is out of range (years 0–9999)".to_string()),
+        }
+    }
+}
+
+/// Inputs of the date mode, kept with the app state.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DateSettings {
+    /// Start date, optionally with a time for timestamp conversion.
+    pub start: String,
+    pub end: String,
+    pub amount: i64,
+    pub unit: DurationUnit,
+    /// Holidays skipped by business-day counting, one date per line.
+    pub holidays: String,
+    /// Unix seconds to convert to a date.
+    pub timestamp: String,
+}
+
+impl Default for DateSettings {
+    fn default() -> Self {
+        Self {
+            start: "today".to_string(),
+            end: String::new(),
+            amount: 30,
+            unit: DurationUnit::default(),
+            holidays: String::new(),
+            timestamp: String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> Date {
+        Date::parse(text).unwrap()
+    }
+
+    #[test]
+    fn day_numbers_match_known_dates_and_round_trip() {
+        assert_eq!(date("1970-01-01").days(), 0);
+        assert_eq!(date("1969-12-31").days(), -1);
+        assert_eq!(date("2000-03-01").days(), 11_017);
+        assert_eq!(date("2024-05-01").days() * SECONDS_PER_DAY, 1_714_521_600);
+        assert_eq!(Date::from_days(-719_468), date("0000-03-01"));
+        for days in date("0000-01-01").days()..=date("2400-12-31").days() {
+            let day = Date::from_days(days);
+            assert_eq!(day.days(), days);
+            assert_eq!(Date::new(day.year, day.month, day.day), Some(day));
+        }
+        assert_eq!(Date::from_days(date("9999-12-31").days()), date("9999-12-31"));
+    }
+
+    #[test]
+    fn leap_years_follow_the_gregorian_rules() {
+        assert!(Date::new(2024, 2, 29).is_some());
+        assert!(Date::new(2000, 2, 29).is_some());
+        assert!(Date::new(1900, 2, 29).is_none());
+        assert!(Date::new(2023, 2, 29).is_none());
+        assert!(Date::parse("2100-02-29").is_err());
+        assert_eq!(date("2024-12-31").day_of_year(), 366);
+        assert_eq!(date("2023-12-31").day_of_year(), 365);
+        assert_eq!(date("1900-06-01").days_in_year(), 365);
+        assert_eq!(date("2000-06-01").days_in_year(), 366);
+        assert_eq!(date("2024-02-28").add_days(1).unwrap(), date("2024-02-29"));
+        assert_eq!(date("2023-02-28").add_days(1).unwrap(), date("2023-03-01"));
+        assert_eq!(date("2024-12-31").add_days(1).unwrap(), date("2025-01-01"));
+    }
+
+    #[test]
+    fn month_arithmetic_clamps_to_the_month_end() {
+        assert_eq!(date("2024-01-31").add_months(1).unwrap(), date("2024-02-29"));
+        assert_eq!(date("2023-01-31").add_months(1).unwrap(), date("2023-02-28"));
+        assert_eq!(date("2024-03-31").add_months(-1).unwrap(), date("2024-02-29"));
+        assert_eq!(date("2024-08-31").add_months(1).unwrap(), date("2024-09-30"));
+        assert_eq!(date("2024-02-29").add_months(12).unwrap(), date("2025-02-28"));
+        assert_eq!(date("2024-11-30").add_months(3).unwrap(), date("2025-02-28"));
+        assert!(date("9999-12-01").add_months(1).is_err());
+
+        assert_eq!(calendar_difference(date("2024-01-31"), date("2024-02-29")), (0, 1, 0));
+        assert_eq!(calendar_difference(date("2024-01-31"), date("2024-03-01")), (0, 1, 1));
+        assert_eq!(calendar_difference(date("2020-02-29"), date("2024-02-28")), (3, 11, 30));
+        assert_eq!(calendar_difference(date("2024-05-15"), date("2023-03-10")), (-1, -2, -5));
+    }
+
+    #[test]
+    fn weekdays_weeks_and_business_days() {
+        assert_eq!(date("2024-05-01").weekday_name(), "Wednesday");
+        assert_eq!(date("1970-01-01").weekday_name(), "Thursday");
+        // 2021-01-03 is a Sunday in the last ISO week of 2020
+        assert_eq!(date("2021-01-03").iso_week(), (2020, 53));
+        assert_eq!(date("2024-12-30").iso_week(), (2025, 1));
+
+        let holidays = parse_holidays("2024-05-06 # bank holiday\n\n2024-05-04").unwrap();
+        assert_eq!(business_days(date("2024-05-01"), date("2024-05-07"), &[]), 5);
+        assert_eq!(business_days(date("2024-05-01"), date("2024-05-07"), &holidays), 4);
+        assert_eq!(business_days(date("2024-05-07"), date("2024-05-01"), &holidays), -4);
+        assert_eq!(add_business_days(date("2024-05-03"), 1, &holidays).unwrap(), date("2024-05-07"));
+        assert_eq!(add_business_days(date("2024-05-07"), -1, &holidays).unwrap(), date("2024-05-03"));
+    }
+
+    #[test]
+    fn date_times_parse_and_format_in_utc() {
+        assert_eq!(parse_datetime("2024-05-01T13:45:30Z").unwrap(), 1_714_571_130);
+        assert_eq!(parse_datetime("2024-05-01 13:45").unwrap(), 1_714_571_100);
+        assert_eq!(parse_datetime("2024-05-01").unwrap(), 1_714_521_600);
+        assert_eq!(format_datetime(1_714_571_130), "2024-05-01 13:45:30");
+        assert_eq!(format_datetime(-1), "1969-12-31 23:59:59");
+        assert!(parse_datetime("2024-05-01 24:00").is_err());
+        assert!(parse_datetime("2024-13-01").is_err());
+    }
+}
@@ -0,0 +1,483 @@
+use crate::calculator::{erfc, ln_gamma, regularized_beta, regularized_gamma_p, regularized_gamma_q};
+use std::f64::consts::{PI, SQRT_2};
+
+/// Probability distribution families offered in scientific mode and as
+/// expression functions such as `normcdf(x, μ, σ)`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Family {
+    #[default]
+    Normal,
+    StudentT,
+    ChiSquare,
+    Binomial,
+    Poisson,
+    Uniform,
+}
+
+impl Family {
+    pub const ALL: [Family; 6] = [
+        Family::Normal,
+        Family::StudentT,
+        Family::ChiSquare,
+        Family::Binomial,
+        Family::Poisson,
+        Family::Uniform,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Family::Normal => "Normal",
+            Family::StudentT => "Student's t",
+            Family::ChiSquare => "Chi-square",
+            Family::Binomial => "Binomial",
+            Family::Poisson => "Poisson",
+            Family::Uniform => "Uniform",
+        }
+    }
+
+    /// Prefix of the expression function names: `norm` for `normpdf`, …
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Family::Normal => "norm",
+            Family::StudentT => "t",
+            Family::ChiSquare => "chi2",
+            Family::Binomial => "binom",
+            Family::Poisson => "poisson",
+            Family::Uniform => "unif",
+        }
+    }
+
+    /// Names of the parameters, in the order they are passed.
+    pub fn parameters(self) -> &'static [&'static str] {
+        match self {
+            Family::Normal => &["μ", "σ"],
+            Family::StudentT => &["ν"],
+            Family::ChiSquare => &["k"],
+            Family::Binomial => &["n", "p"],
+            Family::Poisson => &["λ"],
+            Family::Uniform => &["a", "b"],
+        }
+    }
+
+    pub fn is_discrete(self) -> bool {
+        matches!(self, Family::Binomial | Family::Poisson)
+    }
+}
+
+/// Which function of a distribution to evaluate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    /// Probability density, or probability mass for discrete families.
+    Density,
+    Cumulative,
+    /// Inverse of the cumulative distribution (quantile function).
+    Inverse,
+}
+
+impl Function {
+    pub const ALL: [Function; 3] = [Function::Density, Function::Cumulative, Function::Inverse];
+
+    /// Suffix of the expression function name and keypad label.
+    pub fn suffix(self, family: Family) -> &'static str {
+        match self {
+            Function::Density if family.is_discrete() => "pmf",
+            Function::Density => "pdf",
+            Function::Cumulative => "cdf",
+            Function::Inverse => "inv",
+        }
+    }
+}
+
+/// A distribution family with its parameters. Unused parameter slots are
+/// ignored, e.g. the second one for Student's t.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Distribution {
+    pub family: Family,
+    pub params: [f64; 2],
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Self::standard(Family::Normal)
+    }
+}
+
+impl Distribution {
+    /// The family with typical parameters: the standard normal, 10 degrees
+    /// of freedom, 10 trials of a fair coin, …
+    pub fn standard(family: Family) -> Self {
+        let params = match family {
+            Family::Normal => [0.0, 1.0],
+            Family::StudentT => [10.0, 0.0],
+            Family::ChiSquare => [1.0, 0.0],
+            Family::Binomial => [10.0, 0.5],
+            Family::Poisson => [1.0, 0.0],
+            Family::Uniform => [0.0, 1.0],
+        };
+        Self { family, params }
+    }
+
+    /// Expression-style name of a function of this family, e.g. `tcdf`.
+    pub fn function_name(self, function: Function) -> String {
+        format!("{}{}", self.family.prefix(), function.suffix(self.family))
+    }
+
+    pub fn evaluate(self, function: Function, x: f64) -> Result<f64, String> {
+        self.validate()?;
+        if function == Function::Inverse && !(0.0..=1.0).contains(&x) {
+            return Err("Probability must be between 0 and 1".to_string());
+        }
+        let [p1, p2] = self.params;
+        Ok(match (self.family, function) {
+            (Family::Normal, Function::Density) => {
+                let z = (x - p1) / p2;
+                (-0.5 * z * z).exp() / (p2 * (2.0 * PI).sqrt())
+            }
+            (Family::Normal, Function::Cumulative) => 0.5 * erfc(-(x - p1) / (p2 * SQRT_2)),
+            (Family::Normal, Function::Inverse) => p1 + p2 * inverse_standard_normal(x),
+            (Family::StudentT, Function::Density) => {
+                let nu = p1;
+                (ln_gamma((nu + 1.0) / 2.0) - ln_gamma(nu / 2.0)).exp() / (nu * PI).sqrt()
+                    * (1.0 + x * x / nu).powf(-(nu + 1.0) / 2.0)
+            }
+            (Family::StudentT, Function::Cumulative) => t_cdf(x, p1),
+            (Family::StudentT, Function::Inverse) => {
+                invert(|t| t_cdf(t, p1), x, f64::NEG_INFINITY, f64::INFINITY)
+            }
+            (Family::ChiSquare, Function::Density) => {
+                let k = p1;
+                if x < 0.0 {
+                    0.0
+                } else if x == 0.0 {
+                    match k {
+                        k if k < 2.0 => f64::INFINITY,
+                        2.0 => 0.5,
+                        _ => 0.0,
+                    }
+                } else {
+                    ((k / 2.0 - 1.0) * x.ln() - x / 2.0 - (k / 2.0) * 2f64.ln() - ln_gamma(k / 2.0)).exp()
+                }
+            }
+            (Family::ChiSquare, Function::Cumulative) => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    regularized_gamma_p(p1 / 2.0, x / 2.0)
+                }
+            }
+            (Family::ChiSquare, Function::Inverse) => {
+                invert(|c| regularized_gamma_p(p1 / 2.0, c / 2.0), x, 0.0, f64::INFINITY)
+            }
+            (Family::Binomial, Function::Density) => binomial_pmf(x, p1, p2),
+            (Family::Binomial, Function::Cumulative) => binomial_cdf(x, p1, p2),
+            (Family::Binomial, Function::Inverse) => {
+                invert_discrete(|k| binomial_cdf(k, p1, p2), x, Some(p1))
+            }
+            (Family::Poisson, Function::Density) => poisson_pmf(x, p1),
+            (Family::Poisson, Function::Cumulative) => poisson_cdf(x, p1),
+            (Family::Poisson, Function::Inverse) => invert_discrete(|k| poisson_cdf(k, p1), x, None),
+            (Family::Uniform, Function::Density) => {
+                if (p1..=p2).contains(&x) {
+                    1.0 / (p2 - p1)
+                } else {
+                    0.0
+                }
+            }
+            (Family::Uniform, Function::Cumulative) => ((x - p1) / (p2 - p1)).clamp(0.0, 1.0),
+            (Family::Uniform, Function::Inverse) => p1 + x * (p2 - p1),
+        })
+    }
+
+    fn validate(self) -> Result<(), String> {
+        let [p1, p2] = self.params;
+        let valid = match self.family {
+            Family::Normal => p2 > 0.0 && p1.is_finite() && p2.is_finite(),
+            Family::StudentT | Family::ChiSquare => p1 > 0.0 && p1.is_finite(),
+            Family::Binomial => p1 >= 0.0 && p1.fract() == 0.0 && (0.0..=1.0).contains(&p2),
+            Family::Poisson => p1 > 0.0 && p1.is_finite(),
+            Family::Uniform => p1 < p2 && p1.is_finite() && p2.is_finite(),
+        };
+        if valid {
+            return Ok(());
+        }
+        Err(match self.family {
+            Family::Normal => "Normal distribution needs σ > 0",
+            Family::StudentT => "Student's t needs ν > 0 degrees of freedom",
+            Family::ChiSquare => "Chi-square needs k > 0 degrees of freedom",
+            Family::Binomial => "Binomial needs an integer n ≥ 0 and 0 ≤ p ≤ 1",
+            Family::Poisson => "Poisson needs λ > 0",
+            Family::Uniform => "Uniform needs a < b",
+        }
+        .to_string())
+    }
+}
+
+/// Looks up an expression function such as `normcdf` or `poissonpmf`.
+pub fn lookup(name: &str) -> Option<(Family, Function)> {
+    Family::ALL.into_iter().find_map(|family| {
+        let suffix = name.strip_prefix(family.prefix())?;
+        let function = match suffix {
+            "pdf" | "pmf" => Function::Density,
+            "cdf" => Function::Cumulative,
+            "inv" => Function::Inverse,
+            _ => return None,
+        };
+        Some((family, function))
+    })
+}
+
+/// Evaluates an expression call: `args` holds x (or p for the inverse)
+/// followed by the parameters. The normal distribution may omit μ and σ.
+pub fn call(family: Family, function: Function, args: &[f64]) -> Result<f64, String> {
+    let count = family.parameters().len();
+    let mut distribution = Distribution::standard(family);
+    let name = distribution.function_name(function);
+    match args {
+        [x] if family == Family::Normal => distribution.evaluate(function, *x),
+        [x, params @ ..] if params.len() == count => {
+            distribution.params[..count].copy_from_slice(params);
+            distribution.evaluate(function, *x)
+        }
+        _ => Err(format!("{} takes {} arguments", name, count + 1)),
+    }
+}
+
+fn t_cdf(t: f64, nu: f64) -> f64 {
+    if t.is_infinite() {
+        return if t > 0.0 { 1.0 } else { 0.0 };
+    }
+    let tail = 0.5 * regularized_beta(nu / (nu + t * t), nu / 2.0, 0.5);
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+fn binomial_pmf(k: f64, n: f64, p: f64) -> f64 {
+    if k < 0.0 || k > n || k.fract() != 0.0 {
+        return 0.0;
+    }
+    // p = 0 or 1 put all mass on one outcome; avoid 0 · ln 0
+    if p == 0.0 || p == 1.0 {
+        let certain = if p == 0.0 { 0.0 } else { n };
+        return if k == certain { 1.0 } else { 0.0 };
+    }
+    let ln_choose = ln_gamma(n + 1.0) - ln_gamma(k + 1.0) - ln_gamma(n - k + 1.0);
+    (ln_choose + k * p.ln() + (n - k) * (1.0 - p).ln()).exp()
+}
+
+fn binomial_cdf(k: f64, n: f64, p: f64) -> f64 {
+    let k = k.floor();
+    if k < 0.0 {
+        0.0
+    } else if k >= n || p == 0.0 {
+        1.0
+    } else if p == 1.0 {
+        0.0
+    } else {
+        regularized_beta(1.0 - p, n - k, k + 1.0)
+    }
+}
+
+fn poisson_pmf(k: f64, lambda: f64) -> f64 {
+    if k < 0.0 || k.fract() != 0.0 {
+        return 0.0;
+    }
+    (k * lambda.ln() - lambda - ln_gamma(k + 1.0)).exp()
+}
+
+fn poisson_cdf(k: f64, lambda: f64) -> f64 {
+    let k = k.floor();
+    if k < 0.0 {
+        0.0
+    } else {
+        regularized_gamma_q(k + 1.0, lambda)
+    }
+}
+
+/// Φ⁻¹(p) by Acklam's rational approximation, refined with one Halley
+/// step to full double precision.
+fn inverse_standard_normal(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.024_25;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    let x = if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    };
+
+    let error = 0.5 * erfc(-x / SQRT_2) - p;
+    let u = error * (2.0 * PI).sqrt() * (x * x / 2.0).exp();
+    x - u / (1.0 + x * u / 2.0)
+}
+
+/// Inverts a continuous, increasing cdf on (lower, upper) by bracketing
+/// and bisection down to adjacent floating-point values.
+fn invert(cdf: impl Fn(f64) -> f64, p: f64, lower: f64, upper: f64) -> f64 {
+    if p == 0.0 {
+        return lower;
+    }
+    if p == 1.0 {
+        return upper;
+    }
+    let (mut lo, mut hi) = (lower.max(-1.0), upper.min(1.0));
+    while cdf(lo) > p {
+        lo *= 2.0;
+    }
+    while cdf(hi) < p {
+        hi *= 2.0;
+    }
+    loop {
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            return mid;
+        }
+        if cdf(mid) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+}
+
+/// Smallest integer k ≥ 0 with cdf(k) ≥ p, capped at `max` when given.
+fn invert_discrete(cdf: impl Fn(f64) -> f64, p: f64, max: Option<f64>) -> f64 {
+    let mut hi = 1.0;
+    while cdf(hi) < p && max.is_none_or(|max| hi < max) {
+        hi *= 2.0;
+    }
+    if let Some(max) = max {
+        hi = hi.min(max);
+    }
+    let mut lo = -1.0;
+    // Invariant: cdf(lo) < p ≤ cdf(hi)
+    while hi - lo > 1.0 {
+        let mid = ((lo + hi) / 2.0).floor();
+        if cdf(mid) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    hi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distribution(family: Family, params: [f64; 2]) -> Distribution {
+        Distribution { family, params }
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        let error = ((actual - expected) / expected).abs();
+        assert!(error <= tolerance, "{} vs {}: relative error {:e}", actual, expected, error);
+    }
+
+    // Standard normal table values
+    #[test]
+    fn normal_matches_z_table() {
+        let normal = Distribution::standard(Family::Normal);
+        assert_close(normal.evaluate(Function::Cumulative, 1.96).unwrap(), 0.975_002_104_851_780, 1e-14);
+        assert_close(normal.evaluate(Function::Cumulative, -3.0).unwrap(), 1.349_898_031_630_094_6e-3, 1e-13);
+        assert_close(normal.evaluate(Function::Density, 0.0).unwrap(), 0.398_942_280_401_432_7, 1e-15);
+        assert_close(normal.evaluate(Function::Inverse, 0.975).unwrap(), 1.959_963_984_540_054, 1e-14);
+        assert_close(normal.evaluate(Function::Inverse, 0.001).unwrap(), -3.090_232_306_167_813_5, 1e-14);
+        let scaled = distribution(Family::Normal, [100.0, 15.0]);
+        assert_close(scaled.evaluate(Function::Cumulative, 130.0).unwrap(), 0.977_249_868_051_820_8, 1e-14);
+    }
+
+    // Critical values from Student's t and χ² tables
+    #[test]
+    fn t_and_chi_square_match_critical_values() {
+        let t10 = distribution(Family::StudentT, [10.0, 0.0]);
+        assert_close(t10.evaluate(Function::Inverse, 0.975).unwrap(), 2.228_138_851_986_274, 1e-12);
+        assert_close(t10.evaluate(Function::Cumulative, 2.228_138_851_986_274).unwrap(), 0.975, 1e-13);
+        // ν = 1 is the Cauchy distribution
+        let cauchy = distribution(Family::StudentT, [1.0, 0.0]);
+        assert_close(cauchy.evaluate(Function::Cumulative, 1.0).unwrap(), 0.75, 1e-14);
+        assert_close(cauchy.evaluate(Function::Density, 0.0).unwrap(), 1.0 / PI, 1e-14);
+
+        let chi1 = distribution(Family::ChiSquare, [1.0, 0.0]);
+        assert_close(chi1.evaluate(Function::Inverse, 0.95).unwrap(), 3.841_458_820_694_124, 1e-12);
+        let chi10 = distribution(Family::ChiSquare, [10.0, 0.0]);
+        assert_close(chi10.evaluate(Function::Inverse, 0.95).unwrap(), 18.307_038_053_275_146, 1e-12);
+        // k = 2 is the exponential distribution with mean 2
+        let chi2 = distribution(Family::ChiSquare, [2.0, 0.0]);
+        assert_close(chi2.evaluate(Function::Cumulative, 3.0).unwrap(), 1.0 - (-1.5f64).exp(), 1e-14);
+        assert_close(chi2.evaluate(Function::Density, 3.0).unwrap(), 0.5 * (-1.5f64).exp(), 1e-14);
+    }
+
+    #[test]
+    fn discrete_distributions_match_exact_values() {
+        let coin = Distribution::standard(Family::Binomial);
+        assert_close(coin.evaluate(Function::Density, 3.0).unwrap(), 120.0 / 1024.0, 1e-14);
+        assert_close(coin.evaluate(Function::Cumulative, 5.0).unwrap(), 638.0 / 1024.0, 1e-14);
+        assert_eq!(coin.evaluate(Function::Inverse, 0.5).unwrap(), 5.0);
+        assert_eq!(coin.evaluate(Function::Inverse, 1.0).unwrap(), 10.0);
+
+        let poisson = distribution(Family::Poisson, [3.0, 0.0]);
+        assert_close(poisson.evaluate(Function::Density, 0.0).unwrap(), (-3.0f64).exp(), 1e-14);
+        assert_close(poisson.evaluate(Function::Cumulative, 2.0).unwrap(), 8.5 * (-3.0f64).exp(), 1e-14);
+        assert_eq!(poisson.evaluate(Function::Inverse, 0.5).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn uniform_and_parameter_errors() {
+        let uniform = distribution(Family::Uniform, [2.0, 6.0]);
+        assert_eq!(uniform.evaluate(Function::Density, 3.0).unwrap(), 0.25);
+        assert_eq!(uniform.evaluate(Function::Cumulative, 5.0).unwrap(), 0.75);
+        assert_eq!(uniform.evaluate(Function::Inverse, 0.5).unwrap(), 4.0);
+
+        assert!(distribution(Family::Normal, [0.0, 0.0]).evaluate(Function::Density, 0.0).is_err());
+        assert!(uniform.evaluate(Function::Inverse, 1.5).is_err());
+        assert!(call(Family::StudentT, Function::Cumulative, &[1.0]).is_err());
+        assert_eq!(call(Family::Normal, Function::Cumulative, &[0.0]), Ok(0.5));
+    }
+}
@@ -0,0 +1,405 @@
+use crate::complex::Complex;
+
+/// Longest mantissa accepted from the keypad; `f64` holds ~16 significant digits.
+const MAX_MANTISSA_DIGITS: usize = 16;
+const MAX_EXPONENT_DIGITS: usize = 3;
+
+/// A number as it is being typed: sign, integer digits, optional fraction
+/// digits and optional exponent. Every state renders and parses to a valid
+/// number, so intermediate input such as `12.` or `3e` is never lost and a
+/// lone `-` or `.` cannot occur.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct NumberEntry {
+    negative: bool,
+    /// Integer digits without leading zeros; empty means zero.
+    integer: String,
+    /// `Some` once the decimal key was pressed.
+    fraction: Option<String>,
+    /// `Some` once the EXP key was pressed.
+    exponent: Option<Exponent>,
+    /// Set by the `i` key in complex mode.
+    imaginary: bool,
+    /// Completed leading fields of a degree-minute-second or fraction
+    /// entry; the digits above are the field being typed.
+    fields: Option<Fields>,
+    /// Set by the ± key: the value typed before it, with the sign. The
+    /// digits above are then its uncertainty.
+    central: Option<Box<NumberEntry>>,
+}
+
+/// The kinds of number entered in several fields.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum FieldKind {
+    /// `12°34'56.7"`: degrees, minutes and seconds.
+    Dms,
+    /// `3/4` or `1 3/4`: an optional whole number, numerator and
+    /// denominator.
+    Fraction,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Fields {
+    kind: FieldKind,
+    /// Integer digits of each completed field; empty means zero.
+    digits: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+struct Exponent {
+    negative: bool,
+    digits: String,
+}
+
+impl NumberEntry {
+    fn mantissa_len(&self) -> usize {
+        self.integer.len() + self.fraction.as_ref().map_or(0, String::len)
+    }
+
+    pub fn push_digit(&mut self, digit: char) {
+        debug_assert!(digit.is_ascii_digit());
+        if let Some(exponent) = &mut self.exponent {
+            if exponent.digits.len() < MAX_EXPONENT_DIGITS && !(exponent.digits.is_empty() && digit == '0') {
+                exponent.digits.push(digit);
+                if !exponent.keeps_in_range(&self.integer, self.fraction.as_deref()) {
+                    exponent.digits.pop();
+                }
+            }
+            return;
+        }
+        if self.mantissa_len() >= MAX_MANTISSA_DIGITS {
+            return;
+        }
+        match &mut self.fraction {
+            Some(fraction) => fraction.push(digit),
+            None if self.integer.is_empty() && digit == '0' => {}
+            None => self.integer.push(digit),
+        }
+    }
+
+    pub fn push_decimal(&mut self) {
+        let in_fraction = self.fields.as_ref().is_some_and(|f| f.kind == FieldKind::Fraction);
+        if self.exponent.is_none() && self.fraction.is_none() && !in_fraction {
+            self.fraction = Some(String::new());
+        }
+    }
+
+    /// Starts the exponent part. Ignored when already entering one and in
+    /// entries with several fields.
+    pub fn start_exponent(&mut self) {
+        if self.exponent.is_none() && self.fields.is_none() {
+            self.exponent = Some(Exponent::default());
+        }
+    }
+
+    /// `i` key: marks the entry as imaginary, or back to real. Entries with
+    /// several fields or an uncertainty stay real.
+    pub fn toggle_imaginary(&mut self) {
+        if self.fields.is_none() && self.central.is_none() {
+            self.imaginary = !self.imaginary;
+        }
+    }
+
+    /// `°'"` and `a b/c` keys: completes the field being typed and starts
+    /// the next one: degrees, then minutes, then seconds; or numerator,
+    /// then denominator, where a second `a b/c` makes the first field the
+    /// whole number. Ignored once the last field is reached, after a
+    /// decimal point, an exponent or `i`, and when mixing the two kinds.
+    pub fn next_field(&mut self, kind: FieldKind) {
+        let completed = self.fields.as_ref().map_or(0, |f| f.digits.len());
+        let same_kind = self.fields.as_ref().is_none_or(|f| f.kind == kind);
+        let ends = self.fraction.is_some() || self.exponent.is_some() || self.imaginary || self.central.is_some();
+        if !same_kind || ends || completed >= 2 {
+            return;
+        }
+        let digits = std::mem::take(&mut self.integer);
+        self.fields.get_or_insert(Fields { kind, digits: Vec::new() }).digits.push(digits);
+    }
+
+    /// ± key in uncertainty mode: the number typed so far becomes the
+    /// value and the digits that follow its uncertainty. Ignored for
+    /// imaginary and multi-field entries and once an uncertainty is started.
+    pub fn start_uncertainty(&mut self) {
+        if !self.imaginary && self.fields.is_none() && self.central.is_none() {
+            let central = std::mem::take(self);
+            self.central = Some(Box::new(central));
+        }
+    }
+
+    /// Flips the sign of the exponent while it is being entered, otherwise
+    /// the sign of the number; an uncertainty has no sign of its own. An
+    /// exponent keeps its sign when the other one would leave the range of
+    /// `f64`.
+    pub fn toggle_sign(&mut self) {
+        match (&mut self.exponent, &mut self.central) {
+            (Some(exponent), _) => {
+                exponent.negative = !exponent.negative;
+                if !exponent.keeps_in_range(&self.integer, self.fraction.as_deref()) {
+                    exponent.negative = !exponent.negative;
+                }
+            }
+            (None, Some(central)) => central.negative = !central.negative,
+            (None, None) => self.negative = !self.negative,
+        }
+    }
+
+    /// Removes the most recently typed part. Removing the last digit leaves
+    /// `0`, never an empty or sign-only entry.
+    pub fn backspace(&mut self) {
+        let uncertainty_started = !self.integer.is_empty() || self.fraction.is_some() || self.exponent.is_some();
+        if !uncertainty_started {
+            if let Some(central) = self.central.take() {
+                *self = *central;
+                return;
+            }
+        }
+        if self.imaginary {
+            self.imaginary = false;
+            return;
+        }
+        if let Some(exponent) = &mut self.exponent {
+            if exponent.digits.pop().is_none() {
+                self.exponent = None;
+            }
+            return;
+        }
+        if let Some(fields) = &mut self.fields {
+            if self.integer.is_empty() && self.fraction.is_none() {
+                // Back into the previous field
+                self.integer = fields.digits.pop().unwrap_or_default();
+                if fields.digits.is_empty() {
+                    self.fields = None;
+                }
+                return;
+            }
+        }
+        match &mut self.fraction {
+            Some(fraction) => {
+                if fraction.pop().is_none() {
+                    self.fraction = None;
+                }
+            }
+            None => {
+                self.integer.pop();
+            }
+        }
+        if self.is_zero() && self.fraction.is_none() {
+            self.negative = false;
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.fields.is_none()
+            && self.central.is_none()
+            && self.integer.is_empty()
+            && self.fraction.as_ref().is_none_or(|f| f.chars().all(|c| c == '0'))
+    }
+
+    pub fn value(&self) -> Complex {
+        if let Some(central) = &self.central {
+            return central.value();
+        }
+        if let Some(fields) = &self.fields {
+            let magnitude = fields.value(self.current_field());
+            return Complex::from(if self.negative { -magnitude } else { magnitude });
+        }
+        let mut text = self.text();
+        // An exponent without digits does not scale the mantissa yet
+        if self.exponent.as_ref().is_some_and(|e| e.digits.is_empty()) {
+            text.truncate(text.find('e').unwrap_or(text.len()));
+        }
+        let magnitude = text.parse().unwrap_or(0.0);
+        if !self.imaginary {
+            Complex::from(magnitude)
+        } else if self.integer.is_empty() && self.fraction.is_none() {
+            // A bare `i` means 1·i
+            Complex::new(0.0, if self.negative { -1.0 } else { 1.0 })
+        } else {
+            Complex::new(0.0, magnitude)
+        }
+    }
+
+    /// The field being typed as a number.
+    fn current_field(&self) -> f64 {
+        let fraction = self.fraction.as_deref().unwrap_or_default();
+        format!("0{}.{}0", self.integer, fraction).parse().unwrap_or(0.0)
+    }
+
+    /// The uncertainty typed after the ± key, if any.
+    pub fn uncertainty(&self) -> Option<f64> {
+        self.central.as_ref()?;
+        let sigma = NumberEntry { central: None, ..self.clone() };
+        Some(sigma.value().re.abs())
+    }
+
+    /// Significant figures as typed: `2.50` and `0.00250` have three,
+    /// `2500` has two and `2500.` four. None for imaginary entries and
+    /// entries with several fields.
+    pub fn significant_figures(&self) -> Option<u32> {
+        if self.imaginary || self.fields.is_some() || self.central.is_some() {
+            return None;
+        }
+        let digits = match &self.fraction {
+            Some(fraction) => format!("{}{}", self.integer, fraction).trim_start_matches('0').len(),
+            None => self.integer.trim_end_matches('0').len(),
+        };
+        Some(digits.max(1) as u32)
+    }
+
+    /// Whether the `i` key marked this entry as imaginary.
+    pub fn is_imaginary(&self) -> bool {
+        self.imaginary
+    }
+
+    /// Canonical text of the number without the imaginary marker, with `.`
+    /// as decimal separator, e.g. `-12.5e-3`. Incomplete parts are shown as
+    /// typed: `12.`, `3e`, `3e-`, `12°`, `1 3/`, `5.2 ± `; a bare `i` has
+    /// no digits at all.
+    pub fn text(&self) -> String {
+        if let Some(central) = &self.central {
+            let sigma = NumberEntry { central: None, ..self.clone() };
+            let started = !self.integer.is_empty() || self.fraction.is_some() || self.exponent.is_some();
+            return format!("{} ± {}", central.text(), if started { sigma.text() } else { String::new() });
+        }
+        let mut text = String::new();
+        if self.negative {
+            text.push('-');
+        }
+        if let Some(fields) = &self.fields {
+            text.push_str(&fields.text());
+            // A field not started yet is left blank rather than shown as 0
+            if !self.integer.is_empty() || self.fraction.is_some() {
+                text.push_str(if self.integer.is_empty() { "0" } else { &self.integer });
+            }
+            if let Some(fraction) = &self.fraction {
+                text.push('.');
+                text.push_str(fraction);
+            }
+            return text;
+        }
+        if self.imaginary && self.integer.is_empty() && self.fraction.is_none() {
+            return text;
+        }
+        if self.integer.is_empty() {
+            text.push('0');
+        } else {
+            text.push_str(&self.integer);
+        }
+        if let Some(fraction) = &self.fraction {
+            text.push('.');
+            text.push_str(fraction);
+        }
+        if let Some(exponent) = &self.exponent {
+            text.push('e');
+            if exponent.negative {
+                text.push('-');
+            }
+            text.push_str(&exponent.digits);
+        }
+        text
+    }
+}
+
+impl Exponent {
+    /// Whether the mantissa `integer.fraction` scaled by this exponent is
+    /// still a normal `f64`. Exponent digits that would overflow to `∞` or
+    /// underflow towards `0` are refused as they are typed, as the policy
+    /// would refuse such a result.
+    fn keeps_in_range(&self, integer: &str, fraction: Option<&str>) -> bool {
+        let mantissa = format!("0{}.{}", integer, fraction.unwrap_or_default());
+        if self.digits.is_empty() || mantissa.parse::<f64>().unwrap_or(0.0) == 0.0 {
+            return true;
+        }
+        let sign = if self.negative { "-" } else { "" };
+        let scaled: f64 = format!("{}e{}{}", mantissa, sign, self.digits).parse().unwrap_or(0.0);
+        scaled.is_normal()
+    }
+}
+
+impl Fields {
+    fn number(digits: &str) -> f64 {
+        digits.parse().unwrap_or(0.0)
+    }
+
+    /// The value with `current` as the field being typed. A fraction
+    /// without a denominator yet is its whole number, or its numerator
+    /// when there is none.
+    fn value(&self, current: f64) -> f64 {
+        match self.kind {
+            FieldKind::Dms => self
+                .digits
+                .iter()
+                .map(|digits| Self::number(digits))
+                .chain(std::iter::once(current))
+                .zip([1.0, 60.0, 3600.0])
+                .map(|(field, divisor)| field / divisor)
+                .sum(),
+            FieldKind::Fraction => match self.digits.as_slice() {
+                [numerator] if current == 0.0 => Self::number(numerator),
+                [numerator] => Self::number(numerator) / current,
+                [whole, _] if current == 0.0 => Self::number(whole),
+                [whole, numerator] => Self::number(whole) + Self::number(numerator) / current,
+                _ => current,
+            },
+        }
+    }
+
+    /// The completed fields with their marks, e.g. `12°34'` or `1 3/`.
+    fn text(&self) -> String {
+        let digits: Vec<&str> = self.digits.iter().map(|d| if d.is_empty() { "0" } else { d.as_str() }).collect();
+        match (self.kind, digits.as_slice()) {
+            (FieldKind::Dms, [degrees]) => format!("{}°", degrees),
+            (FieldKind::Dms, [degrees, minutes]) => format!("{}°{}'", degrees, minutes),
+            (FieldKind::Fraction, [numerator]) => format!("{}/", numerator),
+            (FieldKind::Fraction, [whole, numerator]) => format!("{} {}/", whole, numerator),
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(keys: &str) -> NumberEntry {
+        let mut entry = NumberEntry::default();
+        for key in keys.chars() {
+            match key {
+                '0'..='9' => entry.push_digit(key),
+                '.' => entry.push_decimal(),
+                'e' => entry.start_exponent(),
+                '-' => entry.toggle_sign(),
+                _ => unreachable!("no key for {:?}", key),
+            }
+        }
+        entry
+    }
+
+    #[test]
+    fn exponent_digits_that_overflow_are_refused() {
+        assert_eq!(typed("1e308").text(), "1e308");
+        assert_eq!(typed("1e999").text(), "1e99");
+        assert_eq!(typed("2e309").text(), "2e30");
+        assert_eq!(typed("1.7e308").value().re, 1.7e308);
+        assert_eq!(typed("1.8e308").text(), "1.8e30");
+        assert!(typed("9e999").value().re.is_finite());
+    }
+
+    #[test]
+    fn exponent_digits_that_underflow_are_refused() {
+        assert_eq!(typed("1e-307").text(), "1e-307");
+        assert_eq!(typed("1e-999").text(), "1e-99");
+        assert_eq!(typed("1e-308").text(), "1e-30");
+        assert_eq!(typed("0.001e-306").text(), "0.001e-30");
+        // Zero is exact at any scale
+        assert_eq!(typed("0e999").text(), "0e999");
+    }
+
+    #[test]
+    fn exponent_sign_stays_when_flipping_leaves_the_range() {
+        // 1e-308 would be subnormal
+        assert_eq!(typed("1e308-").text(), "1e308");
+        assert_eq!(typed("1e300-").text(), "1e-300");
+        assert_eq!(typed("5e-308-").text(), "5e-308");
+        assert_eq!(typed("5e-30-").text(), "5e30");
+    }
+}
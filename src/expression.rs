@@ -0,0 +1,638 @@
+use crate::calculus::Calculus;
+use crate::calculator::{erf, erfc, NumericOptions, Operation};
+use crate::distribution::{self, Family, Function};
+use crate::integer::IntegerFunction;
+use crate::locale::NumberLocale;
+use crate::units::{self, Quantity};
+use crate::value::{self, Value, VectorFunction};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+/// Parsed arithmetic expression, evaluated with the same `calculate`
+/// rules as the keypad.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    /// Integer literal with more digits than `f64` holds exactly.
+    BigInteger(BigInt),
+    Variable(String),
+    Negate(Box<Expr>),
+    Unary(Operation, Box<Expr>),
+    Binary(Operation, Box<Expr>, Box<Expr>),
+    /// Function call with a parenthesized argument list, e.g. `normcdf(x, μ, σ)`.
+    Call(Builtin, Vec<Expr>),
+    /// Derivative, integral, Σ, Π or limit of the body over a bound
+    /// variable, e.g. `integral(sin(x), x, 0, pi)`.
+    Calculus(Calculus, Box<Expr>, String, Vec<Expr>),
+    /// Vector literal such as `[1, 2, 3]`.
+    Vector(Vec<Expr>),
+    /// Unit conversion, e.g. `60 mph to m/s`.
+    Convert(Box<Expr>, Box<Expr>),
+    /// Number-theory function whose arguments are evaluated as exact big
+    /// integers, e.g. `modpow(2, 10^30, 1000000007)`.
+    Integer(IntegerFunction, Vec<Expr>),
+}
+
+/// Functions that take an argument list, unlike the keypad operations
+/// which may be applied without parentheses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Builtin {
+    Erf,
+    Erfc,
+    Distribution(Family, Function),
+    Vector(VectorFunction),
+}
+
+impl Builtin {
+    fn lookup(name: &str) -> Option<Builtin> {
+        match name {
+            "erf" => Some(Builtin::Erf),
+            "erfc" => Some(Builtin::Erfc),
+            _ => distribution::lookup(name)
+                .map(|(family, function)| Builtin::Distribution(family, function))
+                .or_else(|| VectorFunction::lookup(name).map(Builtin::Vector)),
+        }
+    }
+
+    fn call(self, args: &[Value], options: NumericOptions) -> Result<Value, String> {
+        if let Builtin::Vector(function) = self {
+            return function.call(args, options);
+        }
+        let args = args.iter().map(Value::scalar).collect::<Result<Vec<_>, _>>()?;
+        let result = match (self, args.as_slice()) {
+            (Builtin::Erf, [x]) => Ok(erf(*x)),
+            (Builtin::Erfc, [x]) => Ok(erfc(*x)),
+            (Builtin::Erf | Builtin::Erfc, _) => Err("erf and erfc take 1 argument".to_string()),
+            (Builtin::Distribution(family, function), args) => distribution::call(family, function, args),
+            (Builtin::Vector(_), _) => unreachable!("handled above"),
+        };
+        result.map(Value::Scalar)
+    }
+}
+
+/// Variable bindings and numeric options used during evaluation.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub options: NumericOptions,
+    variables: Vec<(String, f64)>,
+    /// Exchange rates as units of each currency per base unit.
+    currencies: Vec<(String, f64)>,
+}
+
+impl Context {
+    pub fn new(options: NumericOptions) -> Self {
+        Self {
+            options,
+            variables: Vec::new(),
+            currencies: Vec::new(),
+        }
+    }
+
+    /// Makes the given currency codes usable as units.
+    pub fn with_currencies(mut self, currencies: Vec<(String, f64)>) -> Self {
+        self.currencies = currencies;
+        self
+    }
+
+    fn currency(&self, code: &str) -> Option<Quantity> {
+        self.currencies
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(code))
+            .map(|(c, rate)| units::currency(c, *rate))
+    }
+
+    /// Binds `name`, replacing any earlier value. Names are
+    /// case-insensitive.
+    pub fn set_variable(&mut self, name: &str, value: f64) {
+        let name = name.to_lowercase();
+        match self.variables.iter_mut().find(|(n, _)| *n == name) {
+            Some(slot) => slot.1 = value,
+            None => self.variables.push((name, value)),
+        }
+    }
+
+    pub fn variable(&self, name: &str) -> Option<f64> {
+        let name = name.to_lowercase();
+        self.variables
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+impl Expr {
+    /// Evaluates an expression that must produce a number.
+    pub fn eval(&self, ctx: &Context) -> Result<f64, String> {
+        self.value(ctx)?.scalar()
+    }
+
+    /// Evaluates to a number, a vector or a quantity with units.
+    pub fn value(&self, ctx: &Context) -> Result<Value, String> {
+        match self {
+            Expr::Number(value) => Ok(Value::Scalar(*value)),
+            Expr::BigInteger(n) => Ok(Value::Scalar(n.to_f64().unwrap_or(f64::NAN))),
+            // Variables and constants take precedence over unit symbols
+            Expr::Variable(name) => match ctx.variable(name).or_else(|| constant(&name.to_lowercase())) {
+                Some(value) => Ok(Value::Scalar(value)),
+                None => units::lookup(name)
+                    .or_else(|| ctx.currency(name))
+                    .map(Value::Quantity)
+                    .ok_or_else(|| format!("Unknown variable or unit '{}'", name)),
+            },
+            Expr::Negate(inner) => inner.value(ctx)?.negate(),
+            Expr::Unary(op, inner) => value::calculate(*op, &inner.value(ctx)?, None, ctx.options),
+            Expr::Binary(op, lhs, rhs) => {
+                value::calculate(*op, &lhs.value(ctx)?, Some(&rhs.value(ctx)?), ctx.options)
+            }
+            Expr::Call(builtin, args) => {
+                let args = args.iter().map(|arg| arg.value(ctx)).collect::<Result<Vec<_>, _>>()?;
+                builtin.call(&args, ctx.options)
+            }
+            Expr::Calculus(operation, body, variable, bounds) => {
+                let bounds = bounds.iter().map(|bound| bound.eval(ctx)).collect::<Result<Vec<_>, _>>()?;
+                Ok(Value::Scalar(operation.evaluate(body, variable, &bounds, ctx)?.value))
+            }
+            Expr::Vector(components) => {
+                let components = components.iter().map(|c| c.eval(ctx)).collect::<Result<Vec<_>, _>>()?;
+                Ok(Value::Vector(components))
+            }
+            Expr::Convert(source, target) => units::convert(&source.value(ctx)?, &target.value(ctx)?),
+            Expr::Integer(function, args) => function.evaluate(args, ctx),
+        }
+    }
+}
+
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
+/// Whether an identifier names a unit, or looks like a currency code,
+/// rather than a constant or function.
+fn is_unit(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let currency = name.len() == 3 && name.chars().all(|c| c.is_ascii_uppercase());
+    constant(&lower).is_none()
+        && unary_function(&lower).is_none()
+        && Builtin::lookup(&lower).is_none()
+        && Calculus::lookup(&lower).is_none()
+        && (currency || units::lookup(name).is_some())
+}
+
+fn unary_function(name: &str) -> Option<Operation> {
+    match name {
+        "sqrt" | "√" => Some(Operation::SquareRoot),
+        "sin" => Some(Operation::Sin),
+        "cos" => Some(Operation::Cos),
+        "tan" => Some(Operation::Tan),
+        "log" => Some(Operation::Log),
+        "ln" => Some(Operation::Ln),
+        "floor" => Some(Operation::Floor),
+        "ceil" => Some(Operation::Ceil),
+        "round" => Some(Operation::Round),
+        "trunc" => Some(Operation::Trunc),
+        "abs" => Some(Operation::Abs),
+        "sign" => Some(Operation::Sign),
+        "frac" => Some(Operation::FractionalPart),
+        _ => None,
+    }
+}
+
+/// Parses and evaluates `text` in one step.
+pub fn evaluate(text: &str, locale: &NumberLocale, ctx: &Context) -> Result<f64, String> {
+    parse(text, locale)?.eval(ctx)
+}
+
+/// Like `evaluate`, but the result may be a vector or carry units.
+pub fn evaluate_value(text: &str, locale: &NumberLocale, ctx: &Context) -> Result<Value, String> {
+    parse(text, locale)?.value(ctx)
+}
+
+/// Deepest nesting of brackets, signs, powers and function arguments the
+/// parser follows; pasted input could otherwise exhaust the stack.
+const MAX_DEPTH: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    BigInteger(BigInt),
+    Ident(String),
+    Symbol(char),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Separator,
+}
+
+/// Parses an expression. Numbers use the locale's decimal separator; when
+/// that is a comma, function arguments are separated with `;` instead.
+pub fn parse(text: &str, locale: &NumberLocale) -> Result<Expr, String> {
+    let tokens = tokenize(text, locale)?;
+    if tokens.is_empty() {
+        return Err("Expression is empty".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0, depth: 0 };
+    let mut expr = parser.expression()?;
+    if parser.eat_conversion() {
+        expr = Expr::Convert(Box::new(expr), Box::new(parser.expression()?));
+    }
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("Unexpected {}", describe(token))),
+    }
+}
+
+fn tokenize(text: &str, locale: &NumberLocale) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let decimal = locale.decimal_separator;
+    let separator = if decimal == ',' { ';' } else { ',' };
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch.is_whitespace() {
+            i += 1;
+        } else if ch.is_ascii_digit() || (ch == decimal && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == decimal) {
+                i += 1;
+            }
+            // Exponent only when digits follow, so `2e` still reads as 2·e
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && matches!(chars[j], '+' | '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let literal: String = chars[start..i]
+                .iter()
+                .map(|&c| if c == decimal { '.' } else { c })
+                .collect();
+            // Long integers are kept exact for the number-theory functions
+            if literal.len() > 15 && literal.chars().all(|c| c.is_ascii_digit()) {
+                tokens.push(Token::BigInteger(literal.parse().map_err(|_| format!("Invalid number '{}'", literal))?));
+                continue;
+            }
+            let value = literal
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number '{}'", literal))?;
+            tokens.push(Token::Number(value));
+        } else if ch.is_alphabetic() || ch == '_' || ch == '°' {
+            let start = i;
+            // ² is alphanumeric but is the squaring operator: x², 5 m²
+            while i < chars.len() && ((chars[i].is_alphanumeric() && chars[i] != '²') || matches!(chars[i], '_' | '°')) {
+                i += 1;
+            }
+            // Case is kept for unit symbols such as MB and mb
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if ch == '→' || (ch == '-' && chars.get(i + 1) == Some(&'>')) {
+            tokens.push(Token::Symbol('→'));
+            i += if ch == '→' { 1 } else { 2 };
+        } else if ch == '±' || chars[i..].starts_with(&['+', '/', '-']) {
+            tokens.push(Token::Symbol('±'));
+            i += if ch == '±' { 1 } else { 3 };
+        } else {
+            tokens.push(match ch {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                '[' => Token::LBracket,
+                ']' => Token::RBracket,
+                '*' | '×' | '·' => Token::Symbol('*'),
+                '/' | '÷' => Token::Symbol('/'),
+                '−' => Token::Symbol('-'),
+                '√' => Token::Ident("√".to_string()),
+                '+' | '-' | '^' | '!' | '%' | '²' => Token::Symbol(ch),
+                c if c == separator || c == ';' => Token::Separator,
+                other => return Err(format!("Unexpected character '{}'", other)),
+            });
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(value) => format!("number {}", value),
+        Token::BigInteger(value) => format!("number {}", value),
+        Token::Ident(name) => format!("'{}'", name),
+        Token::Symbol(ch) => format!("'{}'", ch),
+        Token::LParen => "'('".to_string(),
+        Token::RParen => "')'".to_string(),
+        Token::LBracket => "'['".to_string(),
+        Token::RBracket => "']'".to_string(),
+        Token::Separator => "argument separator".to_string(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_symbol(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `to` or `→` before a conversion target.
+    fn eat_conversion(&mut self) -> bool {
+        let found = match self.peek() {
+            Some(Token::Ident(name)) => name.eq_ignore_ascii_case("to"),
+            Some(Token::Symbol('→')) => true,
+            _ => false,
+        };
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn is_conversion(&self) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name.eq_ignore_ascii_case("to"))
+    }
+
+    /// Runs `parse` one nesting level deeper.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err("Expression is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {} but found {}", describe(&expected), describe(&token))),
+            None => Err(format!("Expected {} at end of expression", describe(&expected))),
+        }
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = if self.eat_symbol('+') {
+                Operation::Add
+            } else if self.eat_symbol('-') {
+                Operation::Subtract
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    // term := measured (('*' | '/') measured | implicit product)*
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.measured()?;
+        loop {
+            let op = if self.eat_symbol('*') {
+                Operation::Multiply
+            } else if self.eat_symbol('/') {
+                Operation::Divide
+            } else if matches!(self.peek(), Some(Token::Ident(_) | Token::LParen | Token::LBracket)) && !self.is_conversion() {
+                // Implicit multiplication: 2pi, 3(4 + 5), 2[1, 2], 5 km
+                Operation::Multiply
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.measured()?));
+        }
+    }
+
+    // measured := unary ('±' unary)?
+    fn measured(&mut self) -> Result<Expr, String> {
+        let value = self.unary()?;
+        if self.eat_symbol('±') {
+            Ok(Expr::Binary(Operation::PlusMinus, Box::new(value), Box::new(self.unary()?)))
+        } else {
+            Ok(value)
+        }
+    }
+
+    // unary := ('-' | '+') unary | power
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_symbol('-') {
+            Ok(Expr::Negate(Box::new(self.nested(Self::unary)?)))
+        } else if self.eat_symbol('+') {
+            self.nested(Self::unary)
+        } else {
+            self.power()
+        }
+    }
+
+    // power := postfix ('^' unary)?, right-associative
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.postfix()?;
+        if self.eat_symbol('^') {
+            Ok(Expr::Binary(Operation::Power, Box::new(base), Box::new(self.nested(Self::unary)?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    // postfix := primary ('!' | '%' | '²')*
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            let op = if self.eat_symbol('!') {
+                Operation::Factorial
+            } else if self.eat_symbol('%') {
+                Operation::Percentage
+            } else if self.eat_symbol('²') {
+                Operation::Square
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Unary(op, Box::new(expr));
+        }
+    }
+
+    // arguments := '(' list ')'
+    fn arguments(&mut self) -> Result<Vec<Expr>, String> {
+        self.expect(Token::LParen)?;
+        let args = self.nested(Self::list)?;
+        self.expect(Token::RParen)?;
+        Ok(args)
+    }
+
+    // list := expression (separator expression)*
+    fn list(&mut self) -> Result<Vec<Expr>, String> {
+        let mut items = vec![self.expression()?];
+        while self.peek() == Some(&Token::Separator) {
+            self.pos += 1;
+            items.push(self.expression()?);
+        }
+        Ok(items)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            // A unit right after a number binds before division, so
+            // `100 km / 2 h` is a speed and `5 km^2` is an area
+            Some(Token::Number(value)) => match self.peek() {
+                Some(Token::Ident(name)) if is_unit(name) => {
+                    Ok(Expr::Binary(Operation::Multiply, Box::new(Expr::Number(value)), Box::new(self.power()?)))
+                }
+                _ => Ok(Expr::Number(value)),
+            },
+            Some(Token::BigInteger(value)) => Ok(Expr::BigInteger(value)),
+            Some(Token::LParen) => {
+                let inner = self.nested(Self::expression)?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            // A single bracketed expression is only grouping: [2 + 3]
+            Some(Token::LBracket) => {
+                let mut items = self.nested(Self::list)?;
+                self.expect(Token::RBracket)?;
+                if items.len() == 1 {
+                    Ok(items.remove(0))
+                } else {
+                    Ok(Expr::Vector(items))
+                }
+            }
+            Some(Token::Ident(name)) => {
+                let lower = name.to_lowercase();
+                if let Some(op) = unary_function(&lower) {
+                    // Parentheses are optional for a single argument: sin 30, √2
+                    let argument = if self.peek() == Some(&Token::LParen) {
+                        self.nested(Self::primary)?
+                    } else {
+                        self.nested(Self::power)?
+                    };
+                    Ok(Expr::Unary(op, Box::new(argument)))
+                } else if let Some(function) = IntegerFunction::lookup(&lower) {
+                    Ok(Expr::Integer(function, self.arguments()?))
+                } else if let Some(builtin) = Builtin::lookup(&lower) {
+                    Ok(Expr::Call(builtin, self.arguments()?))
+                } else if let Some(operation) = Calculus::lookup(&lower) {
+                    let mut args = self.arguments()?.into_iter();
+                    let usage = || {
+                        let bounds = if operation.bounds() == 1 { "a point" } else { "two bounds" };
+                        format!("{} takes an expression, a variable and {}", name, bounds)
+                    };
+                    let body = args.next().ok_or_else(usage)?;
+                    let Some(Expr::Variable(variable)) = args.next() else {
+                        return Err(usage());
+                    };
+                    let bounds: Vec<Expr> = args.collect();
+                    if bounds.len() != operation.bounds() {
+                        return Err(usage());
+                    }
+                    Ok(Expr::Calculus(operation, Box::new(body), variable, bounds))
+                } else {
+                    Ok(Expr::Variable(name))
+                }
+            }
+            Some(token) => Err(format!("Unexpected {}", describe(&token))),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> Result<f64, String> {
+        evaluate(text, &NumberLocale::default(), &Context::new(NumericOptions::default()))
+    }
+
+    #[test]
+    fn operators_follow_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("2 * 3 ^ 2"), Ok(18.0));
+        assert_eq!(eval("10 - 4 - 3"), Ok(3.0));
+        assert_eq!(eval("12 / 3 / 2"), Ok(2.0));
+        assert_eq!(eval("3! + 2²"), Ok(10.0));
+        assert_eq!(eval("50% * 8"), Ok(4.0));
+    }
+
+    #[test]
+    fn unary_minus_binds_looser_than_powers() {
+        assert_eq!(eval("-2^2"), Ok(-4.0));
+        assert_eq!(eval("(-2)^2"), Ok(4.0));
+        assert_eq!(eval("2^-1"), Ok(0.5));
+        assert_eq!(eval("3 - -2"), Ok(5.0));
+        assert_eq!(eval("--3"), Ok(3.0));
+        assert_eq!(eval("+3 * -2"), Ok(-6.0));
+    }
+
+    #[test]
+    fn powers_are_right_associative() {
+        assert_eq!(eval("2^3^2"), Ok(512.0));
+        assert_eq!(eval("(2^3)^2"), Ok(64.0));
+        assert_eq!(eval("2^-2^2"), Ok(1.0 / 16.0));
+    }
+
+    #[test]
+    fn juxtaposition_multiplies() {
+        assert_eq!(eval("2pi"), Ok(2.0 * std::f64::consts::PI));
+        assert_eq!(eval("3(4 + 5)"), Ok(27.0));
+        assert_eq!(eval("(1 + 1)(2 + 2)"), Ok(8.0));
+        // Implicit products share the precedence of `*` and `/`
+        assert_eq!(eval("6 / 2(3)"), Ok(9.0));
+        assert_eq!(eval("2 sqrt 16"), Ok(8.0));
+        let mut ctx = Context::new(NumericOptions::default());
+        ctx.set_variable("x", 3.0);
+        assert_eq!(evaluate("2x^2", &NumberLocale::default(), &ctx), Ok(18.0));
+    }
+
+    #[test]
+    fn malformed_input_is_described() {
+        assert_eq!(eval("   "), Err("Expression is empty".to_string()));
+        assert_eq!(eval("1 + $"), Err("Unexpected character '$'".to_string()));
+        assert_eq!(eval("(1 + 2"), Err("Expected ')' at end of expression".to_string()));
+        assert_eq!(eval("[1, 2"), Err("Expected ']' at end of expression".to_string()));
+        assert_eq!(eval("1 + 2)"), Err("Unexpected ')'".to_string()));
+        assert_eq!(eval("1 +"), Err("Unexpected end of expression".to_string()));
+        assert_eq!(eval("erf(1, 2)"), Err("erf and erfc take 1 argument".to_string()));
+        assert_eq!(eval("foo + 1"), Err("Unknown variable or unit 'foo'".to_string()));
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&nested(MAX_DEPTH)), Ok(1.0));
+        let too_deep = Err("Expression is nested too deeply".to_string());
+        assert_eq!(eval(&nested(MAX_DEPTH + 1)), too_deep);
+        assert_eq!(eval(&nested(300_000)), too_deep);
+        assert_eq!(eval(&format!("{}1", "-".repeat(300_000))), too_deep);
+        assert_eq!(eval(&format!("{}1", "2^".repeat(300_000))), too_deep);
+        assert_eq!(eval(&format!("{}1", "sin ".repeat(300_000))), too_deep);
+        assert_eq!(eval(&format!("{}1{}", "[".repeat(300_000), "]".repeat(300_000))), too_deep);
+    }
+}
@@ -0,0 +1,587 @@
+use crate::calculator::full_precision;
+use crate::locale::NumberLocale;
+
+/// Longest amortization schedule, in periods.
+const MAX_PERIODS: f64 = 10_000.0;
+
+/// Longest depreciation schedule, in years.
+const MAX_LIFE: u32 = 100;
+
+/// The five time-value-of-money variables; any one can be solved from
+/// the other four.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TvmVariable {
+    Periods,
+    Rate,
+    PresentValue,
+    Payment,
+    FutureValue,
+}
+
+impl TvmVariable {
+    pub const ALL: [TvmVariable; 5] = [
+        TvmVariable::Periods,
+        TvmVariable::Rate,
+        TvmVariable::PresentValue,
+        TvmVariable::Payment,
+        TvmVariable::FutureValue,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TvmVariable::Periods => "N",
+            TvmVariable::Rate => "I/Y",
+            TvmVariable::PresentValue => "PV",
+            TvmVariable::Payment => "PMT",
+            TvmVariable::FutureValue => "FV",
+        }
+    }
+}
+
+/// Time-value-of-money worksheet. Cash received is positive and cash paid
+/// is negative, so a loan has a positive PV and a negative PMT.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Tvm {
+    pub periods: f64,
+    /// Nominal annual interest rate in percent.
+    pub rate: f64,
+    pub present_value: f64,
+    pub payment: f64,
+    pub future_value: f64,
+    pub periods_per_year: f64,
+    /// Payments at the start of each period (annuity due).
+    pub begin: bool,
+}
+
+impl Default for Tvm {
+    fn default() -> Self {
+        Self {
+            periods: 360.0,
+            rate: 6.0,
+            present_value: 200_000.0,
+            payment: 0.0,
+            future_value: 0.0,
+            periods_per_year: 12.0,
+            begin: false,
+        }
+    }
+}
+
+/// One period of an amortization schedule. Interest and principal are the
+/// parts of the payment, with the payment's sign.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmortizationRow {
+    pub period: usize,
+    pub payment: f64,
+    pub interest: f64,
+    pub principal: f64,
+    pub balance: f64,
+}
+
+/// (1 + i)^n and ((1 + i)^n − 1)/i, accurate for small rates.
+fn growth(i: f64, n: f64) -> (f64, f64) {
+    let exponent = n * i.ln_1p();
+    let annuity = if i == 0.0 { n } else { exponent.exp_m1() / i };
+    (exponent.exp(), annuity)
+}
+
+impl Tvm {
+    /// Interest rate per payment period as a fraction.
+    fn periodic_rate(&self) -> f64 {
+        self.rate / 100.0 / self.periods_per_year
+    }
+
+    /// Value of all cash flows moved to the end; zero when they balance.
+    fn balance(&self, i: f64) -> f64 {
+        let (g, annuity) = growth(i, self.periods);
+        let timing = if self.begin { 1.0 + i } else { 1.0 };
+        self.present_value * g + self.payment * timing * annuity + self.future_value
+    }
+
+    /// Solves for `target` from the other four values.
+    pub fn solve(&self, target: TvmVariable) -> Result<f64, String> {
+        if self.periods_per_year.is_nan() || self.periods_per_year <= 0.0 {
+            return Err("Payments per year must be positive".to_string());
+        }
+        let i = self.periodic_rate();
+        if i.is_nan() || i <= -1.0 {
+            return Err("Interest rate must be above −100% per period".to_string());
+        }
+        let timing = if self.begin { 1.0 + i } else { 1.0 };
+        let (g, annuity) = growth(i, self.periods);
+        let result = match target {
+            TvmVariable::FutureValue => -(self.present_value * g + self.payment * timing * annuity),
+            TvmVariable::PresentValue => -(self.future_value + self.payment * timing * annuity) / g,
+            TvmVariable::Payment => {
+                if self.periods == 0.0 {
+                    return Err("N must not be zero".to_string());
+                }
+                -(self.present_value * g + self.future_value) / (timing * annuity)
+            }
+            TvmVariable::Periods => self.solve_periods(i, timing)?,
+            TvmVariable::Rate => {
+                let i = find_rate(|i| self.balance(i))
+                    .ok_or_else(|| "No interest rate balances these cash flows".to_string())?;
+                i * self.periods_per_year * 100.0
+            }
+        };
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err(format!("{} is undefined for these values", target.label()))
+        }
+    }
+
+    fn solve_periods(&self, i: f64, timing: f64) -> Result<f64, String> {
+        let none = || "No number of periods balances these cash flows".to_string();
+        if i == 0.0 {
+            if self.payment == 0.0 {
+                return Err(none());
+            }
+            return Ok(-(self.present_value + self.future_value) / self.payment);
+        }
+        let payment = self.payment * timing / i;
+        let ratio = (payment - self.future_value) / (payment + self.present_value);
+        if ratio.is_nan() || ratio <= 0.0 {
+            return Err(none());
+        }
+        Ok(ratio.ln() / i.ln_1p())
+    }
+
+    /// Period-by-period split of each payment into interest and principal,
+    /// for N rounded up to whole periods.
+    pub fn amortization(&self) -> Result<Vec<AmortizationRow>, String> {
+        let periods = self.periods.ceil();
+        if !(1.0..=MAX_PERIODS).contains(&periods) {
+            return Err(format!("Amortization needs N between 1 and {}", MAX_PERIODS));
+        }
+        let i = self.periodic_rate();
+        let mut balance = self.present_value;
+        let mut rows = Vec::with_capacity(periods as usize);
+        for period in 1..=periods as usize {
+            let accruing = if self.begin { balance + self.payment } else { balance };
+            let interest = -accruing * i;
+            balance = accruing * (1.0 + i) + if self.begin { 0.0 } else { self.payment };
+            rows.push(AmortizationRow {
+                period,
+                payment: self.payment,
+                interest,
+                principal: self.payment - interest,
+                balance,
+            });
+        }
+        Ok(rows)
+    }
+}
+
+/// A root of `f` over rates above −100%, by bisection on the first sign
+/// change found scanning outward from small positive rates.
+fn find_rate(f: impl Fn(f64) -> f64) -> Option<f64> {
+    const POSITIVE: [f64; 13] = [0.0, 1e-4, 0.001, 0.01, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0, 100.0];
+    const NEGATIVE: [f64; 5] = [0.0, -0.01, -0.1, -0.5, -0.99];
+    let pairs = POSITIVE.windows(2).chain(NEGATIVE.windows(2));
+    for pair in pairs {
+        let (a, b) = (pair[0], pair[1]);
+        let (fa, fb) = (f(a), f(b));
+        if fa == 0.0 {
+            return Some(a);
+        }
+        if fa.is_finite() && fb.is_finite() && fa.signum() != fb.signum() {
+            return Some(bisect(&f, a, b, fa));
+        }
+    }
+    None
+}
+
+fn bisect(f: &impl Fn(f64) -> f64, mut a: f64, mut b: f64, mut fa: f64) -> f64 {
+    for _ in 0..200 {
+        let mid = 0.5 * (a + b);
+        let fm = f(mid);
+        if fm == 0.0 || mid == a || mid == b {
+            return mid;
+        }
+        if fm.signum() == fa.signum() {
+            a = mid;
+            fa = fm;
+        } else {
+            b = mid;
+        }
+    }
+    0.5 * (a + b)
+}
+
+/// Net present value at `rate` percent per period, with the first cash
+/// flow at time zero.
+pub fn npv(rate: f64, flows: &[f64]) -> f64 {
+    let i = rate / 100.0;
+    flows
+        .iter()
+        .enumerate()
+        .map(|(t, flow)| flow / (1.0 + i).powi(t as i32))
+        .sum()
+}
+
+fn check_flows(flows: &[f64]) -> Result<(), String> {
+    let positive = flows.iter().any(|&flow| flow > 0.0);
+    let negative = flows.iter().any(|&flow| flow < 0.0);
+    if flows.len() < 2 || !positive || !negative {
+        Err("Cash flows need at least one inflow and one outflow".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Internal rate of return in percent per period.
+pub fn irr(flows: &[f64]) -> Result<f64, String> {
+    check_flows(flows)?;
+    find_rate(|i| npv(i * 100.0, flows))
+        .map(|i| i * 100.0)
+        .ok_or_else(|| "IRR not found".to_string())
+}
+
+/// Modified IRR in percent per period: outflows discounted at the finance
+/// rate, inflows compounded at the reinvestment rate.
+pub fn mirr(flows: &[f64], finance_rate: f64, reinvest_rate: f64) -> Result<f64, String> {
+    check_flows(flows)?;
+    let n = (flows.len() - 1) as i32;
+    let (finance, reinvest) = (1.0 + finance_rate / 100.0, 1.0 + reinvest_rate / 100.0);
+    let mut outflows = 0.0;
+    let mut inflows = 0.0;
+    for (t, &flow) in flows.iter().enumerate() {
+        if flow < 0.0 {
+            outflows += flow / finance.powi(t as i32);
+        } else {
+            inflows += flow * reinvest.powi(n - t as i32);
+        }
+    }
+    Ok(((inflows / -outflows).powf(1.0 / n as f64) - 1.0) * 100.0)
+}
+
+/// What to compute from the cash-flow list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CashFlowMeasure {
+    Npv,
+    Irr,
+    Mirr,
+}
+
+impl CashFlowMeasure {
+    pub const ALL: [CashFlowMeasure; 3] = [CashFlowMeasure::Npv, CashFlowMeasure::Irr, CashFlowMeasure::Mirr];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CashFlowMeasure::Npv => "NPV",
+            CashFlowMeasure::Irr => "IRR",
+            CashFlowMeasure::Mirr => "MIRR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum DepreciationMethod {
+    #[default]
+    StraightLine,
+    /// Declining balance at `factor` times the straight-line rate
+    /// (2 for double-declining), never going below salvage.
+    DecliningBalance,
+    SumOfYearsDigits,
+}
+
+impl DepreciationMethod {
+    pub const ALL: [DepreciationMethod; 3] = [
+        DepreciationMethod::StraightLine,
+        DepreciationMethod::DecliningBalance,
+        DepreciationMethod::SumOfYearsDigits,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DepreciationMethod::StraightLine => "Straight line",
+            DepreciationMethod::DecliningBalance => "Declining balance",
+            DepreciationMethod::SumOfYearsDigits => "Sum of years' digits",
+        }
+    }
+
+    /// Each year's expense and the book value after it.
+    pub fn schedule(self, cost: f64, salvage: f64, life: u32, factor: f64) -> Result<Vec<(f64, f64)>, String> {
+        if life == 0 || life > MAX_LIFE {
+            return Err(format!("Life must be 1 to {} years", MAX_LIFE));
+        }
+        if salvage > cost {
+            return Err("Salvage value exceeds the cost".to_string());
+        }
+        let years = life as f64;
+        let mut book = cost;
+        let mut rows = Vec::with_capacity(life as usize);
+        for year in 1..=life {
+            let expense = match self {
+                DepreciationMethod::StraightLine => (cost - salvage) / years,
+                DepreciationMethod::DecliningBalance => (book * factor / years).min(book - salvage).max(0.0),
+                DepreciationMethod::SumOfYearsDigits => {
+                    (cost - salvage) * (life - year + 1) as f64 / (years * (years + 1.0) / 2.0)
+                }
+            };
+            book -= expense;
+            rows.push((expense, book));
+        }
+        Ok(rows)
+    }
+}
+
+/// Future value of `principal` at a nominal annual `rate` percent over
+/// `years`, compounded `per_year` times a year (0 for continuously), and
+/// the effective annual rate in percent.
+pub fn compound(principal: f64, rate: f64, per_year: u32, years: f64) -> (f64, f64) {
+    let r = rate / 100.0;
+    let growth_per_year = if per_year == 0 {
+        r.exp()
+    } else {
+        (1.0 + r / per_year as f64).powi(per_year as i32)
+    };
+    (principal * growth_per_year.powf(years), (growth_per_year - 1.0) * 100.0)
+}
+
+/// A table for the financial panel: amortization or depreciation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinanceTable {
+    pub title: String,
+    pub headers: &'static [&'static str],
+    pub rows: Vec<Vec<f64>>,
+}
+
+impl FinanceTable {
+    pub fn amortization(rows: &[AmortizationRow]) -> Self {
+        Self {
+            title: "Amortization".to_string(),
+            headers: &["Period", "Payment", "Interest", "Principal", "Balance"],
+            rows: rows
+                .iter()
+                .map(|row| vec![row.period as f64, row.payment, row.interest, row.principal, row.balance])
+                .collect(),
+        }
+    }
+
+    /// The table as CSV, using the locale's decimal separator (and `;`
+    /// between fields for a decimal comma).
+    pub fn to_csv(&self, locale: &NumberLocale) -> String {
+        let separator = if locale.decimal_separator == ',' { ";" } else { "," };
+        let number = |value: f64| full_precision(value).replace('.', &locale.decimal_separator.to_string());
+        let mut csv = self.headers.join(separator) + "\n";
+        for row in &self.rows {
+            let fields: Vec<String> = row.iter().map(|&value| number(value)).collect();
+            csv.push_str(&fields.join(separator));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    pub fn depreciation(method: DepreciationMethod, rows: &[(f64, f64)]) -> Self {
+        Self {
+            title: format!("Depreciation ({})", method.label()),
+            headers: &["Year", "Expense", "Book value"],
+            rows: rows
+                .iter()
+                .enumerate()
+                .map(|(year, &(expense, book))| vec![(year + 1) as f64, expense, book])
+                .collect(),
+        }
+    }
+}
+
+/// Inputs of the financial mode, kept with the app state.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FinanceSettings {
+    pub tvm: Tvm,
+    /// Cash flows from time zero, separated like expression arguments.
+    pub cash_flows: String,
+    /// Discount rate for NPV, percent per period.
+    pub discount_rate: f64,
+    pub finance_rate: f64,
+    pub reinvest_rate: f64,
+    pub depreciation: DepreciationMethod,
+    pub cost: f64,
+    pub salvage: f64,
+    pub life: u32,
+    pub declining_factor: f64,
+    pub principal: f64,
+    pub annual_rate: f64,
+    /// Compounding periods per year; 0 compounds continuously.
+    pub compounding: u32,
+    pub years: f64,
+}
+
+impl Default for FinanceSettings {
+    fn default() -> Self {
+        Self {
+            tvm: Tvm::default(),
+            cash_flows: "-1000, 300, 400, 500".to_string(),
+            discount_rate: 10.0,
+            finance_rate: 10.0,
+            reinvest_rate: 10.0,
+            depreciation: DepreciationMethod::default(),
+            cost: 10_000.0,
+            salvage: 1000.0,
+            life: 5,
+            declining_factor: 2.0,
+            principal: 1000.0,
+            annual_rate: 5.0,
+            compounding: 12,
+            years: 10.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    fn mortgage() -> Tvm {
+        Tvm {
+            payment: -1_199.101_050_305_514,
+            ..Tvm::default()
+        }
+    }
+
+    #[test]
+    fn tvm_solves_a_mortgage_for_each_variable() {
+        let loan = Tvm::default();
+        assert_close(loan.solve(TvmVariable::Payment).unwrap(), -1_199.101_050_305_514, 1e-8);
+
+        let loan = mortgage();
+        assert_close(loan.solve(TvmVariable::Periods).unwrap(), 360.0, 1e-8);
+        assert_close(loan.solve(TvmVariable::Rate).unwrap(), 6.0, 1e-8);
+        assert_close(loan.solve(TvmVariable::PresentValue).unwrap(), 200_000.0, 1e-6);
+        assert_close(loan.solve(TvmVariable::FutureValue).unwrap(), 0.0, 1e-6);
+    }
+
+    #[test]
+    fn tvm_future_value_of_a_savings_plan() {
+        let savings = Tvm {
+            periods: 120.0,
+            present_value: 0.0,
+            payment: -100.0,
+            ..Tvm::default()
+        };
+        assert_close(savings.solve(TvmVariable::FutureValue).unwrap(), 16_387.934_680_645_8, 1e-6);
+
+        let due = Tvm { begin: true, ..savings };
+        assert_close(due.solve(TvmVariable::FutureValue).unwrap(), 16_387.934_680_645_8 * 1.005, 1e-6);
+    }
+
+    #[test]
+    fn tvm_at_zero_interest_is_simple_arithmetic() {
+        let loan = Tvm {
+            periods: 12.0,
+            rate: 0.0,
+            present_value: 1200.0,
+            ..Tvm::default()
+        };
+        assert_eq!(loan.solve(TvmVariable::Payment), Ok(-100.0));
+
+        let loan = Tvm { payment: -100.0, ..loan };
+        assert_eq!(loan.solve(TvmVariable::Periods), Ok(12.0));
+        assert_eq!(loan.solve(TvmVariable::Rate), Ok(0.0));
+        assert_eq!(loan.solve(TvmVariable::FutureValue), Ok(0.0));
+
+        let stalled = Tvm { payment: 0.0, ..loan };
+        assert_eq!(
+            stalled.solve(TvmVariable::Periods),
+            Err("No number of periods balances these cash flows".to_string())
+        );
+    }
+
+    #[test]
+    fn tvm_rejects_impossible_inputs() {
+        let loan = Tvm { periods: 0.0, ..Tvm::default() };
+        assert_eq!(loan.solve(TvmVariable::Payment), Err("N must not be zero".to_string()));
+
+        let gift = Tvm {
+            present_value: 1000.0,
+            payment: 100.0,
+            ..Tvm::default()
+        };
+        assert_eq!(
+            gift.solve(TvmVariable::Rate),
+            Err("No interest rate balances these cash flows".to_string())
+        );
+    }
+
+    #[test]
+    fn amortization_pays_off_the_loan() {
+        let rows = mortgage().amortization().unwrap();
+        assert_eq!(rows.len(), 360);
+        assert_close(rows[0].interest, -1000.0, 1e-9);
+        assert_close(rows[0].principal, -199.101_050_305_514, 1e-9);
+        assert_close(rows[359].balance, 0.0, 1e-6);
+    }
+
+    #[test]
+    fn npv_discounts_each_flow_from_time_zero() {
+        let flows = [-1000.0, 500.0, 500.0, 500.0];
+        assert_close(npv(10.0, &flows), 243.425_995_492_111, 1e-9);
+        assert_eq!(npv(0.0, &flows), 500.0);
+    }
+
+    #[test]
+    fn irr_matches_known_answers() {
+        assert_close(irr(&[-100.0, 110.0]).unwrap(), 10.0, 1e-9);
+        // 60/(1 + r) + 60/(1 + r)² = 100
+        assert_close(irr(&[-100.0, 60.0, 60.0]).unwrap(), 13.066_238_629_180_75, 1e-9);
+        // A loss has a negative IRR
+        assert_close(irr(&[-100.0, 90.0]).unwrap(), -10.0, 1e-9);
+
+        let flows = [-1000.0, 300.0, 400.0, 500.0];
+        assert_close(npv(irr(&flows).unwrap(), &flows), 0.0, 1e-9);
+    }
+
+    #[test]
+    fn irr_needs_a_sign_change() {
+        let error = Err("Cash flows need at least one inflow and one outflow".to_string());
+        assert_eq!(irr(&[100.0, 200.0]), error);
+        assert_eq!(irr(&[-100.0, -50.0]), error);
+        assert_eq!(irr(&[-100.0]), error);
+        assert_eq!(irr(&[]), error);
+        // Every rate leaves these flows underwater
+        assert_eq!(irr(&[-100.0, 50.0, -100.0]), Err("IRR not found".to_string()));
+    }
+
+    #[test]
+    fn mirr_matches_a_known_answer() {
+        let flows = [-120_000.0, 39_000.0, 30_000.0, 21_000.0, 37_000.0, 46_000.0];
+        assert_close(mirr(&flows, 10.0, 12.0).unwrap(), 12.609_413_036_590_5, 1e-9);
+    }
+
+    #[test]
+    fn compound_interest_and_effective_rate() {
+        let (future, effective) = compound(1000.0, 5.0, 12, 10.0);
+        assert_close(future, 1_647.009_497_690_28, 1e-6);
+        assert_close(effective, 5.116_189_788_173_298, 1e-9);
+
+        let (future, effective) = compound(1000.0, 5.0, 0, 10.0);
+        assert_close(future, 1000.0 * 0.5f64.exp(), 1e-9);
+        assert_close(effective, (0.05f64.exp() - 1.0) * 100.0, 1e-12);
+    }
+
+    #[test]
+    fn depreciation_schedules_end_at_salvage() {
+        for method in DepreciationMethod::ALL {
+            let rows = method.schedule(10_000.0, 1000.0, 5, 2.0).unwrap();
+            assert_eq!(rows.len(), 5);
+            assert!(rows.iter().all(|&(_, book)| book >= 1000.0 - 1e-9), "{:?}", method);
+        }
+        let straight = DepreciationMethod::StraightLine.schedule(10_000.0, 1000.0, 5, 2.0).unwrap();
+        assert_eq!(straight[0], (1800.0, 8200.0));
+        assert_close(straight[4].1, 1000.0, 1e-9);
+    }
+}
@@ -0,0 +1,477 @@
+use crate::calculator::{full_precision, AngleUnit};
+use crate::expression::{self, Context, Expr};
+use crate::locale::NumberLocale;
+use crate::statistics::parse_points;
+use std::f64::consts::PI;
+
+/// Colors assigned to new curves, in order.
+pub const CURVE_COLORS: [[u8; 3]; 6] = [
+    [70, 130, 180],
+    [220, 20, 60],
+    [46, 139, 87],
+    [255, 140, 0],
+    [148, 0, 211],
+    [0, 139, 139],
+];
+
+/// Points sampled across the visible x range for drawing and analysis.
+pub const SAMPLES: usize = 400;
+
+/// How a curve is defined.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum CurveKind {
+    /// `y = f(x)`
+    #[default]
+    Function,
+    /// `(x(t), y(t))` over a range of t
+    Parametric,
+    /// `r(θ)` over a range of θ in the configured angle unit
+    Polar,
+    /// (x, y) sample rows typed or pasted in
+    Table,
+}
+
+impl CurveKind {
+    pub const ALL: [CurveKind; 4] = [
+        CurveKind::Function,
+        CurveKind::Parametric,
+        CurveKind::Polar,
+        CurveKind::Table,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CurveKind::Function => "y = f(x)",
+            CurveKind::Parametric => "Parametric",
+            CurveKind::Polar => "Polar",
+            CurveKind::Table => "Table",
+        }
+    }
+}
+
+/// A curve on the graph. Fields that do not apply to its kind are kept,
+/// so switching kinds back and forth loses nothing.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Curve {
+    pub kind: CurveKind,
+    /// f(x), x(t) or r(θ) as typed; a leading `y =` style prefix is accepted.
+    pub expression: String,
+    /// y(t) of a parametric curve.
+    pub y_expression: String,
+    /// Range of t or θ.
+    pub parameter: [f64; 2],
+    /// Rows of a sample table, one `x, y` pair per line.
+    pub table: String,
+    pub color: [u8; 3],
+    pub visible: bool,
+}
+
+impl Curve {
+    pub fn new(kind: CurveKind, color: [u8; 3], angle: AngleUnit) -> Self {
+        let parameter = match kind {
+            CurveKind::Polar => [0.0, angle.radians_to_unit(2.0 * PI)],
+            _ => [0.0, 2.0 * PI],
+        };
+        Self {
+            kind,
+            expression: String::new(),
+            y_expression: String::new(),
+            parameter,
+            table: String::new(),
+            color,
+            visible: true,
+        }
+    }
+
+    /// Parses f(x) of a `y = f(x)` curve.
+    pub fn parse(&self, locale: &NumberLocale) -> Result<Expr, String> {
+        parse_rhs(&self.expression, locale)
+    }
+
+    /// The curve as drawable segments, sampled over `x_range` for
+    /// functions and over the parameter range otherwise.
+    pub fn segments(&self, locale: &NumberLocale, ctx: &Context, x_range: [f64; 2]) -> Result<Vec<Vec<[f64; 2]>>, String> {
+        match self.kind {
+            CurveKind::Function => {
+                let expr = self.parse(locale)?;
+                Ok(Function::new(&expr, ctx).segments(x_range[0], x_range[1]))
+            }
+            CurveKind::Parametric => {
+                let x = parse_rhs(&self.expression, locale)?;
+                let y = parse_rhs(&self.y_expression, locale)?;
+                let (mut x, mut y) = (Parameterized::new(&x, ctx, &["t"]), Parameterized::new(&y, ctx, &["t"]));
+                Ok(self.trace(|t| [x.eval(t), y.eval(t)]))
+            }
+            CurveKind::Polar => {
+                let r = parse_rhs(&self.expression, locale)?;
+                let mut r = Parameterized::new(&r, ctx, &["θ", "theta"]);
+                let angle = ctx.options.angle;
+                Ok(self.trace(|theta| {
+                    let radius = r.eval(theta);
+                    let radians = angle.to_radians(theta);
+                    [radius * radians.cos(), radius * radians.sin()]
+                }))
+            }
+            CurveKind::Table => {
+                let points: Vec<[f64; 2]> = parse_points(&self.table, locale)
+                    .into_iter()
+                    .filter_map(|point| Some([point.x, point.y?]))
+                    .collect();
+                if points.is_empty() {
+                    Err("Enter one x, y pair per line".to_string())
+                } else {
+                    Ok(vec![points])
+                }
+            }
+        }
+    }
+
+    /// Samples a parametric curve over the parameter range, breaking it
+    /// where it is undefined.
+    fn trace(&self, mut point: impl FnMut(f64) -> [f64; 2]) -> Vec<Vec<[f64; 2]>> {
+        let [start, end] = self.parameter;
+        let mut segments = Vec::new();
+        let mut current = Vec::new();
+        for i in 0..=SAMPLES {
+            let [x, y] = point(start + (end - start) * i as f64 / SAMPLES as f64);
+            if x.is_finite() && y.is_finite() {
+                current.push([x, y]);
+            } else if !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+        segments
+    }
+
+    /// The sampled points as CSV with an `x,y` header, using the locale's
+    /// decimal separator (and `;` between fields for a decimal comma).
+    pub fn export_csv(&self, locale: &NumberLocale, ctx: &Context, x_range: [f64; 2]) -> Result<String, String> {
+        let separator = if locale.decimal_separator == ',' { ';' } else { ',' };
+        let number = |value: f64| full_precision(value).replace('.', &locale.decimal_separator.to_string());
+        let mut csv = format!("x{}y\n", separator);
+        for point in self.segments(locale, ctx, x_range)?.concat() {
+            csv.push_str(&format!("{}{}{}\n", number(point[0]), separator, number(point[1])));
+        }
+        Ok(csv)
+    }
+}
+
+/// Parses the right-hand side of `y = …`, `r(θ) = …` or a bare expression.
+fn parse_rhs(text: &str, locale: &NumberLocale) -> Result<Expr, String> {
+    let text = match text.split_once('=') {
+        Some((_, rhs)) => rhs,
+        None => text,
+    };
+    expression::parse(text, locale)
+}
+
+/// An expression evaluated for values of one variable, which may be
+/// spelled several ways (`θ` or `theta`).
+struct Parameterized<'a> {
+    expr: &'a Expr,
+    ctx: Context,
+    names: &'static [&'static str],
+}
+
+impl<'a> Parameterized<'a> {
+    fn new(expr: &'a Expr, ctx: &Context, names: &'static [&'static str]) -> Self {
+        Self { expr, ctx: ctx.clone(), names }
+    }
+
+    fn eval(&mut self, value: f64) -> f64 {
+        for name in self.names {
+            self.ctx.set_variable(name, value);
+        }
+        self.expr.eval(&self.ctx).unwrap_or(f64::NAN)
+    }
+}
+
+/// Axis settings; the ranges are applied when the view is reset.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Axes {
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+    pub grid: bool,
+    /// Same scale on both axes, so circles look round.
+    pub equal_aspect: bool,
+}
+
+impl Default for Axes {
+    fn default() -> Self {
+        Self {
+            x_min: -10.0,
+            x_max: 10.0,
+            y_min: -10.0,
+            y_max: 10.0,
+            grid: true,
+            equal_aspect: false,
+        }
+    }
+}
+
+/// Which analysis markers are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct Markers {
+    pub roots: bool,
+    pub extrema: bool,
+    pub intersections: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Graph {
+    pub curves: Vec<Curve>,
+    pub axes: Axes,
+    pub markers: Markers,
+    /// Cleared to make the plot jump back to the configured axis ranges.
+    #[serde(skip)]
+    pub view_applied: bool,
+    /// Result of the last export, shown under the curve list.
+    #[serde(skip)]
+    pub status: Option<String>,
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self {
+            curves: vec![Curve {
+                expression: "y = x^2 - 4".to_string(),
+                ..Curve::new(CurveKind::Function, CURVE_COLORS[0], AngleUnit::default())
+            }],
+            axes: Axes::default(),
+            markers: Markers::default(),
+            view_applied: false,
+            status: None,
+        }
+    }
+}
+
+impl Graph {
+    pub fn add_curve(&mut self, kind: CurveKind, angle: AngleUnit) {
+        let color = CURVE_COLORS[self.curves.len() % CURVE_COLORS.len()];
+        self.curves.push(Curve::new(kind, color, angle));
+    }
+
+    pub fn reset_view(&mut self) {
+        self.view_applied = false;
+    }
+}
+
+/// Kinds of points marked on the graph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeatureKind {
+    Root,
+    Minimum,
+    Maximum,
+    Intersection,
+}
+
+impl FeatureKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            FeatureKind::Root => "Root",
+            FeatureKind::Minimum => "Minimum",
+            FeatureKind::Maximum => "Maximum",
+            FeatureKind::Intersection => "Intersection",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Feature {
+    pub kind: FeatureKind,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A parsed `y = f(x)` curve bound to an evaluation context.
+pub struct Function<'a>(Parameterized<'a>);
+
+impl<'a> Function<'a> {
+    pub fn new(expr: &'a Expr, ctx: &Context) -> Self {
+        Self(Parameterized::new(expr, ctx, &["x"]))
+    }
+
+    /// f(x), or NaN where the expression is undefined.
+    pub fn eval(&mut self, x: f64) -> f64 {
+        self.0.eval(x)
+    }
+
+    /// Samples the function over [x_min, x_max] and splits the result into
+    /// continuous segments at undefined points, poles and jumps.
+    pub fn segments(&mut self, x_min: f64, x_max: f64) -> Vec<Vec<[f64; 2]>> {
+        let samples = self.sample(x_min, x_max);
+        let scale = typical_magnitude(&samples);
+        let mut segments = Vec::new();
+        let mut current: Vec<[f64; 2]> = Vec::new();
+        for [x, y] in samples {
+            let broken = match current.last() {
+                Some(&[a, fa]) => y.is_finite() && self.sign_change(a, fa, x, y, scale) == Some(None),
+                None => false,
+            };
+            if !y.is_finite() || broken {
+                if current.len() > 1 {
+                    segments.push(std::mem::take(&mut current));
+                }
+                current.clear();
+            }
+            if y.is_finite() {
+                current.push([x, y]);
+            }
+        }
+        if current.len() > 1 {
+            segments.push(current);
+        }
+        segments
+    }
+
+    fn sample(&mut self, x_min: f64, x_max: f64) -> Vec<[f64; 2]> {
+        (0..=SAMPLES)
+            .map(|i| {
+                let x = x_min + (x_max - x_min) * i as f64 / SAMPLES as f64;
+                [x, self.eval(x)]
+            })
+            .collect()
+    }
+
+    /// Classifies the interval between two finite samples: `None` without
+    /// a sign change, `Some(Some(root))` for a continuous crossing and
+    /// `Some(None)` for a pole or jump, where bisection closes in on a
+    /// point whose value is not near zero.
+    fn sign_change(&mut self, a: f64, fa: f64, b: f64, fb: f64, scale: f64) -> Option<Option<f64>> {
+        if fa.signum() == fb.signum() || fa == 0.0 || fb == 0.0 {
+            return None;
+        }
+        let x = bisect(|x| self.eval(x), a, b);
+        Some((self.eval(x).abs() <= 1e-6 * scale).then_some(x))
+    }
+
+    /// Zeros in [x_min, x_max], found from sign changes between samples
+    /// and refined by bisection. Sign changes across poles are skipped.
+    pub fn roots(&mut self, x_min: f64, x_max: f64) -> Vec<Feature> {
+        let samples = self.sample(x_min, x_max);
+        let scale = typical_magnitude(&samples);
+        let mut roots = Vec::new();
+        for pair in samples.windows(2) {
+            let ([a, fa], [b, fb]) = (pair[0], pair[1]);
+            if fa == 0.0 {
+                roots.push(Feature { kind: FeatureKind::Root, x: a, y: 0.0 });
+            } else if fa.is_finite() && fb.is_finite() {
+                if let Some(Some(x)) = self.sign_change(a, fa, b, fb, scale) {
+                    roots.push(Feature { kind: FeatureKind::Root, x, y: 0.0 });
+                }
+            }
+        }
+        roots
+    }
+
+    /// Local minima and maxima in the open interval, from slope sign
+    /// changes between samples refined by golden-section search. A
+    /// refined value far beyond the samples means a pole, not an extremum.
+    pub fn extrema(&mut self, x_min: f64, x_max: f64) -> Vec<Feature> {
+        let samples = self.sample(x_min, x_max);
+        let mut extrema = Vec::new();
+        for triple in samples.windows(3) {
+            let ([a, fa], [_, fm], [b, fb]) = (triple[0], triple[1], triple[2]);
+            if !(fa.is_finite() && fm.is_finite() && fb.is_finite()) {
+                continue;
+            }
+            let kind = if fm > fa && fm >= fb {
+                FeatureKind::Maximum
+            } else if fm < fa && fm <= fb {
+                FeatureKind::Minimum
+            } else {
+                continue;
+            };
+            let sign = if kind == FeatureKind::Maximum { -1.0 } else { 1.0 };
+            let x = golden_section(|x| sign * self.eval(x), a, b);
+            let y = self.eval(x);
+            if y.is_finite() && (y - fm).abs() <= (fm - fa).abs() + (fm - fb).abs() {
+                extrema.push(Feature { kind, x, y });
+            }
+        }
+        extrema
+    }
+}
+
+/// Points in [x_min, x_max] where two curves meet.
+pub fn intersections(f: &mut Function, g: &mut Function, x_min: f64, x_max: f64) -> Vec<Feature> {
+    let samples: Vec<[f64; 2]> = (0..=SAMPLES)
+        .map(|i| {
+            let x = x_min + (x_max - x_min) * i as f64 / SAMPLES as f64;
+            [x, f.eval(x) - g.eval(x)]
+        })
+        .collect();
+    let scale = typical_magnitude(&samples);
+    let mut points = Vec::new();
+    for pair in samples.windows(2) {
+        let ([a, da], [b, db]) = (pair[0], pair[1]);
+        if !(da.is_finite() && db.is_finite()) || da.signum() == db.signum() || db == 0.0 {
+            continue;
+        }
+        let x = bisect(|x| f.eval(x) - g.eval(x), a, b);
+        let y = f.eval(x);
+        if (y - g.eval(x)).abs() <= 1e-6 * scale {
+            points.push(Feature { kind: FeatureKind::Intersection, x, y });
+        }
+    }
+    points
+}
+
+/// Magnitude used to tell real zeros from sign changes across poles.
+fn typical_magnitude(samples: &[[f64; 2]]) -> f64 {
+    let finite: Vec<f64> = samples.iter().map(|p| p[1].abs()).filter(|y| y.is_finite()).collect();
+    (finite.iter().sum::<f64>() / finite.len().max(1) as f64).max(1.0)
+}
+
+/// Bisection on [a, b] where f changes sign, down to adjacent floats.
+fn bisect(mut f: impl FnMut(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let mut fa = f(a);
+    loop {
+        let mid = a + (b - a) / 2.0;
+        if mid <= a || mid >= b {
+            return mid;
+        }
+        let fm = f(mid);
+        if fm == 0.0 {
+            return mid;
+        }
+        if fm.signum() == fa.signum() {
+            a = mid;
+            fa = fm;
+        } else {
+            b = mid;
+        }
+    }
+}
+
+/// Minimum of a unimodal function on [a, b].
+fn golden_section(mut f: impl FnMut(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let (mut fc, mut fd) = (f(c), f(d));
+    for _ in 0..100 {
+        if (b - a).abs() <= 1e-12 * (a.abs() + b.abs()).max(1e-300) {
+            break;
+        }
+        if fc < fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = b - ratio * (b - a);
+            fc = f(c);
+        } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = a + ratio * (b - a);
+            fd = f(d);
+        }
+    }
+    (a + b) / 2.0
+}
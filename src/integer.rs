@@ -0,0 +1,616 @@
+use crate::calculator::Operation;
+use crate::expression::{Context, Expr};
+use crate::value::Value;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// Integers up to 2^53 convert exactly from `f64`.
+const EXACT_LIMIT: f64 = 9_007_199_254_740_992.0;
+/// Exact `^` results are limited to this many bits (about 30 000 digits).
+const MAX_BITS: u64 = 100_000;
+const MAX_FACTORIAL: u32 = 5_000;
+/// Pollard's rho gives up after about this many steps (a second or so).
+const RHO_STEPS: u64 = 1 << 18;
+/// Miller–Rabin with the primes up to 41 as bases is exact below this.
+const DETERMINISTIC_LIMIT: u128 = 3_317_044_064_679_887_385_961_981;
+const BASES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+/// Extra bases for larger numbers; each lets a composite pass with
+/// probability at most 1/4.
+const EXTRA_BASES: [u32; 12] = [43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97];
+
+/// Inputs of the number theory panel, kept with the app state. Each
+/// field is an integer expression; an empty `a` uses the displayed number.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct IntegerSettings {
+    pub a: String,
+    pub b: String,
+    pub modulus: String,
+    /// Sign rule of the panel's mod and div.
+    pub convention: ModConvention,
+}
+
+/// Sign rule of integer division and its remainder.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ModConvention {
+    /// Quotient rounded down; the remainder has the divisor's sign.
+    #[default]
+    Floored,
+    /// Quotient rounded toward zero; the remainder has the dividend's sign.
+    Truncated,
+    /// The remainder is never negative.
+    Euclidean,
+}
+
+impl ModConvention {
+    pub const ALL: [ModConvention; 3] = [ModConvention::Floored, ModConvention::Truncated, ModConvention::Euclidean];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ModConvention::Floored => "Floored (sign of divisor)",
+            ModConvention::Truncated => "Truncated (sign of dividend)",
+            ModConvention::Euclidean => "Euclidean (non-negative)",
+        }
+    }
+}
+
+/// Quotient and remainder of `a / b` under `convention`.
+pub fn div_rem(a: &BigInt, b: &BigInt, convention: ModConvention) -> Result<(BigInt, BigInt), String> {
+    if b.is_zero() {
+        return Err("Cannot divide by zero".to_string());
+    }
+    Ok(match convention {
+        ModConvention::Floored => a.div_mod_floor(b),
+        ModConvention::Truncated => a.div_rem(b),
+        ModConvention::Euclidean => {
+            let (q, r) = a.div_rem(b);
+            if !r.is_negative() {
+                (q, r)
+            } else if b.is_positive() {
+                (q - 1, r + b)
+            } else {
+                (q + 1, r - b)
+            }
+        }
+    })
+}
+
+fn small_primes() -> impl Iterator<Item = u32> {
+    BASES.into_iter().chain(EXTRA_BASES)
+}
+
+/// Miller–Rabin primality test: exact below about 3.3·10^24 and with an
+/// error chance below 4^-25 above that.
+pub fn is_prime(n: &BigInt) -> bool {
+    if *n < BigInt::from(2) {
+        return false;
+    }
+    for p in small_primes() {
+        if *n == BigInt::from(p) {
+            return true;
+        }
+        if (n % p).is_zero() {
+            return false;
+        }
+    }
+    let n_minus_one = n - 1u32;
+    let shift = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> shift;
+    let bases: Vec<u32> = if is_certain(n) { BASES.to_vec() } else { small_primes().collect() };
+    bases.into_iter().all(|base| {
+        let mut x = BigInt::from(base).modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            return true;
+        }
+        for _ in 1..shift {
+            x = &x * &x % n;
+            if x == n_minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Whether `is_prime` is exact for `n` rather than probabilistic.
+pub fn is_certain(n: &BigInt) -> bool {
+    *n < BigInt::from(DETERMINISTIC_LIMIT)
+}
+
+/// The smallest prime above `n`.
+pub fn next_prime(n: &BigInt) -> BigInt {
+    if *n < BigInt::from(2) {
+        return BigInt::from(2);
+    }
+    let mut candidate = n + 1u32;
+    while !is_prime(&candidate) {
+        candidate += 1u32;
+    }
+    candidate
+}
+
+/// The largest prime below `n`.
+pub fn previous_prime(n: &BigInt) -> Result<BigInt, String> {
+    if *n <= BigInt::from(2) {
+        return Err("There is no prime below 2".to_string());
+    }
+    let mut candidate = n - 1u32;
+    while !is_prime(&candidate) {
+        candidate -= 1u32;
+    }
+    Ok(candidate)
+}
+
+/// Prime factors of |n| with their exponents, smallest first. Trial
+/// division removes small factors and Pollard's rho splits the rest.
+pub fn factorize(n: &BigInt) -> Result<Vec<(BigInt, u32)>, String> {
+    if n.is_zero() {
+        return Err("0 has no prime factorization".to_string());
+    }
+    let mut rest = n.abs();
+    let mut primes = Vec::new();
+    let mut p = 2u32;
+    while p < 10_000 && BigInt::from(p) * p <= rest {
+        while (&rest % p).is_zero() {
+            rest /= p;
+            primes.push(BigInt::from(p));
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if !rest.is_one() {
+        split(rest, &mut primes)?;
+    }
+    primes.sort();
+    let mut factors: Vec<(BigInt, u32)> = Vec::new();
+    for prime in primes {
+        match factors.last_mut() {
+            Some((last, count)) if *last == prime => *count += 1,
+            _ => factors.push((prime, 1)),
+        }
+    }
+    Ok(factors)
+}
+
+fn split(n: BigInt, primes: &mut Vec<BigInt>) -> Result<(), String> {
+    if is_prime(&n) {
+        primes.push(n);
+        return Ok(());
+    }
+    let mut factor = None;
+    for c in 1..=20u32 {
+        match brent(&n, &BigInt::from(c)) {
+            Some(g) if g != n => {
+                factor = Some(g);
+                break;
+            }
+            // The sequence cycled without a factor; try another constant
+            Some(_) => {}
+            None => break,
+        }
+    }
+    let factor = factor.ok_or_else(|| format!("No factor of {} found within the search limit", n))?;
+    let cofactor = &n / &factor;
+    split(factor, primes)?;
+    split(cofactor, primes)
+}
+
+/// Brent's variant of Pollard's rho with x² + c on the composite `n`: a
+/// proper factor, `n` itself when the sequence cycles first, or `None`
+/// when the step limit is reached.
+fn brent(n: &BigInt, c: &BigInt) -> Option<BigInt> {
+    const BATCH: u64 = 128;
+    let f = |x: &BigInt| (x * x + c) % n;
+    let mut y = BigInt::from(2);
+    let mut x = y.clone();
+    let mut saved = y.clone();
+    let mut product = BigInt::one();
+    let mut g = BigInt::one();
+    let mut r = 1u64;
+    while g.is_one() {
+        x = y.clone();
+        for _ in 0..r {
+            y = f(&y);
+        }
+        let mut k = 0;
+        while k < r && g.is_one() {
+            saved = y.clone();
+            for _ in 0..BATCH.min(r - k) {
+                y = f(&y);
+                product = product * (&x - &y).abs() % n;
+            }
+            g = product.gcd(n);
+            k += BATCH;
+        }
+        r *= 2;
+        if g.is_one() && r > RHO_STEPS {
+            return None;
+        }
+    }
+    if g == *n {
+        // The batch overshot; step through it one value at a time
+        loop {
+            saved = f(&saved);
+            g = (&x - &saved).abs().gcd(n);
+            if !g.is_one() {
+                break;
+            }
+        }
+    }
+    Some(g)
+}
+
+/// `2^3 × 3 × 5` for 120, with `−1 ×` for negative numbers.
+pub fn format_factors(n: &BigInt, factors: &[(BigInt, u32)]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if n.is_negative() {
+        parts.push("−1".to_string());
+    }
+    for (prime, exponent) in factors {
+        parts.push(if *exponent == 1 { prime.to_string() } else { format!("{}^{}", prime, exponent) });
+    }
+    if parts.is_empty() {
+        parts.push("1".to_string());
+    }
+    parts.join(" × ")
+}
+
+/// Euler's totient: how many of 1..=n are coprime to n.
+pub fn totient(n: &BigInt) -> Result<BigInt, String> {
+    if !n.is_positive() {
+        return Err("Totient needs a positive integer".to_string());
+    }
+    let mut result = BigInt::one();
+    for (prime, exponent) in factorize(n)? {
+        result *= prime.pow(exponent - 1) * (prime - 1u32);
+    }
+    Ok(result)
+}
+
+/// `a^e mod m` in `0..m`; a negative exponent uses the inverse of `a`.
+pub fn mod_pow(a: &BigInt, e: &BigInt, m: &BigInt) -> Result<BigInt, String> {
+    if !m.is_positive() {
+        return Err("Modulus must be positive".to_string());
+    }
+    let base = if e.is_negative() { mod_inverse(a, m)? } else { a.mod_floor(m) };
+    Ok(base.modpow(&e.abs(), m))
+}
+
+/// The x in `0..m` with `a·x ≡ 1 (mod m)`.
+pub fn mod_inverse(a: &BigInt, m: &BigInt) -> Result<BigInt, String> {
+    if !m.is_positive() {
+        return Err("Modulus must be positive".to_string());
+    }
+    let extended = a.mod_floor(m).extended_gcd(m);
+    if !extended.gcd.is_one() {
+        return Err(format!("{} has no inverse modulo {} (gcd {})", a, m, extended.gcd));
+    }
+    Ok(extended.x.mod_floor(m))
+}
+
+fn factorial(n: &BigInt) -> Result<BigInt, String> {
+    match n.to_u32() {
+        Some(n) if n <= MAX_FACTORIAL => Ok((2..=n).fold(BigInt::one(), |acc, k| acc * k)),
+        _ if n.is_negative() => Err("Factorial is only defined for non-negative integers".to_string()),
+        _ => Err(format!("Exact factorials go up to {}!", MAX_FACTORIAL)),
+    }
+}
+
+fn power(base: &BigInt, exponent: &BigInt) -> Result<BigInt, String> {
+    // 0, 1 and −1 stay small for any exponent
+    if base.abs() <= BigInt::one() {
+        return if exponent.is_zero() {
+            Ok(BigInt::one())
+        } else if base.is_zero() && exponent.is_negative() {
+            Err("Cannot raise zero to a negative power".to_string())
+        } else if base.is_negative() && exponent.is_even() {
+            Ok(BigInt::one())
+        } else {
+            Ok(base.clone())
+        };
+    }
+    if exponent.is_negative() {
+        return Err(format!("{}^{} is not an integer", base, exponent));
+    }
+    match exponent.to_u32() {
+        Some(e) if base.bits().saturating_mul(u64::from(e)) <= MAX_BITS => Ok(base.pow(e)),
+        _ => Err("Exact power is too large".to_string()),
+    }
+}
+
+/// `f64` values are exact integers only up to 2^53.
+pub fn from_float(x: f64) -> Result<BigInt, String> {
+    if !x.is_finite() || x.fract() != 0.0 {
+        return Err(format!("{} is not an integer", x));
+    }
+    if x.abs() > EXACT_LIMIT {
+        return Err(format!("{} is too large to be exact; write out its digits or use ^", x));
+    }
+    Ok(BigInt::from(x as i64))
+}
+
+/// Big integers are shown as the nearest `f64`.
+pub fn to_f64(n: &BigInt) -> f64 {
+    n.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Evaluates an integer argument exactly: literals, `+ − × ^ !`, exact
+/// division and the number-theory functions stay big integers; anything
+/// else is evaluated as a number that must be a whole number.
+pub fn exact(expr: &Expr, ctx: &Context) -> Result<BigInt, String> {
+    match expr {
+        Expr::BigInteger(n) => Ok(n.clone()),
+        Expr::Negate(inner) => Ok(-exact(inner, ctx)?),
+        Expr::Binary(
+            op @ (Operation::Add | Operation::Subtract | Operation::Multiply | Operation::Divide | Operation::Power),
+            lhs,
+            rhs,
+        ) => {
+            let (a, b) = (exact(lhs, ctx)?, exact(rhs, ctx)?);
+            match op {
+                Operation::Add => Ok(a + b),
+                Operation::Subtract => Ok(a - b),
+                Operation::Multiply => Ok(a * b),
+                Operation::Divide => match div_rem(&a, &b, ModConvention::Truncated)? {
+                    (q, r) if r.is_zero() => Ok(q),
+                    _ => Err(format!("{}/{} is not an integer", a, b)),
+                },
+                _ => power(&a, &b),
+            }
+        }
+        Expr::Unary(Operation::Square, inner) => {
+            let a = exact(inner, ctx)?;
+            Ok(&a * &a)
+        }
+        Expr::Unary(Operation::Factorial, inner) => factorial(&exact(inner, ctx)?),
+        Expr::Integer(function, args) => function.exact(args, ctx),
+        _ => from_float(expr.eval(ctx)?),
+    }
+}
+
+/// Number-theory functions of the expression language.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegerFunction {
+    Gcd,
+    Lcm,
+    Mod(ModConvention),
+    Div(ModConvention),
+    IsPrime,
+    NextPrime,
+    PreviousPrime,
+    Totient,
+    ModPow,
+    ModInverse,
+    /// The prime factors with repetition, as a vector.
+    Factor,
+}
+
+impl IntegerFunction {
+    pub fn lookup(name: &str) -> Option<IntegerFunction> {
+        Some(match name {
+            "gcd" => IntegerFunction::Gcd,
+            "lcm" => IntegerFunction::Lcm,
+            "mod" => IntegerFunction::Mod(ModConvention::Floored),
+            "rem" => IntegerFunction::Mod(ModConvention::Truncated),
+            "emod" => IntegerFunction::Mod(ModConvention::Euclidean),
+            "div" => IntegerFunction::Div(ModConvention::Floored),
+            "quot" => IntegerFunction::Div(ModConvention::Truncated),
+            "ediv" => IntegerFunction::Div(ModConvention::Euclidean),
+            "isprime" => IntegerFunction::IsPrime,
+            "nextprime" => IntegerFunction::NextPrime,
+            "prevprime" => IntegerFunction::PreviousPrime,
+            "totient" | "phi" | "φ" => IntegerFunction::Totient,
+            "modpow" => IntegerFunction::ModPow,
+            "modinv" => IntegerFunction::ModInverse,
+            "factor" => IntegerFunction::Factor,
+            _ => return None,
+        })
+    }
+
+    /// Fields of the number theory panel the function reads.
+    pub fn fields(self) -> &'static [&'static str] {
+        match self {
+            IntegerFunction::Gcd | IntegerFunction::Lcm | IntegerFunction::Mod(_) | IntegerFunction::Div(_) => &["a", "b"],
+            IntegerFunction::ModPow => &["a", "b", "m"],
+            IntegerFunction::ModInverse => &["a", "m"],
+            _ => &["a"],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IntegerFunction::Gcd => "gcd",
+            IntegerFunction::Lcm => "lcm",
+            IntegerFunction::Mod(ModConvention::Floored) => "mod",
+            IntegerFunction::Mod(ModConvention::Truncated) => "rem",
+            IntegerFunction::Mod(ModConvention::Euclidean) => "emod",
+            IntegerFunction::Div(ModConvention::Floored) => "div",
+            IntegerFunction::Div(ModConvention::Truncated) => "quot",
+            IntegerFunction::Div(ModConvention::Euclidean) => "ediv",
+            IntegerFunction::IsPrime => "isprime",
+            IntegerFunction::NextPrime => "nextprime",
+            IntegerFunction::PreviousPrime => "prevprime",
+            IntegerFunction::Totient => "totient",
+            IntegerFunction::ModPow => "modpow",
+            IntegerFunction::ModInverse => "modinv",
+            IntegerFunction::Factor => "factor",
+        }
+    }
+
+    /// Applies the function to exact integer arguments. `isprime` gives 1
+    /// or 0.
+    pub fn apply(self, args: &[BigInt]) -> Result<BigInt, String> {
+        let usage = |count: &str| format!("{} takes {}", self.name(), count);
+        match (self, args) {
+            (IntegerFunction::Gcd, [first, rest @ ..]) if !rest.is_empty() => {
+                Ok(rest.iter().fold(first.abs(), |acc, n| acc.gcd(n)))
+            }
+            (IntegerFunction::Lcm, [first, rest @ ..]) if !rest.is_empty() => {
+                Ok(rest.iter().fold(first.abs(), |acc, n| acc.lcm(n)))
+            }
+            (IntegerFunction::Gcd | IntegerFunction::Lcm, _) => Err(usage("2 or more integers")),
+            (IntegerFunction::Mod(convention), [a, b]) => Ok(div_rem(a, b, convention)?.1),
+            (IntegerFunction::Div(convention), [a, b]) => Ok(div_rem(a, b, convention)?.0),
+            (IntegerFunction::Mod(_) | IntegerFunction::Div(_), _) => Err(usage("2 integers")),
+            (IntegerFunction::IsPrime, [n]) => Ok(BigInt::from(is_prime(n) as u8)),
+            (IntegerFunction::NextPrime, [n]) => Ok(next_prime(n)),
+            (IntegerFunction::PreviousPrime, [n]) => previous_prime(n),
+            (IntegerFunction::Totient, [n]) => totient(n),
+            (IntegerFunction::ModInverse, [a, m]) => mod_inverse(a, m),
+            (IntegerFunction::ModInverse, _) => Err(usage("an integer and a modulus")),
+            (IntegerFunction::ModPow, [a, e, m]) => mod_pow(a, e, m),
+            (IntegerFunction::ModPow, _) => Err(usage("a base, an exponent and a modulus")),
+            (IntegerFunction::Factor, _) => Err("factor gives a list of primes, not an integer".to_string()),
+            _ => Err(usage("1 integer")),
+        }
+    }
+
+    fn exact(self, args: &[Expr], ctx: &Context) -> Result<BigInt, String> {
+        let args = args.iter().map(|arg| exact(arg, ctx)).collect::<Result<Vec<_>, _>>()?;
+        self.apply(&args)
+    }
+
+    /// Evaluates a call; the result is shown as a number, or as a vector
+    /// of primes for `factor`.
+    pub fn evaluate(self, args: &[Expr], ctx: &Context) -> Result<Value, String> {
+        if self == IntegerFunction::Factor {
+            let [n] = args else {
+                return Err("factor takes 1 integer".to_string());
+            };
+            let factors = factorize(&exact(n, ctx)?)?;
+            let primes = factors
+                .iter()
+                .flat_map(|(prime, exponent)| std::iter::repeat_n(to_f64(prime), *exponent as usize))
+                .collect();
+            return Ok(Value::Vector(primes));
+        }
+        Ok(Value::Scalar(to_f64(&self.exact(args, ctx)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        text.parse().unwrap()
+    }
+
+    fn factors_of(text: &str) -> Vec<(BigInt, u32)> {
+        factorize(&big(text)).unwrap()
+    }
+
+    #[test]
+    fn known_primes_and_composites() {
+        for prime in ["2", "3", "97", "101", "7919", "2147483647", "2305843009213693951"] {
+            assert!(is_prime(&big(prime)), "{} is prime", prime);
+        }
+        for composite in ["-7", "0", "1", "4", "100", "7917", "4294967297"] {
+            assert!(!is_prime(&big(composite)), "{} is composite", composite);
+        }
+    }
+
+    #[test]
+    fn carmichael_numbers_are_composite() {
+        for carmichael in ["561", "1105", "1729", "2465", "2821", "6601", "8911", "41041", "825265", "321197185"] {
+            assert!(!is_prime(&big(carmichael)), "{} is a Carmichael number", carmichael);
+        }
+    }
+
+    #[test]
+    fn primality_near_the_deterministic_limit() {
+        let limit = BigInt::from(DETERMINISTIC_LIMIT);
+        assert!(is_certain(&(&limit - 1u32)));
+        assert!(!is_certain(&limit));
+        // The smallest strong pseudoprime to the bases up to 37 is caught by 41
+        assert!(!is_prime(&big("318665857834031151167461")));
+        // The limit itself passes every base up to 41; the extra bases catch it
+        assert!(!is_prime(&limit));
+        assert_eq!(previous_prime(&limit), Ok(big("3317044064679887385961813")));
+        assert_eq!(next_prime(&limit), big("3317044064679887385962123"));
+        // 2^89 − 1 is above the limit and still found prime
+        assert!(is_prime(&big("618970019642690137449562111")));
+    }
+
+    #[test]
+    fn next_and_previous_primes() {
+        assert_eq!(next_prime(&big("-5")), big("2"));
+        assert_eq!(next_prime(&big("2")), big("3"));
+        assert_eq!(next_prime(&big("7919")), big("7927"));
+        assert_eq!(previous_prime(&big("3")), Ok(big("2")));
+        assert_eq!(previous_prime(&big("7927")), Ok(big("7919")));
+        assert_eq!(previous_prime(&big("2")), Err("There is no prime below 2".to_string()));
+        assert!(previous_prime(&big("-10")).is_err());
+    }
+
+    #[test]
+    fn factorization_by_trial_division() {
+        assert_eq!(factors_of("120"), vec![(big("2"), 3), (big("3"), 1), (big("5"), 1)]);
+        assert_eq!(format_factors(&big("-120"), &factors_of("-120")), "−1 × 2^3 × 3 × 5");
+        assert_eq!(format_factors(&big("1"), &factors_of("1")), "1");
+        assert_eq!(factorize(&big("0")), Err("0 has no prime factorization".to_string()));
+        assert_eq!(
+            factors_of("600851475143"),
+            vec![(big("71"), 1), (big("839"), 1), (big("1471"), 1), (big("6857"), 1)]
+        );
+    }
+
+    #[test]
+    fn factorization_by_brent_splits_large_semiprimes() {
+        assert_eq!(brent(&big("8051"), &big("1")).map(|g| g == big("83") || g == big("97")), Some(true));
+        assert_eq!(
+            factors_of("998244359987710471"),
+            vec![(big("998244353"), 1), (big("1000000007"), 1)]
+        );
+        // Mersenne primes 2^31 − 1 and 2^61 − 1
+        assert_eq!(
+            factors_of("4951760154835678088235319297"),
+            vec![(big("2147483647"), 1), (big("2305843009213693951"), 1)]
+        );
+        assert_eq!(factors_of("4294967297"), vec![(big("641"), 1), (big("6700417"), 1)]);
+    }
+
+    #[test]
+    fn modular_inverse_and_power() {
+        assert_eq!(mod_inverse(&big("3"), &big("11")), Ok(big("4")));
+        assert_eq!(mod_inverse(&big("-3"), &big("11")), Ok(big("7")));
+        assert_eq!(
+            mod_inverse(&big("6"), &big("9")),
+            Err("6 has no inverse modulo 9 (gcd 3)".to_string())
+        );
+        assert_eq!(mod_inverse(&big("3"), &big("0")), Err("Modulus must be positive".to_string()));
+        assert_eq!(mod_pow(&big("3"), &big("-1"), &big("11")), Ok(big("4")));
+        assert_eq!(mod_pow(&big("2"), &big("100"), &big("1000000007")), Ok(big("976371285")));
+        assert!(mod_pow(&big("6"), &big("-1"), &big("9")).is_err());
+    }
+
+    #[test]
+    fn division_conventions() {
+        let cases = [
+            (-7, 2, [(-4, 1), (-3, -1), (-4, 1)]),
+            (7, -2, [(-4, -1), (-3, 1), (-3, 1)]),
+            (-7, -2, [(3, -1), (3, -1), (4, 1)]),
+        ];
+        for (a, b, expected) in cases {
+            for (convention, (q, r)) in ModConvention::ALL.into_iter().zip(expected) {
+                assert_eq!(
+                    div_rem(&BigInt::from(a), &BigInt::from(b), convention),
+                    Ok((BigInt::from(q), BigInt::from(r))),
+                    "{} / {} {:?}",
+                    a,
+                    b,
+                    convention
+                );
+            }
+        }
+        assert!(div_rem(&big("1"), &big("0"), ModConvention::Floored).is_err());
+    }
+
+    #[test]
+    fn totient_of_known_values() {
+        assert_eq!(totient(&big("1")), Ok(big("1")));
+        assert_eq!(totient(&big("36")), Ok(big("12")));
+        assert_eq!(totient(&big("97")), Ok(big("96")));
+        assert!(totient(&big("0")).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+/// Digit grouping styles for the integer part of a number.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Grouping {
+    #[default]
+    None,
+    /// Groups of three: 1,234,567
+    Thousands,
+    /// Indian lakh/crore grouping: 12,34,567
+    Indian,
+}
+
+impl Grouping {
+    pub const ALL: [Grouping; 3] = [Grouping::None, Grouping::Thousands, Grouping::Indian];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Grouping::None => "None",
+            Grouping::Thousands => "Thousands",
+            Grouping::Indian => "Lakh/crore",
+        }
+    }
+}
+
+/// Separators used when showing and reading numbers.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct NumberLocale {
+    pub grouping: Grouping,
+    pub group_separator: char,
+    pub decimal_separator: char,
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self {
+            grouping: Grouping::None,
+            group_separator: ',',
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl NumberLocale {
+    pub const GROUP_SEPARATORS: [(char, &'static str); 4] = [
+        (',', "Comma"),
+        ('.', "Period"),
+        ('\u{202F}', "Space"),
+        ('\'', "Apostrophe"),
+    ];
+
+    pub const DECIMAL_SEPARATORS: [(char, &'static str); 2] = [('.', "Period"), (',', "Comma")];
+
+    /// Whether a typed character should be treated as the decimal key.
+    pub fn is_decimal_key(&self, ch: char) -> bool {
+        ch == self.decimal_separator || (ch == '.' && !self.uses_group_separator('.'))
+    }
+
+    fn uses_group_separator(&self, ch: char) -> bool {
+        self.grouping != Grouping::None && self.group_separator == ch
+    }
+
+    /// Rewrites a canonical number string (`-1234.5e6`) with this locale's
+    /// grouping and decimal separator. Numbers written in parts, such as
+    /// `12°34'56.7"`, `1 3/4` or `5.2 ± 0.1`, are rewritten part by part; anything else
+    /// that is not a plain number, such as `∞` or `NaN`, is returned
+    /// unchanged.
+    pub fn localize(&self, canonical: &str) -> String {
+        if canonical.contains(['°', '/', '±']) {
+            let mut out = String::new();
+            let mut part = String::new();
+            for ch in canonical.chars() {
+                if ch.is_ascii_digit() || ch == '.' {
+                    part.push(ch);
+                } else {
+                    out.push_str(&self.localize(&std::mem::take(&mut part)));
+                    out.push(ch);
+                }
+            }
+            out.push_str(&self.localize(&part));
+            return out;
+        }
+        let is_numeric = canonical
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e'));
+        if canonical.is_empty() || !is_numeric {
+            return canonical.to_string();
+        }
+
+        let (mantissa, exponent) = match canonical.split_once('e') {
+            Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+            None => (canonical, None),
+        };
+        let (sign, unsigned) = match mantissa.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", mantissa),
+        };
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let mut out = String::from(sign);
+        out.push_str(&self.group_digits(integer));
+        if let Some(fraction) = fraction {
+            out.push(self.decimal_separator);
+            out.push_str(fraction);
+        }
+        if let Some(exponent) = exponent {
+            out.push('e');
+            out.push_str(exponent);
+        }
+        out
+    }
+
+    fn group_digits(&self, digits: &str) -> String {
+        if self.grouping == Grouping::None || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return digits.to_string();
+        }
+
+        // Split off group sizes from the right: always 3 first, then 3 or 2
+        let mut groups = Vec::new();
+        let mut rest = digits;
+        let mut size = 3;
+        while rest.len() > size {
+            let (head, tail) = rest.split_at(rest.len() - size);
+            groups.push(tail);
+            rest = head;
+            if self.grouping == Grouping::Indian {
+                size = 2;
+            }
+        }
+        groups.push(rest);
+        groups.reverse();
+        groups.join(&self.group_separator.to_string())
+    }
+
+    /// Converts localized text back into canonical form, dropping group
+    /// separators and mapping the decimal separator to `.`. A `,` or `.`
+    /// that is neither separator in this locale is kept, so that the text
+    /// fails to parse rather than having its digits joined.
+    pub fn delocalize(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for ch in text.trim().chars() {
+            if ch == self.decimal_separator {
+                out.push('.');
+            } else if self.is_group_separator(ch) {
+                continue;
+            } else {
+                out.push(ch);
+            }
+        }
+        out
+    }
+
+    fn is_group_separator(&self, ch: char) -> bool {
+        self.uses_group_separator(ch) || matches!(ch, ' ' | '\u{00A0}' | '\u{202F}' | '\'' | '_')
+    }
+
+    /// Parses localized text such as `1.234.567,89` into a number.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        crate::calculator::parse_number(&self.delocalize(text))
+    }
+}
+
+const CURRENCY_SYMBOLS: [char; 8] = ['$', '€', '£', '¥', '₹', '₩', '₽', '¢'];
+
+impl NumberLocale {
+    /// Parses a number copied from elsewhere: tolerates currency symbols and
+    /// codes (`$`, `USD`), accounting-style `(1,234)` negatives, and either
+    /// locale-style or canonical separators.
+    pub fn parse_lenient(&self, text: &str) -> Option<f64> {
+        if let Some(value) = self.parse(text) {
+            return Some(value);
+        }
+
+        let without_symbols: String = text
+            .chars()
+            .filter(|c| !CURRENCY_SYMBOLS.contains(c))
+            .collect();
+        let cleaned = without_symbols.trim_matches(|c: char| c.is_ascii_uppercase() || c.is_whitespace());
+        let (negative, body) = match cleaned.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(inner) => (true, inner.trim()),
+            None => (false, cleaned),
+        };
+
+        let value = self.parse(body).or_else(|| parse_canonical(body))?;
+        Some(if negative { -value } else { value })
+    }
+}
+
+/// Reads canonical `1,234.5` notation. Every comma must start a group of
+/// exactly three digits, so that `1,5` is rejected rather than read as 15.
+fn parse_canonical(text: &str) -> Option<f64> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let unsigned = text.strip_prefix('-').unwrap_or(&text);
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if fraction.contains(',') {
+        return None;
+    }
+    let mut groups = integer.split(',');
+    let leading = groups.next().unwrap_or_default();
+    let grouped = !leading.is_empty()
+        && leading.len() <= 3
+        && leading.chars().all(|c| c.is_ascii_digit())
+        && groups.all(|group| group.len() == 3 && group.chars().all(|c| c.is_ascii_digit()));
+    if !grouped {
+        return None;
+    }
+    text.replace(',', "").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale(grouping: Grouping, group_separator: char, decimal_separator: char) -> NumberLocale {
+        NumberLocale { grouping, group_separator, decimal_separator }
+    }
+
+    #[test]
+    fn stray_separators_are_rejected_rather_than_joined() {
+        let plain = NumberLocale::default();
+        assert_eq!(plain.parse("1,5"), None);
+        assert_eq!(plain.parse_lenient("1,5"), None);
+        assert_eq!(plain.parse_lenient("1,23,4.5"), None);
+        assert_eq!(plain.parse("1.5"), Some(1.5));
+        // Canonical thousands grouping is still understood when pasted
+        assert_eq!(plain.parse_lenient("$1,234,567.25"), Some(1_234_567.25));
+        assert_eq!(plain.parse_lenient("(1,234)"), Some(-1234.0));
+
+        let german = locale(Grouping::None, '.', ',');
+        assert_eq!(german.parse("1,5"), Some(1.5));
+        assert_eq!(german.parse("1.234,5"), None);
+        assert_eq!(german.parse_lenient("1.234,5"), None);
+    }
+
+    #[test]
+    fn own_group_separator_is_dropped() {
+        let german = locale(Grouping::Thousands, '.', ',');
+        assert_eq!(german.parse("1.234.567,89"), Some(1_234_567.89));
+        let english = locale(Grouping::Thousands, ',', '.');
+        assert_eq!(english.parse("1,234.5"), Some(1234.5));
+        assert_eq!(english.parse("1\u{202F}234.5"), Some(1234.5));
+        assert_eq!(english.localize("-1234567.5"), "-1,234,567.5");
+    }
+}
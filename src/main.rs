@@ -1,6 +1,27 @@
 mod app;
+mod business;
 mod calculator;
+mod calculus;
+mod complex;
+mod currency;
+mod dates;
+mod distribution;
+mod entry;
+mod expression;
+mod finance;
+mod graph;
+mod integer;
+mod locale;
+mod matrix;
+mod notation;
+mod regression;
+mod solver;
+mod statistics;
+mod tape;
 mod ui;
+mod uncertainty;
+mod units;
+mod value;
 
 use app::CalcsPlus;
 
@@ -0,0 +1,598 @@
+use crate::complex::Complex;
+use std::ops::{Index, IndexMut};
+
+/// Largest number of rows or columns the grid editor offers.
+pub const MAX_DIMENSION: usize = 8;
+const MAX_QR_ITERATIONS: usize = 1_000;
+
+/// Dense row-major matrix of reals.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (row, col): (usize, usize)) -> &f64 {
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![0.0; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut matrix = Self::zeros(n, n);
+        for i in 0..n {
+            matrix[(i, i)] = 1.0;
+        }
+        matrix
+    }
+
+    pub fn from_rows(rows: &[&[f64]]) -> Self {
+        let mut matrix = Self::zeros(rows.len(), rows.first().map_or(0, |row| row.len()));
+        for (i, row) in rows.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                matrix[(i, j)] = value;
+            }
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Changes the shape, keeping the entries that still fit and filling
+    /// new ones with zero.
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        let mut resized = Self::zeros(rows, cols);
+        for i in 0..rows.min(self.rows) {
+            for j in 0..cols.min(self.cols) {
+                resized[(i, j)] = self[(i, j)];
+            }
+        }
+        *self = resized;
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let mut result = Self::zeros(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                result[(j, i)] = self[(i, j)];
+            }
+        }
+        result
+    }
+
+    fn same_shape(&self, other: &Matrix, symbol: char) -> Result<(), String> {
+        if self.rows == other.rows && self.cols == other.cols {
+            Ok(())
+        } else {
+            Err(format!(
+                "Cannot {} a {}×{} and a {}×{} matrix",
+                if symbol == '+' { "add" } else { "subtract" },
+                self.rows,
+                self.cols,
+                other.rows,
+                other.cols
+            ))
+        }
+    }
+
+    pub fn add(&self, other: &Matrix) -> Result<Matrix, String> {
+        self.same_shape(other, '+')?;
+        let data = self.data.iter().zip(&other.data).map(|(a, b)| a + b).collect();
+        Ok(Matrix { data, ..*self })
+    }
+
+    pub fn subtract(&self, other: &Matrix) -> Result<Matrix, String> {
+        self.same_shape(other, '−')?;
+        let data = self.data.iter().zip(&other.data).map(|(a, b)| a - b).collect();
+        Ok(Matrix { data, ..*self })
+    }
+
+    pub fn multiply(&self, other: &Matrix) -> Result<Matrix, String> {
+        if self.cols != other.rows {
+            return Err(format!(
+                "Cannot multiply a {}×{} by a {}×{} matrix",
+                self.rows, self.cols, other.rows, other.cols
+            ));
+        }
+        let mut result = Self::zeros(self.rows, other.cols);
+        for i in 0..self.rows {
+            for j in 0..other.cols {
+                result[(i, j)] = (0..self.cols).map(|k| self[(i, k)] * other[(k, j)]).sum();
+            }
+        }
+        Ok(result)
+    }
+
+    fn require_square(&self, what: &str) -> Result<(), String> {
+        if self.is_square() {
+            Ok(())
+        } else {
+            Err(format!("{} needs a square matrix, not {}×{}", what, self.rows, self.cols))
+        }
+    }
+
+    pub fn trace(&self) -> Result<f64, String> {
+        self.require_square("The trace")?;
+        Ok((0..self.rows).map(|i| self[(i, i)]).sum())
+    }
+
+    /// Entries this small relative to the largest are treated as zero by
+    /// rank and singularity tests.
+    fn tolerance(&self) -> f64 {
+        let largest = self.data.iter().fold(0.0f64, |acc, x| acc.max(x.abs()));
+        self.rows.max(self.cols) as f64 * f64::EPSILON * largest
+    }
+
+    /// LU decomposition with partial pivoting: P·A = L·U, returned as
+    /// (P, L, U) together with the permutation's sign.
+    pub fn lu(&self) -> Result<(Matrix, Matrix, Matrix, f64), String> {
+        self.require_square("LU decomposition")?;
+        let n = self.rows;
+        let tolerance = self.tolerance();
+        let mut u = self.clone();
+        let mut l = Self::identity(n);
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+        for k in 0..n {
+            let pivot = (k..n).max_by(|&a, &b| u[(a, k)].abs().total_cmp(&u[(b, k)].abs())).unwrap_or(k);
+            if pivot != k {
+                for j in 0..n {
+                    u.data.swap(k * n + j, pivot * n + j);
+                }
+                for j in 0..k {
+                    l.data.swap(k * n + j, pivot * n + j);
+                }
+                permutation.swap(k, pivot);
+                sign = -sign;
+            }
+            if u[(k, k)].abs() <= tolerance {
+                continue;
+            }
+            for i in k + 1..n {
+                let factor = u[(i, k)] / u[(k, k)];
+                l[(i, k)] = factor;
+                u[(i, k)] = 0.0;
+                for j in k + 1..n {
+                    u[(i, j)] -= factor * u[(k, j)];
+                }
+            }
+        }
+        let mut p = Self::zeros(n, n);
+        for (i, &row) in permutation.iter().enumerate() {
+            p[(i, row)] = 1.0;
+        }
+        Ok((p, l, u, sign))
+    }
+
+    /// The determinant, exactly 0 when a pivot is within rounding of zero,
+    /// as `solve` and `rank` judge singularity.
+    pub fn determinant(&self) -> Result<f64, String> {
+        self.require_square("The determinant")?;
+        let (_, _, u, sign) = self.lu()?;
+        let tolerance = self.tolerance();
+        if (0..self.rows).any(|i| u[(i, i)].abs() <= tolerance) {
+            return Ok(0.0);
+        }
+        Ok((0..self.rows).fold(sign, |acc, i| acc * u[(i, i)]))
+    }
+
+    /// Solves A·X = B for X, with A square and nonsingular.
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, String> {
+        self.require_square("Solving Ax = b")?;
+        if b.rows != self.rows {
+            return Err(format!("b needs {} rows to match A, not {}", self.rows, b.rows));
+        }
+        let n = self.rows;
+        let (p, l, u, _) = self.lu()?;
+        let tolerance = self.tolerance();
+        if (0..n).any(|i| u[(i, i)].abs() <= tolerance) {
+            return Err("Matrix is singular".to_string());
+        }
+        let mut x = p.multiply(b)?;
+        for col in 0..b.cols {
+            // Forward substitution with the unit lower triangle, then back
+            // substitution with the upper one
+            for i in 0..n {
+                let sum: f64 = (0..i).map(|k| l[(i, k)] * x[(k, col)]).sum();
+                x[(i, col)] -= sum;
+            }
+            for i in (0..n).rev() {
+                let sum: f64 = (i + 1..n).map(|k| u[(i, k)] * x[(k, col)]).sum();
+                x[(i, col)] = (x[(i, col)] - sum) / u[(i, i)];
+            }
+        }
+        Ok(x)
+    }
+
+    pub fn inverse(&self) -> Result<Matrix, String> {
+        self.require_square("The inverse")?;
+        self.solve(&Self::identity(self.rows))
+    }
+
+    /// Number of pivots found by Gaussian elimination with partial pivoting.
+    pub fn rank(&self) -> usize {
+        let tolerance = self.tolerance();
+        let mut m = self.clone();
+        let mut rank = 0;
+        for col in 0..self.cols {
+            if rank == self.rows {
+                break;
+            }
+            let pivot = (rank..self.rows)
+                .max_by(|&a, &b| m[(a, col)].abs().total_cmp(&m[(b, col)].abs()))
+                .unwrap_or(rank);
+            if m[(pivot, col)].abs() <= tolerance {
+                continue;
+            }
+            for j in 0..self.cols {
+                m.data.swap(rank * self.cols + j, pivot * self.cols + j);
+            }
+            for i in rank + 1..self.rows {
+                let factor = m[(i, col)] / m[(rank, col)];
+                for j in col..self.cols {
+                    m[(i, j)] -= factor * m[(rank, j)];
+                }
+            }
+            rank += 1;
+        }
+        rank
+    }
+
+    /// Householder QR decomposition: A = Q·R with Q orthogonal (m×m) and R
+    /// upper triangular (m×n).
+    pub fn qr(&self) -> (Matrix, Matrix) {
+        let (m, n) = (self.rows, self.cols);
+        let mut q = Self::identity(m);
+        let mut r = self.clone();
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let norm = (k..m).map(|i| r[(i, k)] * r[(i, k)]).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if r[(k, k)] > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = (k..m).map(|i| r[(i, k)]).collect();
+            v[0] -= alpha;
+            let v_norm = v.iter().map(|x| x * x).sum::<f64>();
+            if v_norm == 0.0 {
+                continue;
+            }
+            // R ← (I − 2vvᵀ/vᵀv)·R and Q ← Q·(I − 2vvᵀ/vᵀv)
+            for j in 0..n {
+                let dot: f64 = v.iter().enumerate().map(|(i, vi)| vi * r[(k + i, j)]).sum();
+                for (i, vi) in v.iter().enumerate() {
+                    r[(k + i, j)] -= 2.0 * dot / v_norm * vi;
+                }
+            }
+            for i in 0..m {
+                let dot: f64 = v.iter().enumerate().map(|(j, vj)| q[(i, k + j)] * vj).sum();
+                for (j, vj) in v.iter().enumerate() {
+                    q[(i, k + j)] -= 2.0 * dot / v_norm * vj;
+                }
+            }
+            for i in k + 1..m {
+                r[(i, k)] = 0.0;
+            }
+        }
+        (q, r)
+    }
+
+    /// Eigenvalues by shifted QR iteration on the Hessenberg form, sorted by
+    /// real then imaginary part. Negligible imaginary parts are dropped.
+    pub fn eigenvalues(&self) -> Result<Vec<Complex>, String> {
+        self.require_square("Eigenvalues")?;
+        let n = self.rows;
+        let scale = self.data.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let mut h: Vec<Vec<Complex>> = self
+            .hessenberg()
+            .data
+            .chunks(n.max(1))
+            .map(|row| row.iter().map(|&x| Complex::from(x)).collect())
+            .collect();
+        let mut eigenvalues = Vec::with_capacity(n);
+        let mut hi = n;
+        let mut iterations = 0;
+        while hi > 0 {
+            let last = hi - 1;
+            // Deflate at the lowest negligible subdiagonal entry
+            let mut lo = last;
+            while lo > 0 {
+                let neighbours = h[lo - 1][lo - 1].abs() + h[lo][lo].abs();
+                if h[lo][lo - 1].abs() <= f64::EPSILON * neighbours.max(f64::MIN_POSITIVE) {
+                    h[lo][lo - 1] = Complex::ZERO;
+                    break;
+                }
+                lo -= 1;
+            }
+            if lo == last {
+                eigenvalues.push(h[last][last]);
+                hi = last;
+                iterations = 0;
+                continue;
+            }
+            iterations += 1;
+            if iterations > MAX_QR_ITERATIONS {
+                return Err("Eigenvalues did not converge".to_string());
+            }
+            let shift = if iterations % 11 == 0 {
+                // Exceptional shift to break cycles
+                h[last][last] + Complex::from(h[last][last - 1].abs())
+            } else {
+                wilkinson_shift(h[last - 1][last - 1], h[last - 1][last], h[last][last - 1], h[last][last])
+            };
+            qr_step(&mut h, lo, last, shift);
+        }
+        let mut eigenvalues: Vec<Complex> = eigenvalues
+            .into_iter()
+            .map(|z| {
+                if z.im.abs() <= 1e-12 * scale.max(f64::MIN_POSITIVE) {
+                    Complex::from(z.re)
+                } else {
+                    z
+                }
+            })
+            .collect();
+        eigenvalues.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+        Ok(eigenvalues)
+    }
+
+    /// Upper Hessenberg matrix similar to this one, by Householder
+    /// reflections.
+    fn hessenberg(&self) -> Matrix {
+        let n = self.rows;
+        let mut h = self.clone();
+        for k in 0..n.saturating_sub(2) {
+            let norm = (k + 1..n).map(|i| h[(i, k)] * h[(i, k)]).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if h[(k + 1, k)] > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = (k + 1..n).map(|i| h[(i, k)]).collect();
+            v[0] -= alpha;
+            let v_norm = v.iter().map(|x| x * x).sum::<f64>();
+            if v_norm == 0.0 {
+                continue;
+            }
+            for j in 0..n {
+                let dot: f64 = v.iter().enumerate().map(|(i, vi)| vi * h[(k + 1 + i, j)]).sum();
+                for (i, vi) in v.iter().enumerate() {
+                    h[(k + 1 + i, j)] -= 2.0 * dot / v_norm * vi;
+                }
+            }
+            for i in 0..n {
+                let dot: f64 = v.iter().enumerate().map(|(j, vj)| h[(i, k + 1 + j)] * vj).sum();
+                for (j, vj) in v.iter().enumerate() {
+                    h[(i, k + 1 + j)] -= 2.0 * dot / v_norm * vj;
+                }
+            }
+        }
+        h
+    }
+}
+
+/// Eigenvalue of the trailing 2×2 block [[a, b], [c, d]] closer to d.
+fn wilkinson_shift(a: Complex, b: Complex, c: Complex, d: Complex) -> Complex {
+    let half_difference = (a - d).scale(0.5);
+    let root = (half_difference * half_difference + b * c).sqrt();
+    let mean = (a + d).scale(0.5);
+    let (first, second) = (mean + root, mean - root);
+    if (first - d).abs() <= (second - d).abs() {
+        first
+    } else {
+        second
+    }
+}
+
+/// One shifted QR step on the active block h[lo..=hi] using Givens rotations.
+fn qr_step(h: &mut [Vec<Complex>], lo: usize, hi: usize, shift: Complex) {
+    for (i, row) in h.iter_mut().enumerate().take(hi + 1).skip(lo) {
+        row[i] = row[i] - shift;
+    }
+    let mut rotations = Vec::with_capacity(hi - lo);
+    for k in lo..hi {
+        let (x, y) = (h[k][k], h[k + 1][k]);
+        let norm = x.abs().hypot(y.abs());
+        let (c, s) = if norm == 0.0 {
+            (Complex::ONE, Complex::ZERO)
+        } else {
+            (x.scale(1.0 / norm), y.scale(1.0 / norm))
+        };
+        let (top, bottom) = h.split_at_mut(k + 1);
+        for (upper, lower) in top[k][k..=hi].iter_mut().zip(&mut bottom[0][k..=hi]) {
+            (*upper, *lower) = (c.conj() * *upper + s.conj() * *lower, c * *lower - s * *upper);
+        }
+        rotations.push((c, s));
+    }
+    for (k, (c, s)) in (lo..hi).zip(rotations) {
+        for row in h.iter_mut().take((k + 2).min(hi) + 1).skip(lo) {
+            let (left, right) = (row[k], row[k + 1]);
+            row[k] = left * c + right * s;
+            row[k + 1] = right * c.conj() - left * s.conj();
+        }
+    }
+    for (i, row) in h.iter_mut().enumerate().take(hi + 1).skip(lo) {
+        row[i] = row[i] + shift;
+    }
+}
+
+/// A matrix stored under a name, e.g. `A`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NamedMatrix {
+    pub name: String,
+    pub matrix: Matrix,
+}
+
+/// Operations offered in matrix mode. Binary ones use the second operand
+/// B; `Solve` treats it as the right-hand side b.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatrixOp {
+    Add,
+    Subtract,
+    Multiply,
+    Transpose,
+    Inverse,
+    Determinant,
+    Rank,
+    Trace,
+    Lu,
+    Qr,
+    Eigenvalues,
+    Solve,
+}
+
+impl MatrixOp {
+    pub const ALL: [MatrixOp; 12] = [
+        MatrixOp::Add,
+        MatrixOp::Subtract,
+        MatrixOp::Multiply,
+        MatrixOp::Solve,
+        MatrixOp::Transpose,
+        MatrixOp::Inverse,
+        MatrixOp::Determinant,
+        MatrixOp::Rank,
+        MatrixOp::Trace,
+        MatrixOp::Lu,
+        MatrixOp::Qr,
+        MatrixOp::Eigenvalues,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MatrixOp::Add => "A + B",
+            MatrixOp::Subtract => "A − B",
+            MatrixOp::Multiply => "A × B",
+            MatrixOp::Transpose => "Aᵀ",
+            MatrixOp::Inverse => "A⁻¹",
+            MatrixOp::Determinant => "det",
+            MatrixOp::Rank => "rank",
+            MatrixOp::Trace => "tr",
+            MatrixOp::Lu => "LU",
+            MatrixOp::Qr => "QR",
+            MatrixOp::Eigenvalues => "eig",
+            MatrixOp::Solve => "Ax = B",
+        }
+    }
+
+    pub fn is_binary(self) -> bool {
+        matches!(self, MatrixOp::Add | MatrixOp::Subtract | MatrixOp::Multiply | MatrixOp::Solve)
+    }
+
+    /// Applies the operation to the named operands; `b` is required when
+    /// the operation is binary.
+    pub fn apply(self, a: &NamedMatrix, b: Option<&NamedMatrix>) -> Result<MatrixOutcome, String> {
+        let (x, y) = (&a.name, b.map_or("B", |b| b.name.as_str()));
+        let second = || b.map(|b| &b.matrix).ok_or_else(|| "Select a second matrix".to_string());
+        let single = |name: String, matrix: Matrix| MatrixOutcome::Matrices(vec![(name, matrix)]);
+        Ok(match self {
+            MatrixOp::Add => single(format!("{} + {}", x, y), a.matrix.add(second()?)?),
+            MatrixOp::Subtract => single(format!("{} − {}", x, y), a.matrix.subtract(second()?)?),
+            MatrixOp::Multiply => single(format!("{} × {}", x, y), a.matrix.multiply(second()?)?),
+            MatrixOp::Solve => single(format!("{}⁻¹{}", x, y), a.matrix.solve(second()?)?),
+            MatrixOp::Transpose => single(format!("{}ᵀ", x), a.matrix.transpose()),
+            MatrixOp::Inverse => single(format!("{}⁻¹", x), a.matrix.inverse()?),
+            MatrixOp::Determinant => MatrixOutcome::Scalar(format!("det({})", x), a.matrix.determinant()?),
+            MatrixOp::Rank => MatrixOutcome::Scalar(format!("rank({})", x), a.matrix.rank() as f64),
+            MatrixOp::Trace => MatrixOutcome::Scalar(format!("tr({})", x), a.matrix.trace()?),
+            MatrixOp::Lu => {
+                let (p, l, u, _) = a.matrix.lu()?;
+                MatrixOutcome::Matrices(vec![("L".to_string(), l), ("U".to_string(), u), ("P".to_string(), p)])
+            }
+            MatrixOp::Qr => {
+                let (q, r) = a.matrix.qr();
+                MatrixOutcome::Matrices(vec![("Q".to_string(), q), ("R".to_string(), r)])
+            }
+            MatrixOp::Eigenvalues => MatrixOutcome::Values(format!("eig({})", x), a.matrix.eigenvalues()?),
+        })
+    }
+}
+
+/// Result of a matrix operation: a number for the display, matrices to
+/// view and store, or a list of (possibly complex) values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatrixOutcome {
+    Scalar(String, f64),
+    Matrices(Vec<(String, Matrix)>),
+    Values(String, Vec<Complex>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matrix_close(actual: &Matrix, expected: &Matrix, tolerance: f64) {
+        assert_eq!((actual.rows(), actual.cols()), (expected.rows(), expected.cols()));
+        for (a, e) in actual.data.iter().zip(&expected.data) {
+            assert!((a - e).abs() <= tolerance, "{:?} vs {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn known_inverse_and_determinant() {
+        // The second-difference matrix; its inverse is ¼·[3 2 1; 2 4 2; 1 2 3]
+        let a = Matrix::from_rows(&[&[2.0, -1.0, 0.0], &[-1.0, 2.0, -1.0], &[0.0, -1.0, 2.0]]);
+        let expected = Matrix::from_rows(&[&[0.75, 0.5, 0.25], &[0.5, 1.0, 0.5], &[0.25, 0.5, 0.75]]);
+        assert_matrix_close(&a.inverse().unwrap(), &expected, 1e-15);
+        assert_matrix_close(&a.multiply(&a.inverse().unwrap()).unwrap(), &Matrix::identity(3), 1e-15);
+        assert!((a.determinant().unwrap() - 4.0).abs() <= 1e-14);
+
+        // A row swap is needed to pivot, which flips the determinant's sign
+        let swapped = Matrix::from_rows(&[&[0.0, 1.0, 2.0], &[1.0, 0.0, 3.0], &[4.0, -3.0, 8.0]]);
+        assert!((swapped.determinant().unwrap() - -2.0).abs() <= 1e-14);
+        let b = Matrix::from_rows(&[&[8.0], &[10.0], &[22.0]]);
+        let x = swapped.solve(&b).unwrap();
+        assert_matrix_close(&swapped.multiply(&x).unwrap(), &b, 1e-13);
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        let singular = Matrix::from_rows(&[&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0], &[7.0, 8.0, 9.0]]);
+        assert_eq!(singular.determinant().unwrap(), 0.0);
+        assert_eq!(singular.inverse().unwrap_err(), "Matrix is singular");
+        assert_eq!(singular.solve(&Matrix::zeros(3, 1)).unwrap_err(), "Matrix is singular");
+        assert_eq!(singular.rank(), 2);
+        assert_eq!(Matrix::zeros(2, 2).inverse().unwrap_err(), "Matrix is singular");
+    }
+
+    #[test]
+    fn non_square_and_mismatched_shapes_are_rejected() {
+        let wide = Matrix::from_rows(&[&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]]);
+        assert_eq!(wide.determinant().unwrap_err(), "The determinant needs a square matrix, not 2×3");
+        assert_eq!(wide.inverse().unwrap_err(), "The inverse needs a square matrix, not 2×3");
+        assert!(wide.solve(&Matrix::zeros(2, 1)).is_err());
+        assert!(wide.trace().is_err());
+        assert_eq!(wide.rank(), 2);
+        assert_eq!(
+            Matrix::identity(3).solve(&Matrix::zeros(2, 1)).unwrap_err(),
+            "b needs 3 rows to match A, not 2"
+        );
+        assert!(wide.multiply(&wide).is_err());
+        assert_eq!(wide.multiply(&wide.transpose()).unwrap(), Matrix::from_rows(&[&[14.0, 32.0], &[32.0, 77.0]]));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::calculator::format_number;
+
+/// Largest magnitude shown as a fraction or in degrees, minutes and
+/// seconds; beyond it the parts are no longer exact.
+const MAX_EXACT: f64 = 1e15;
+
+/// Closest fraction to `x` in `[0, 1)` with a denominator of at most
+/// `max_denominator`, from the continued fraction of `x` and the best
+/// semiconvergent at the bound.
+fn best_fraction(x: f64, max_denominator: u64) -> (u64, u64) {
+    let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+    let mut rest = x;
+    for _ in 0..64 {
+        // Capped so that the products below cannot overflow
+        let a = rest.floor().min(max_denominator as f64 + 1.0) as u64;
+        let (p2, q2) = (a * p1 + p0, a * q1 + q0);
+        if q2 > max_denominator {
+            let k = (max_denominator - q0) / q1;
+            let (ps, qs) = (k * p1 + p0, k * q1 + q0);
+            let error = |p: u64, q: u64| (x - p as f64 / q as f64).abs();
+            return if error(ps, qs) < error(p1, q1) { (ps, qs) } else { (p1, q1) };
+        }
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+        let remainder = rest - a as f64;
+        if remainder <= 0.0 || p1 as f64 / q1 as f64 == x {
+            break;
+        }
+        rest = 1.0 / remainder;
+    }
+    (p1, q1)
+}
+
+/// `x` as a whole number and a proper fraction, e.g. `1 3/4`, `-3/8` or
+/// `5`, with denominators of up to `digits` digits. Numbers no such
+/// fraction matches to within rounding are shown as decimals.
+pub fn format_fraction(x: f64, digits: u8) -> String {
+    if !x.is_finite() || x.abs() >= MAX_EXACT {
+        return format_number(x);
+    }
+    let max_denominator = 10u64.pow(u32::from(digits.clamp(1, 9))) - 1;
+    let whole = x.abs().trunc();
+    let (numerator, denominator) = best_fraction(x.abs() - whole, max_denominator);
+    let (whole, numerator) = if numerator == denominator { (whole + 1.0, 0) } else { (whole, numerator) };
+    let approximation = whole + numerator as f64 / denominator as f64;
+    if (approximation - x.abs()).abs() > 1e-12 * x.abs().max(1.0) {
+        return format_number(x);
+    }
+    let sign = if x < 0.0 && approximation != 0.0 { "-" } else { "" };
+    match (whole, numerator) {
+        (_, 0) => format!("{}{}", sign, whole),
+        (0.0, _) => format!("{}{}/{}", sign, numerator, denominator),
+        _ => format!("{}{} {}/{}", sign, whole, numerator, denominator),
+    }
+}
+
+/// Decimal degrees as degrees, minutes and seconds, e.g. `12°34'56.7"`,
+/// with `decimals` decimal places on the seconds.
+pub fn format_dms(degrees: f64, decimals: u8) -> String {
+    let decimals = usize::from(decimals.min(9));
+    let scale = 10f64.powi(decimals as i32);
+    let units = (degrees.abs() * 3600.0 * scale).round();
+    if !units.is_finite() || degrees.abs() >= MAX_EXACT / 3600.0 {
+        return format_number(degrees);
+    }
+    let whole = (units / (3600.0 * scale)).floor();
+    let units = units - whole * 3600.0 * scale;
+    let minutes = (units / (60.0 * scale)).floor();
+    let seconds = (units - minutes * 60.0 * scale) / scale;
+    let sign = if degrees < 0.0 && (whole != 0.0 || minutes != 0.0 || seconds != 0.0) { "-" } else { "" };
+    let width = if decimals > 0 { decimals + 3 } else { 2 };
+    format!("{}{}°{:02}'{:0width$.decimals$}\"", sign, whole, minutes, seconds)
+}
+
+/// Reads a pasted `12°34'56.7"` (minutes and seconds optional, `′` and
+/// `″` accepted) as decimal degrees, or a pasted `1 3/4` or `-3/8` as its
+/// value. Anything else is left to the number and expression parsers.
+pub fn parse(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, text),
+    };
+    let magnitude = if body.contains('°') {
+        parse_dms(body)?
+    } else {
+        parse_fraction(body)?
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+fn parse_dms(text: &str) -> Option<f64> {
+    let (degrees, mut rest) = text.split_once('°')?;
+    let mut value = parse_part(degrees)?;
+    for (marks, divisor) in [(['\'', '′'], 60.0), (['"', '″'], 3600.0)] {
+        if rest.trim().is_empty() {
+            break;
+        }
+        let (part, tail) = rest.split_once(marks)?;
+        value += parse_part(part)? / divisor;
+        rest = tail;
+    }
+    rest.trim().is_empty().then_some(value)
+}
+
+fn parse_fraction(text: &str) -> Option<f64> {
+    let (front, denominator) = text.split_once('/')?;
+    let (whole, numerator) = match front.trim().split_once(' ') {
+        Some((whole, numerator)) => (parse_whole(whole)?, parse_whole(numerator)?),
+        None => (0, parse_whole(front)?),
+    };
+    let denominator = parse_whole(denominator)?;
+    (denominator != 0).then(|| whole as f64 + numerator as f64 / denominator as f64)
+}
+
+/// A non-negative decimal field of a DMS value.
+fn parse_part(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let plain = !text.is_empty() && text.chars().all(|c| c.is_ascii_digit() || c == '.');
+    plain.then(|| text.parse().ok()).flatten()
+}
+
+/// A non-negative whole field of a fraction.
+fn parse_whole(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let plain = !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    plain.then(|| text.parse().ok()).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dms_round_trips_through_parse_and_format() {
+        let degrees = parse("12°34'56\"").unwrap();
+        assert!((degrees - (12.0 + 34.0 / 60.0 + 56.0 / 3600.0)).abs() < 1e-12);
+        assert_eq!(format_dms(degrees, 0), "12°34'56\"");
+        assert_eq!(format_dms(degrees, 2), "12°34'56.00\"");
+        assert_eq!(parse("-12° 34′ 56″"), Some(-degrees));
+        assert_eq!(format_dms(-degrees, 0), "-12°34'56\"");
+        assert_eq!(parse("12°"), Some(12.0));
+        assert_eq!(parse("12°30'"), Some(12.5));
+        assert_eq!(format_dms(-0.5, 1), "-0°30'00.0\"");
+    }
+
+    #[test]
+    fn dms_seconds_rounding_up_carry_into_minutes_and_degrees() {
+        // 12°59'59.964" has no 60" on display
+        assert_eq!(format_dms(12.99999, 0), "13°00'00\"");
+        assert_eq!(format_dms(12.99999, 2), "12°59'59.96\"");
+        assert_eq!(format_dms(10.0 + 29.0 / 60.0 + 59.6 / 3600.0, 0), "10°30'00\"");
+        // Too small to show is not negative
+        assert_eq!(format_dms(-1e-9, 0), "0°00'00\"");
+    }
+
+    #[test]
+    fn mixed_numbers_parse_and_format() {
+        assert_eq!(parse("1 3/4"), Some(1.75));
+        assert_eq!(parse("-1 3/4"), Some(-1.75));
+        assert_eq!(parse("-3/8"), Some(-0.375));
+        assert_eq!(parse(" 7/2 "), Some(3.5));
+        assert_eq!(format_fraction(1.75, 2), "1 3/4");
+        assert_eq!(format_fraction(-1.75, 2), "-1 3/4");
+        assert_eq!(format_fraction(-0.375, 1), "-3/8");
+        assert_eq!(format_fraction(5.0, 2), "5");
+        assert_eq!(format_fraction(-0.0, 2), "0");
+    }
+
+    #[test]
+    fn best_fractions_come_from_the_continued_fraction() {
+        assert_eq!(best_fraction(1.0 / 3.0, 9), (1, 3));
+        assert_eq!(best_fraction(0.333_333_333_333, 999), (1, 3));
+        assert_eq!(best_fraction(0.0, 99), (0, 1));
+        // π − 3: the convergent 1/7, the semiconvergent 14/99 and 16/113 (355/113)
+        assert_eq!(best_fraction(std::f64::consts::PI - 3.0, 9), (1, 7));
+        assert_eq!(best_fraction(std::f64::consts::PI - 3.0, 99), (14, 99));
+        assert_eq!(best_fraction(std::f64::consts::PI - 3.0, 999), (16, 113));
+        assert_eq!(format_fraction(2.0 / 3.0, 1), "2/3");
+        // No three-digit denominator matches π to within rounding
+        assert_eq!(format_fraction(std::f64::consts::PI, 3), format_number(std::f64::consts::PI));
+    }
+
+    #[test]
+    fn malformed_input_is_left_to_the_other_parsers() {
+        for text in ["1 3/0", "1/0", "1/2/3", "1.5/2", "a/b", "3", "", "1 /4 2", "12°x'", "12°30'extra", "°30'", "12°30\"5'"] {
+            assert_eq!(parse(text), None, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use crate::statistics::mean;
+
+/// Curve families fitted to paired (x, y) data.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum RegressionModel {
+    /// y = a + b·x
+    #[default]
+    Linear,
+    /// y = a₀ + a₁·x + … + aₙ·xⁿ
+    Polynomial(u8),
+    /// y = a·e^(b·x)
+    Exponential,
+    /// y = a + b·ln x
+    Logarithmic,
+    /// y = a·x^b
+    Power,
+}
+
+pub const MAX_POLYNOMIAL_DEGREE: u8 = 6;
+
+impl RegressionModel {
+    pub const ALL: [RegressionModel; 5] = [
+        RegressionModel::Linear,
+        RegressionModel::Polynomial(2),
+        RegressionModel::Exponential,
+        RegressionModel::Logarithmic,
+        RegressionModel::Power,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RegressionModel::Linear => "Linear",
+            RegressionModel::Polynomial(_) => "Polynomial",
+            RegressionModel::Exponential => "Exponential",
+            RegressionModel::Logarithmic => "Logarithmic",
+            RegressionModel::Power => "Power",
+        }
+    }
+
+    pub fn same_kind(self, other: RegressionModel) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+}
+
+/// Result of a regression. For the exponential and power models the fit
+/// is done on the log-transformed data, and `r`/`r_squared` describe that
+/// linearised fit, as spreadsheet trendlines and graphing calculators do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    pub model: RegressionModel,
+    /// Coefficients in the order of the model's formula: a, b for the
+    /// two-parameter models, a₀…aₙ for polynomials.
+    pub coefficients: Vec<f64>,
+    /// Correlation coefficient; not defined for polynomials above degree 1.
+    pub r: Option<f64>,
+    pub r_squared: f64,
+}
+
+impl Fit {
+    pub fn predict(&self, x: f64) -> f64 {
+        let c = &self.coefficients;
+        match self.model {
+            RegressionModel::Linear => c[0] + c[1] * x,
+            RegressionModel::Polynomial(_) => c.iter().rev().fold(0.0, |acc, &a| acc * x + a),
+            RegressionModel::Exponential => c[0] * (c[1] * x).exp(),
+            RegressionModel::Logarithmic => c[0] + c[1] * x.ln(),
+            RegressionModel::Power => c[0] * x.powf(c[1]),
+        }
+    }
+
+    /// The fitted equation, with coefficients rendered by `fmt`.
+    pub fn equation(&self, fmt: impl Fn(f64) -> String) -> String {
+        let c = &self.coefficients;
+        match self.model {
+            RegressionModel::Linear => format!("y = {} + {}·x", fmt(c[0]), fmt(c[1])),
+            RegressionModel::Polynomial(_) => {
+                let terms: Vec<String> = c
+                    .iter()
+                    .enumerate()
+                    .map(|(power, &a)| match power {
+                        0 => fmt(a),
+                        1 => format!("{}·x", fmt(a)),
+                        _ => format!("{}·x^{}", fmt(a), power),
+                    })
+                    .collect();
+                format!("y = {}", terms.join(" + "))
+            }
+            RegressionModel::Exponential => format!("y = {}·e^({}·x)", fmt(c[0]), fmt(c[1])),
+            RegressionModel::Logarithmic => format!("y = {} + {}·ln x", fmt(c[0]), fmt(c[1])),
+            RegressionModel::Power => format!("y = {}·x^{}", fmt(c[0]), fmt(c[1])),
+        }
+    }
+}
+
+pub fn fit(model: RegressionModel, points: &[(f64, f64)]) -> Result<Fit, String> {
+    match model {
+        RegressionModel::Linear => {
+            let (a, b, r) = linear(points)?;
+            Ok(Fit { model, coefficients: vec![a, b], r: Some(r), r_squared: r * r })
+        }
+        RegressionModel::Polynomial(degree) => polynomial(points, degree.clamp(1, MAX_POLYNOMIAL_DEGREE)),
+        RegressionModel::Exponential => {
+            if points.iter().any(|&(_, y)| y <= 0.0) {
+                return Err("Exponential regression needs positive y values".to_string());
+            }
+            let transformed: Vec<_> = points.iter().map(|&(x, y)| (x, y.ln())).collect();
+            let (ln_a, b, r) = linear(&transformed)?;
+            Ok(Fit { model, coefficients: vec![ln_a.exp(), b], r: Some(r), r_squared: r * r })
+        }
+        RegressionModel::Logarithmic => {
+            if points.iter().any(|&(x, _)| x <= 0.0) {
+                return Err("Logarithmic regression needs positive x values".to_string());
+            }
+            let transformed: Vec<_> = points.iter().map(|&(x, y)| (x.ln(), y)).collect();
+            let (a, b, r) = linear(&transformed)?;
+            Ok(Fit { model, coefficients: vec![a, b], r: Some(r), r_squared: r * r })
+        }
+        RegressionModel::Power => {
+            if points.iter().any(|&(x, y)| x <= 0.0 || y <= 0.0) {
+                return Err("Power regression needs positive x and y values".to_string());
+            }
+            let transformed: Vec<_> = points.iter().map(|&(x, y)| (x.ln(), y.ln())).collect();
+            let (ln_a, b, r) = linear(&transformed)?;
+            Ok(Fit { model, coefficients: vec![ln_a.exp(), b], r: Some(r), r_squared: r * r })
+        }
+    }
+}
+
+/// Least-squares line through `points`: intercept, slope and Pearson r.
+fn linear(points: &[(f64, f64)]) -> Result<(f64, f64, f64), String> {
+    if points.len() < 2 {
+        return Err("Regression needs at least two data points".to_string());
+    }
+    let xs: Vec<f64> = points.iter().map(|p| p.0).collect();
+    let ys: Vec<f64> = points.iter().map(|p| p.1).collect();
+    let (mx, my) = (mean(&xs), mean(&ys));
+    let sxx: f64 = xs.iter().map(|x| (x - mx) * (x - mx)).sum();
+    let syy: f64 = ys.iter().map(|y| (y - my) * (y - my)).sum();
+    let sxy: f64 = points.iter().map(|&(x, y)| (x - mx) * (y - my)).sum();
+    if sxx == 0.0 {
+        return Err("Regression needs at least two distinct x values".to_string());
+    }
+    let slope = sxy / sxx;
+    // A constant y is fitted exactly by a horizontal line
+    let r = if syy == 0.0 { 1.0 } else { sxy / (sxx * syy).sqrt() };
+    Ok((my - slope * mx, slope, r))
+}
+
+/// Polynomial least squares by solving the normal equations with
+/// partially pivoted Gaussian elimination. x is centred first to keep the
+/// system well conditioned, then the coefficients are expanded back.
+fn polynomial(points: &[(f64, f64)], degree: u8) -> Result<Fit, String> {
+    let n = degree as usize + 1;
+    if points.len() < n {
+        return Err(format!("Degree {} regression needs at least {} data points", degree, n));
+    }
+    let shift = mean(&points.iter().map(|p| p.0).collect::<Vec<_>>());
+
+    let mut matrix = vec![vec![0.0; n + 1]; n];
+    for &(x, y) in points {
+        let powers: Vec<f64> = (0..2 * n).map(|k| (x - shift).powi(k as i32)).collect();
+        for row in 0..n {
+            for col in 0..n {
+                matrix[row][col] += powers[row + col];
+            }
+            matrix[row][n] += powers[row] * y;
+        }
+    }
+    let centred = solve_augmented(matrix)
+        .ok_or_else(|| "Regression needs more distinct x values than the degree".to_string())?;
+
+    // Expand Σ cₖ (x − s)ᵏ into powers of x
+    let mut coefficients = vec![0.0; n];
+    for (k, &c) in centred.iter().enumerate() {
+        let mut binomial = 1.0;
+        for (j, coefficient) in coefficients.iter_mut().enumerate().take(k + 1) {
+            *coefficient += c * binomial * (-shift).powi((k - j) as i32);
+            binomial = binomial * (k - j) as f64 / (j + 1) as f64;
+        }
+    }
+
+    let fit = Fit {
+        model: RegressionModel::Polynomial(degree),
+        coefficients,
+        r: None,
+        r_squared: 0.0,
+    };
+    let my = mean(&points.iter().map(|p| p.1).collect::<Vec<_>>());
+    let ss_res: f64 = points.iter().map(|&(x, y)| (y - fit.predict(x)).powi(2)).sum();
+    let ss_tot: f64 = points.iter().map(|&(_, y)| (y - my).powi(2)).sum();
+    let r_squared = if ss_tot == 0.0 { 1.0 } else { 1.0 - ss_res / ss_tot };
+    let r = (degree == 1).then(|| r_squared.sqrt().copysign(fit.coefficients[1]));
+    Ok(Fit { r, r_squared, ..fit })
+}
+
+/// Solves an n×(n+1) augmented system; `None` if it is singular.
+fn solve_augmented(mut m: Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let n = m.len();
+    let scale = m.iter().flatten().fold(0.0f64, |acc, v| acc.max(v.abs()));
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+        if m[pivot][col].abs() <= 1e-12 * scale {
+            return None;
+        }
+        m.swap(col, pivot);
+        let (upper, lower) = m.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower {
+            let factor = row[col] / pivot_row[col];
+            for (value, &p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * p;
+            }
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| m[row][k] * solution[k]).sum();
+        solution[row] = (m[row][n] - tail) / m[row][row];
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        let error = (actual - expected).abs() / expected.abs().max(1.0);
+        assert!(error <= tolerance, "{} vs {}: error {:e}", actual, expected, error);
+    }
+
+    fn points(xs: &[f64], f: impl Fn(f64) -> f64) -> Vec<(f64, f64)> {
+        xs.iter().map(|&x| (x, f(x))).collect()
+    }
+
+    #[test]
+    fn exact_data_recovers_the_coefficients() {
+        let xs = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let cases = [
+            (RegressionModel::Linear, points(&xs, |x| 3.0 - 2.0 * x), [3.0, -2.0]),
+            (RegressionModel::Exponential, points(&xs, |x| 2.5 * (0.3 * x).exp()), [2.5, 0.3]),
+            (RegressionModel::Logarithmic, points(&xs, |x| 1.0 + 4.0 * x.ln()), [1.0, 4.0]),
+            (RegressionModel::Power, points(&xs, |x| 0.5 * x.powf(1.5)), [0.5, 1.5]),
+        ];
+        for (model, data, expected) in &cases {
+            let fit = fit(*model, data).unwrap();
+            assert_close(fit.coefficients[0], expected[0], 1e-12);
+            assert_close(fit.coefficients[1], expected[1], 1e-12);
+            assert_close(fit.r_squared, 1.0, 1e-12);
+        }
+        let falling = fit(RegressionModel::Linear, &cases[0].1).unwrap();
+        assert_close(falling.r.unwrap(), -1.0, 1e-12);
+        assert_close(falling.predict(10.0), -17.0, 1e-12);
+    }
+
+    #[test]
+    fn quadratic_data_recovers_the_polynomial() {
+        // Far from the origin, where uncentred normal equations lose digits
+        let data = points(&[1000.0, 1001.0, 1002.0, 1003.0, 1004.0, 1005.0], |x| 2.0 - 3.0 * x + 0.5 * x * x);
+        let quadratic = fit(RegressionModel::Polynomial(2), &data).unwrap();
+        assert_eq!(quadratic.coefficients.len(), 3);
+        for &(x, y) in &data {
+            assert_close(quadratic.predict(x), y, 1e-9);
+        }
+        assert_close(quadratic.coefficients[2], 0.5, 1e-9);
+        assert_close(quadratic.r_squared, 1.0, 1e-12);
+        assert_eq!(quadratic.r, None);
+    }
+
+    #[test]
+    fn identical_x_values_are_rejected() {
+        let data = [(2.0, 1.0), (2.0, 3.0), (2.0, 5.0)];
+        for model in RegressionModel::ALL {
+            assert!(fit(model, &data).is_err(), "{:?}", model);
+        }
+        assert_eq!(
+            fit(RegressionModel::Linear, &data).unwrap_err(),
+            "Regression needs at least two distinct x values"
+        );
+        assert_eq!(
+            fit(RegressionModel::Polynomial(2), &data).unwrap_err(),
+            "Regression needs more distinct x values than the degree"
+        );
+        assert!(fit(RegressionModel::Linear, &[(1.0, 1.0)]).is_err());
+        assert!(fit(RegressionModel::Power, &[(1.0, 1.0), (2.0, -1.0)]).is_err());
+    }
+}
@@ -0,0 +1,484 @@
+use crate::calculator::{full_precision, NumericPolicy};
+use crate::complex::Complex;
+use crate::expression::{self, Context, Expr};
+use crate::locale::NumberLocale;
+
+/// Highest polynomial degree accepted by the polynomial solver.
+pub const MAX_POLYNOMIAL_DEGREE: usize = 10;
+const MAX_ITERATIONS: usize = 200;
+/// Halvings that take any finite bracket down to adjacent doubles, which
+/// a root at 0 needs: the subnormals are only reached after about 1000.
+const MAX_BISECTIONS: usize = 2100;
+
+/// Root-finding algorithm for `f(x) = g(x)`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Method {
+    /// Brent's method when a sign change can be bracketed, otherwise Newton.
+    #[default]
+    Auto,
+    Bisection,
+    Brent,
+    Newton,
+}
+
+impl Method {
+    pub const ALL: [Method; 4] = [Method::Auto, Method::Bisection, Method::Brent, Method::Newton];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Method::Auto => "Auto",
+            Method::Bisection => "Bisection",
+            Method::Brent => "Brent",
+            Method::Newton => "Newton",
+        }
+    }
+}
+
+/// Where the search for a root starts.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Start {
+    /// Search outward from 0 (or 1 where 0 is undefined).
+    #[default]
+    Auto,
+    Guess(f64),
+    /// An interval [a, b] over which f(x) − g(x) changes sign.
+    Bracket(f64, f64),
+}
+
+impl Start {
+    pub fn label(self) -> &'static str {
+        match self {
+            Start::Auto => "Auto",
+            Start::Guess(_) => "Guess",
+            Start::Bracket(_, _) => "Bracket",
+        }
+    }
+}
+
+/// Inputs of the solver panel, kept with the app state.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct SolverSettings {
+    pub equation: String,
+    pub method: Method,
+    pub start: Start,
+    /// Coefficients from the highest power down, e.g. `1, 0, -2`.
+    pub coefficients: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solution {
+    pub root: f64,
+    /// The algorithm that produced the root; never `Auto`.
+    pub method: Method,
+    pub iterations: usize,
+}
+
+/// Solves `f(x) = g(x)` for x; without `=` the equation is `f(x) = 0`.
+pub fn solve(equation: &str, locale: &NumberLocale, ctx: &Context, method: Method, start: Start) -> Result<Solution, String> {
+    let (lhs, rhs) = match equation.split_once('=') {
+        Some((lhs, rhs)) => (expression::parse(lhs, locale)?, expression::parse(rhs, locale)?),
+        None => (expression::parse(equation, locale)?, Expr::Number(0.0)),
+    };
+    // Probe points follow IEEE rules, so that an underflow close to a root
+    // at 0 does not make the equation look undefined there
+    let mut ctx = ctx.clone();
+    ctx.options.policy = NumericPolicy::Ieee;
+    let mut equation = Equation { lhs, rhs, ctx };
+
+    let solution = match method {
+        Method::Newton => newton(&mut equation, start)?,
+        Method::Bisection => {
+            let (a, b) = bracket(&mut equation, start)?;
+            bisection(&mut equation, a, b)?
+        }
+        Method::Brent => {
+            let (a, b) = bracket(&mut equation, start)?;
+            brent(&mut equation, a, b)?
+        }
+        Method::Auto => match bracket(&mut equation, start) {
+            Ok((a, b)) => brent(&mut equation, a, b)?,
+            Err(_) => newton(&mut equation, start)?,
+        },
+    };
+
+    // Bracketing methods also close in on poles, where the sign flips too
+    let residual = equation.eval(solution.root);
+    if !residual.is_finite() || residual.abs() > 1e-8 * equation.scale(solution.root) {
+        return Err(format!(
+            "No root: the sign change at x = {} is a discontinuity",
+            full_precision(solution.root)
+        ));
+    }
+    Ok(solution)
+}
+
+/// f(x) − g(x) with x bound in a cloned context.
+struct Equation {
+    lhs: Expr,
+    rhs: Expr,
+    ctx: Context,
+}
+
+impl Equation {
+    fn eval(&mut self, x: f64) -> f64 {
+        self.ctx.set_variable("x", x);
+        match (self.lhs.eval(&self.ctx), self.rhs.eval(&self.ctx)) {
+            (Ok(lhs), Ok(rhs)) => lhs - rhs,
+            _ => f64::NAN,
+        }
+    }
+
+    /// Magnitude of the two sides at x, to judge a residual against.
+    fn scale(&mut self, x: f64) -> f64 {
+        self.ctx.set_variable("x", x);
+        let lhs = self.lhs.eval(&self.ctx).unwrap_or(0.0);
+        let rhs = self.rhs.eval(&self.ctx).unwrap_or(0.0);
+        lhs.abs().max(rhs.abs()).max(1.0)
+    }
+}
+
+fn initial_guess(equation: &mut Equation, start: Start) -> f64 {
+    match start {
+        Start::Guess(x) => x,
+        Start::Bracket(a, b) => (a + b) / 2.0,
+        Start::Auto => {
+            if equation.eval(0.0).is_finite() {
+                0.0
+            } else {
+                1.0
+            }
+        }
+    }
+}
+
+/// A sign-changing interval: the given bracket, or one found by stepping
+/// outward from the initial guess with growing steps.
+fn bracket(equation: &mut Equation, start: Start) -> Result<(f64, f64), String> {
+    if let Start::Bracket(a, b) = start {
+        let (fa, fb) = (equation.eval(a), equation.eval(b));
+        if !(fa.is_finite() && fb.is_finite()) {
+            return Err("The equation is undefined at an end of the bracket".to_string());
+        }
+        if fa.signum() == fb.signum() && fa != 0.0 && fb != 0.0 {
+            return Err("No sign change between the bracket ends".to_string());
+        }
+        return Ok((a.min(b), a.max(b)));
+    }
+    let x0 = initial_guess(equation, start);
+    let f0 = equation.eval(x0);
+    if f0 == 0.0 {
+        return Ok((x0, x0));
+    }
+    let mut step = x0.abs().max(1.0) * 0.1;
+    let (mut left, mut right) = ((x0, f0), (x0, f0));
+    for _ in 0..60 {
+        for (side, direction) in [(&mut left, -1.0), (&mut right, 1.0)] {
+            let x = x0 + direction * step;
+            let fx = equation.eval(x);
+            if fx.is_finite() {
+                if side.1.is_finite() && fx.signum() != side.1.signum() {
+                    return Ok((side.0.min(x), side.0.max(x)));
+                }
+                *side = (x, fx);
+            }
+        }
+        step *= 1.6;
+    }
+    Err("Could not bracket a root; enter a bracket or a guess".to_string())
+}
+
+fn bisection(equation: &mut Equation, mut a: f64, mut b: f64) -> Result<Solution, String> {
+    let mut fa = equation.eval(a);
+    if fa == 0.0 || equation.eval(b) == 0.0 {
+        let root = if fa == 0.0 { a } else { b };
+        return Ok(Solution { root, method: Method::Bisection, iterations: 0 });
+    }
+    for iterations in 1..=MAX_BISECTIONS {
+        let mid = a + (b - a) / 2.0;
+        let fm = equation.eval(mid);
+        if fm == 0.0 || mid <= a || mid >= b {
+            return Ok(Solution { root: mid, method: Method::Bisection, iterations });
+        }
+        if fm.signum() == fa.signum() {
+            a = mid;
+            fa = fm;
+        } else {
+            b = mid;
+        }
+    }
+    Err(format!("Bisection did not converge in {} iterations", MAX_BISECTIONS))
+}
+
+/// Brent's method: inverse quadratic interpolation and secant steps,
+/// falling back to bisection whenever they would leave the bracket or
+/// converge too slowly.
+fn brent(equation: &mut Equation, a: f64, b: f64) -> Result<Solution, String> {
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (equation.eval(a), equation.eval(b));
+    if fa == 0.0 {
+        return Ok(Solution { root: a, method: Method::Brent, iterations: 0 });
+    }
+    let (mut c, mut fc) = (b, fb);
+    let (mut d, mut e) = (b - a, b - a);
+    for iterations in 1..=MAX_ITERATIONS {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+        let tolerance = 2.0 * f64::EPSILON * b.abs() + f64::MIN_POSITIVE;
+        let half = (c - b) / 2.0;
+        if half.abs() <= tolerance || fb == 0.0 {
+            return Ok(Solution { root: b, method: Method::Brent, iterations });
+        }
+        if e.abs() >= tolerance && fa.abs() > fb.abs() {
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * half * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * half * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            if 2.0 * p < (3.0 * half * q - (tolerance * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = half;
+                e = d;
+            }
+        } else {
+            d = half;
+            e = d;
+        }
+        a = b;
+        fa = fb;
+        b += if d.abs() > tolerance { d } else { tolerance.copysign(half) };
+        fb = equation.eval(b);
+        if !fb.is_finite() {
+            return Err(format!("Brent's method reached x = {}, where the equation is undefined", full_precision(b)));
+        }
+    }
+    Err(format!("Brent's method did not converge in {} iterations", MAX_ITERATIONS))
+}
+
+/// Newton's method with a central-difference derivative.
+fn newton(equation: &mut Equation, start: Start) -> Result<Solution, String> {
+    let mut x = initial_guess(equation, start);
+    for iterations in 1..=MAX_ITERATIONS {
+        let fx = equation.eval(x);
+        if !fx.is_finite() {
+            return Err(format!("Newton's method reached x = {}, where the equation is undefined", full_precision(x)));
+        }
+        if fx == 0.0 {
+            return Ok(Solution { root: x, method: Method::Newton, iterations });
+        }
+        let h = f64::EPSILON.cbrt() * x.abs().max(1.0);
+        let slope = (equation.eval(x + h) - equation.eval(x - h)) / (2.0 * h);
+        if slope == 0.0 || !slope.is_finite() {
+            return Err(format!("Newton's method stalled: the slope is zero or undefined at x = {}", full_precision(x)));
+        }
+        let step = fx / slope;
+        x -= step;
+        if step.abs() <= 4.0 * f64::EPSILON * x.abs().max(f64::MIN_POSITIVE) {
+            return Ok(Solution { root: x, method: Method::Newton, iterations });
+        }
+    }
+    Err(format!("Newton's method did not converge in {} iterations", MAX_ITERATIONS))
+}
+
+/// Parses polynomial coefficients from the highest power down, separated
+/// like expression arguments. Each coefficient may be an expression.
+pub fn parse_coefficients(text: &str, locale: &NumberLocale, ctx: &Context) -> Result<Vec<f64>, String> {
+    let separator = if locale.decimal_separator == ',' { ';' } else { ',' };
+    text.split(separator)
+        .filter(|field| !field.trim().is_empty())
+        .map(|field| expression::evaluate(field, locale, ctx))
+        .collect()
+}
+
+/// All roots of the polynomial with the given coefficients (highest power
+/// first), by the Aberth–Ehrlich method. Roots whose imaginary part is
+/// negligible are returned as real.
+pub fn polynomial_roots(coefficients: &[f64]) -> Result<Vec<Complex>, String> {
+    let first = coefficients.iter().position(|&c| c != 0.0);
+    let coefficients = &coefficients[first.unwrap_or(coefficients.len())..];
+    if coefficients.len() < 2 {
+        return Err("Enter a polynomial of degree 1 or more".to_string());
+    }
+    if coefficients.len() - 1 > MAX_POLYNOMIAL_DEGREE {
+        return Err(format!("Polynomial degree is limited to {}", MAX_POLYNOMIAL_DEGREE));
+    }
+    if coefficients.iter().any(|c| !c.is_finite()) {
+        return Err("Coefficients must be finite".to_string());
+    }
+
+    // Roots at zero come from trailing zero coefficients
+    let zeros = coefficients.iter().rev().take_while(|&&c| c == 0.0).count();
+    let monic: Vec<f64> = coefficients[..coefficients.len() - zeros]
+        .iter()
+        .map(|c| c / coefficients[0])
+        .collect();
+    let degree = monic.len() - 1;
+    let mut roots = vec![Complex::ZERO; zeros];
+    if degree == 0 {
+        return Ok(roots);
+    }
+
+    // Start on a circle enclosing every root (Cauchy's bound), with an
+    // offset angle so no start is symmetric to another
+    let radius = 1.0 + monic[1..].iter().fold(0.0f64, |acc, c| acc.max(c.abs()));
+    let mut z: Vec<Complex> = (0..degree)
+        .map(|k| Complex::from_polar(radius, 0.4 + 2.0 * std::f64::consts::PI * k as f64 / degree as f64))
+        .collect();
+    let mut converged = false;
+    for _ in 0..500 {
+        let mut largest_step = 0.0f64;
+        for i in 0..degree {
+            let (value, derivative) = horner(&monic, z[i]);
+            if value == Complex::ZERO {
+                continue;
+            }
+            let ratio = value / derivative;
+            let repulsion = (0..degree)
+                .filter(|&j| j != i)
+                .fold(Complex::ZERO, |acc, j| acc + Complex::ONE / (z[i] - z[j]));
+            let step = ratio / (Complex::ONE - ratio * repulsion);
+            if step.is_finite() {
+                z[i] = z[i] - step;
+                largest_step = largest_step.max(step.abs() / z[i].abs().max(1.0));
+            }
+        }
+        if largest_step <= 1e-15 {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        return Err("Polynomial root finding did not converge".to_string());
+    }
+
+    // Multiple roots are only found to about ε^(1/k), so a repeated real
+    // root keeps a small imaginary residue
+    for root in z {
+        let imaginary = if root.im.abs() <= f64::EPSILON.cbrt() * root.abs() { 0.0 } else { root.im };
+        roots.push(Complex::new(root.re, imaginary));
+    }
+    roots.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+    Ok(roots)
+}
+
+/// p(z) and p'(z) by Horner's scheme.
+fn horner(coefficients: &[f64], z: Complex) -> (Complex, Complex) {
+    let mut value = Complex::ZERO;
+    let mut derivative = Complex::ZERO;
+    for &c in coefficients {
+        derivative = derivative * z + value;
+        value = value * z + Complex::from(c);
+    }
+    (value, derivative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::NumericOptions;
+
+    fn run(equation: &str, method: Method, start: Start) -> Result<Solution, String> {
+        solve(equation, &NumberLocale::default(), &Context::new(NumericOptions::default()), method, start)
+    }
+
+    #[test]
+    fn every_method_finds_a_simple_root() {
+        for method in Method::ALL {
+            for start in [Start::Auto, Start::Guess(1.0), Start::Bracket(0.0, 3.0)] {
+                // Newton cannot start from 0, where the slope of x² is flat
+                if method == Method::Newton && start == Start::Auto {
+                    continue;
+                }
+                // The automatic search may find either root
+                let solution = run("x^2 = 2", method, start).unwrap();
+                assert!(
+                    (solution.root.abs() - std::f64::consts::SQRT_2).abs() <= 1e-14,
+                    "{:?} from {:?}: {}",
+                    method,
+                    start,
+                    solution.root
+                );
+                assert_ne!(solution.method, Method::Auto);
+            }
+        }
+        // Auto prefers Brent when it can bracket the root
+        assert_eq!(run("x^3 - x - 2", Method::Auto, Start::Auto).unwrap().method, Method::Brent);
+    }
+
+    #[test]
+    fn roots_at_an_end_of_the_bracket_are_returned_exactly() {
+        for method in [Method::Auto, Method::Bisection, Method::Brent] {
+            for bracket in [Start::Bracket(2.0, 5.0), Start::Bracket(-1.0, 2.0), Start::Bracket(5.0, 2.0)] {
+                let solution = run("x - 2", method, bracket).unwrap();
+                assert_eq!(solution.root, 2.0, "{:?} over {:?}", method, bracket);
+            }
+        }
+    }
+
+    #[test]
+    fn bisection_reaches_a_root_at_zero() {
+        for method in [Method::Bisection, Method::Brent] {
+            let solution = run("x^3 + x", method, Start::Bracket(-1.0, 2.0)).unwrap();
+            assert!(solution.root.abs() < 1e-300, "{:?}: {}", method, solution.root);
+        }
+    }
+
+    #[test]
+    fn brackets_without_a_root_are_rejected() {
+        for method in [Method::Bisection, Method::Brent] {
+            assert_eq!(
+                run("x^2 + 1", method, Start::Bracket(-2.0, 2.0)).unwrap_err(),
+                "No sign change between the bracket ends"
+            );
+            assert_eq!(
+                run("x^2 + 1", method, Start::Auto).unwrap_err(),
+                "Could not bracket a root; enter a bracket or a guess"
+            );
+            assert_eq!(
+                run("ln(x)", method, Start::Bracket(-1.0, 2.0)).unwrap_err(),
+                "The equation is undefined at an end of the bracket"
+            );
+        }
+        // The sign change of 1/x is a pole, not a root
+        let pole = run("1/x", Method::Bisection, Start::Bracket(-1.0, 2.0)).unwrap_err();
+        assert!(pole.starts_with("No root"), "{}", pole);
+        assert!(run("1/x", Method::Brent, Start::Bracket(-1.0, 2.0)).is_err());
+        assert!(run("x^2 + 1", Method::Newton, Start::Guess(0.0)).is_err());
+    }
+
+    #[test]
+    fn polynomial_roots_include_complex_and_zero_roots() {
+        // x³ − x² + x − 1 = (x − 1)(x² + 1), times x
+        let roots = polynomial_roots(&[1.0, -1.0, 1.0, -1.0, 0.0]).unwrap();
+        let expected = [Complex::new(0.0, 0.0), Complex::new(0.0, -1.0), Complex::new(0.0, 1.0), Complex::new(1.0, 0.0)];
+        assert_eq!(roots.len(), expected.len());
+        for expected in expected {
+            assert!(roots.iter().any(|&root| (root - expected).abs() <= 1e-12), "{:?} not in {:?}", expected, roots);
+        }
+        assert!(roots.contains(&Complex::ZERO));
+        assert!(polynomial_roots(&[0.0, 3.0]).is_err());
+    }
+}
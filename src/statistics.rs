@@ -0,0 +1,267 @@
+/// One entry of the data set; `y` is present for paired data.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DataPoint {
+    pub x: f64,
+    pub y: Option<f64>,
+}
+
+/// Summary statistics available on the statistics keypad.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Count,
+    Sum,
+    SumOfSquares,
+    Mean,
+    Median,
+    Mode,
+    SampleVariance,
+    PopulationVariance,
+    SampleStdDev,
+    PopulationStdDev,
+    Min,
+    Max,
+    Q1,
+    Q3,
+    /// Percentile at the given rank in [0, 100].
+    Percentile(f64),
+}
+
+impl Statistic {
+    pub fn label(self) -> String {
+        match self {
+            Statistic::Count => "n".to_string(),
+            Statistic::Sum => "Σx".to_string(),
+            Statistic::SumOfSquares => "Σx²".to_string(),
+            Statistic::Mean => "x̄".to_string(),
+            Statistic::Median => "median".to_string(),
+            Statistic::Mode => "mode".to_string(),
+            Statistic::SampleVariance => "s²".to_string(),
+            Statistic::PopulationVariance => "σ²".to_string(),
+            Statistic::SampleStdDev => "s".to_string(),
+            Statistic::PopulationStdDev => "σ".to_string(),
+            Statistic::Min => "min".to_string(),
+            Statistic::Max => "max".to_string(),
+            Statistic::Q1 => "Q1".to_string(),
+            Statistic::Q3 => "Q3".to_string(),
+            Statistic::Percentile(p) => format!("P{}", p),
+        }
+    }
+}
+
+pub fn compute(stat: Statistic, data: &[f64]) -> Result<f64, String> {
+    if data.is_empty() && stat != Statistic::Count && stat != Statistic::Sum {
+        return Err("Data set is empty".to_string());
+    }
+    match stat {
+        Statistic::Count => Ok(data.len() as f64),
+        Statistic::Sum => Ok(data.iter().sum()),
+        Statistic::SumOfSquares => Ok(data.iter().map(|x| x * x).sum()),
+        Statistic::Mean => Ok(mean(data)),
+        Statistic::Median => percentile(data, 50.0),
+        Statistic::Mode => mode(data),
+        Statistic::SampleVariance => variance(data, 1),
+        Statistic::PopulationVariance => variance(data, 0),
+        Statistic::SampleStdDev => variance(data, 1).map(f64::sqrt),
+        Statistic::PopulationStdDev => variance(data, 0).map(f64::sqrt),
+        Statistic::Min => Ok(data.iter().copied().fold(f64::INFINITY, f64::min)),
+        Statistic::Max => Ok(data.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        Statistic::Q1 => percentile(data, 25.0),
+        Statistic::Q3 => percentile(data, 75.0),
+        Statistic::Percentile(p) => percentile(data, p),
+    }
+}
+
+pub fn mean(data: &[f64]) -> f64 {
+    data.iter().sum::<f64>() / data.len() as f64
+}
+
+/// Variance with `ddof` delta degrees of freedom: 1 for the sample
+/// variance, 0 for the population variance. Uses the two-pass algorithm
+/// to avoid cancellation.
+fn variance(data: &[f64], ddof: usize) -> Result<f64, String> {
+    if data.len() <= ddof {
+        return Err(match ddof {
+            0 => "Population variance needs at least one value".to_string(),
+            _ => "Sample variance needs at least two values".to_string(),
+        });
+    }
+    let m = mean(data);
+    let squares: f64 = data.iter().map(|x| (x - m) * (x - m)).sum();
+    Ok(squares / (data.len() - ddof) as f64)
+}
+
+/// Percentile by linear interpolation between closest ranks (the method
+/// spreadsheets use for PERCENTILE.INC).
+fn percentile(data: &[f64], p: f64) -> Result<f64, String> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err("Percentile must be between 0 and 100".to_string());
+    }
+    let sorted = sorted(data);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    Ok(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// The most frequent value; ties resolve to the smallest value.
+fn mode(data: &[f64]) -> Result<f64, String> {
+    let sorted = sorted(data);
+    let mut best = (sorted[0], 0);
+    let mut run = (sorted[0], 0);
+    for &x in &sorted {
+        if x == run.0 {
+            run.1 += 1;
+        } else {
+            run = (x, 1);
+        }
+        if run.1 > best.1 {
+            best = run;
+        }
+    }
+    if best.1 == 1 && sorted.len() > 1 {
+        Err("No mode: every value occurs once".to_string())
+    } else {
+        Ok(best.0)
+    }
+}
+
+fn sorted(data: &[f64]) -> Vec<f64> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+/// Extracts data points from pasted or imported text such as CSV. When
+/// every line holds exactly two numbers the lines become (x, y) pairs;
+/// otherwise every number becomes a single value.
+pub fn parse_points(text: &str, locale: &crate::locale::NumberLocale) -> Vec<DataPoint> {
+    let rows: Vec<Vec<f64>> = text
+        .lines()
+        .map(|line| parse_data(line, locale))
+        .filter(|row| !row.is_empty())
+        .collect();
+    if rows.len() > 1 && rows.iter().all(|row| row.len() == 2) {
+        rows.iter()
+            .map(|row| DataPoint { x: row[0], y: Some(row[1]) })
+            .collect()
+    } else {
+        rows.concat()
+            .into_iter()
+            .map(|x| DataPoint { x, y: None })
+            .collect()
+    }
+}
+
+/// Extracts every number from a line of text. Fields are split on
+/// whitespace and semicolons, and on commas unless the locale uses a
+/// decimal comma; fields that are not numbers (headers, labels) are skipped.
+fn parse_data(text: &str, locale: &crate::locale::NumberLocale) -> Vec<f64> {
+    let comma_separates = locale.decimal_separator != ',';
+    text.split(|c: char| c.is_whitespace() || c == ';' || (comma_separates && c == ','))
+        .filter_map(|field| {
+            let field = field.trim().trim_matches('"');
+            if field.is_empty() {
+                None
+            } else {
+                locale.parse_lenient(field)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::NumberLocale;
+
+    const ONE_TO_TEN: [f64; 10] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+
+    fn assert_close(actual: Result<f64, String>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() <= 1e-12, "expected {} but got {}", expected, actual);
+    }
+
+    #[test]
+    fn quartiles_and_percentiles_match_percentile_inc() {
+        assert_close(compute(Statistic::Q1, &ONE_TO_TEN), 3.25);
+        assert_close(compute(Statistic::Median, &ONE_TO_TEN), 5.5);
+        assert_close(compute(Statistic::Q3, &ONE_TO_TEN), 7.75);
+        assert_close(compute(Statistic::Percentile(90.0), &ONE_TO_TEN), 9.1);
+        assert_close(compute(Statistic::Percentile(0.0), &ONE_TO_TEN), 1.0);
+        assert_close(compute(Statistic::Percentile(100.0), &ONE_TO_TEN), 10.0);
+        // Order of entry does not matter
+        assert_close(compute(Statistic::Median, &[9.0, 1.0, 5.0]), 5.0);
+        assert_close(compute(Statistic::Q1, &[7.0]), 7.0);
+        assert_eq!(
+            compute(Statistic::Percentile(101.0), &ONE_TO_TEN),
+            Err("Percentile must be between 0 and 100".to_string())
+        );
+    }
+
+    #[test]
+    fn sample_and_population_variance() {
+        let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_close(compute(Statistic::Mean, &data), 5.0);
+        assert_close(compute(Statistic::PopulationVariance, &data), 4.0);
+        assert_close(compute(Statistic::PopulationStdDev, &data), 2.0);
+        assert_close(compute(Statistic::SampleVariance, &data), 32.0 / 7.0);
+        assert_close(compute(Statistic::SampleStdDev, &data), (32.0f64 / 7.0).sqrt());
+        // Two-pass variance keeps its accuracy around a large mean
+        let offset: Vec<f64> = data.iter().map(|x| x + 1e9).collect();
+        assert_close(compute(Statistic::PopulationVariance, &offset), 4.0);
+
+        assert_close(compute(Statistic::PopulationVariance, &[3.0]), 0.0);
+        assert_eq!(
+            compute(Statistic::SampleVariance, &[3.0]),
+            Err("Sample variance needs at least two values".to_string())
+        );
+        assert_eq!(variance(&[], 0), Err("Population variance needs at least one value".to_string()));
+    }
+
+    #[test]
+    fn mode_ties_resolve_to_the_smallest_value() {
+        assert_close(compute(Statistic::Mode, &[3.0, 1.0, 3.0, 1.0, 2.0]), 1.0);
+        assert_close(compute(Statistic::Mode, &[4.0, 2.0, 4.0]), 4.0);
+        assert_close(compute(Statistic::Mode, &[5.0]), 5.0);
+        assert_eq!(
+            compute(Statistic::Mode, &[1.0, 2.0, 3.0]),
+            Err("No mode: every value occurs once".to_string())
+        );
+    }
+
+    #[test]
+    fn sums_and_extremes() {
+        assert_close(compute(Statistic::Count, &ONE_TO_TEN), 10.0);
+        assert_close(compute(Statistic::Sum, &ONE_TO_TEN), 55.0);
+        assert_close(compute(Statistic::SumOfSquares, &ONE_TO_TEN), 385.0);
+        assert_close(compute(Statistic::Min, &[3.0, -1.0, 2.0]), -1.0);
+        assert_close(compute(Statistic::Max, &[3.0, -1.0, 2.0]), 3.0);
+        assert_close(compute(Statistic::Count, &[]), 0.0);
+        assert_close(compute(Statistic::Sum, &[]), 0.0);
+        assert_eq!(compute(Statistic::Mean, &[]), Err("Data set is empty".to_string()));
+    }
+
+    #[test]
+    fn pasted_text_becomes_values_or_pairs() {
+        let locale = NumberLocale::default();
+        let pairs = |points: Vec<DataPoint>| points.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>();
+        assert_eq!(
+            pairs(parse_points("1, 2, 3", &locale)),
+            vec![(1.0, None), (2.0, None), (3.0, None)]
+        );
+        // A header row is skipped, and two numbers per line are pairs
+        assert_eq!(
+            pairs(parse_points("x,y\n1,2\n3,4\n", &locale)),
+            vec![(1.0, Some(2.0)), (3.0, Some(4.0))]
+        );
+        // A ragged line makes every number a single value
+        assert_eq!(pairs(parse_points("1;2\n3;4\n5", &locale)).len(), 5);
+        assert_eq!(
+            pairs(parse_points("\"1.5\"\t-2e3", &locale)),
+            vec![(1.5, None), (-2000.0, None)]
+        );
+        let comma = NumberLocale { decimal_separator: ',', ..locale };
+        assert_eq!(pairs(parse_points("1,5 2,5", &comma)), vec![(1.5, None), (2.5, None)]);
+        assert!(parse_points("no numbers here", &locale).is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+use crate::calculator::Operation;
+
+/// One line of the adding-machine tape. Calculations are kept as their
+/// steps rather than as text so that an edited number recomputes every
+/// line after it.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TapeLine {
+    /// The first number of a calculation.
+    Start(f64),
+    /// A calculation continuing from the previous result.
+    Carry,
+    /// A number applied to the running value; `percent` marks the desk
+    /// calculator % key in place of `=`.
+    Step { op: Operation, value: f64, percent: bool },
+    /// `=`: the result is an item counted into the grand total.
+    Total,
+    /// The sum of the items since the previous subtotal.
+    Subtotal,
+}
+
+impl TapeLine {
+    /// The editable number of a start or step line.
+    pub fn value_mut(&mut self) -> Option<&mut f64> {
+        match self {
+            TapeLine::Start(value) | TapeLine::Step { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// The tape of the desk-calculator mode, kept with the app state.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Tape {
+    /// Whether `=` records onto the tape.
+    pub recording: bool,
+    pub lines: Vec<TapeLine>,
+}
+
+/// Values of a tape after recomputation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TapeTotals {
+    /// The running value after each line: the result on `=` lines and the
+    /// subtotal on subtotal lines.
+    pub values: Vec<Result<f64, String>>,
+    /// Item number of each `=` line.
+    pub items: Vec<Option<usize>>,
+    pub grand_total: f64,
+    pub item_count: usize,
+}
+
+impl TapeTotals {
+    /// The result of the last calculation, if the tape ends with one.
+    pub fn last_total(&self, tape: &Tape) -> Option<f64> {
+        match tape.lines.last() {
+            Some(TapeLine::Total) => self.values.last()?.as_ref().ok().copied(),
+            _ => None,
+        }
+    }
+}
+
+impl Tape {
+    /// Replays the tape; `step` applies an operation (or a % step) to the
+    /// running value. A failed calculation is left out of the totals.
+    pub fn recompute(&self, step: impl Fn(Operation, f64, f64, bool) -> Result<f64, String>) -> TapeTotals {
+        let mut values = Vec::with_capacity(self.lines.len());
+        let mut items = Vec::with_capacity(self.lines.len());
+        let mut running: Result<f64, String> = Ok(0.0);
+        let mut previous = 0.0;
+        let mut subtotal = 0.0;
+        let mut grand_total = 0.0;
+        let mut item_count = 0;
+        for line in &self.lines {
+            let mut item = None;
+            let value = match *line {
+                TapeLine::Start(value) => {
+                    running = Ok(value);
+                    running.clone()
+                }
+                TapeLine::Carry => {
+                    running = Ok(previous);
+                    running.clone()
+                }
+                TapeLine::Step { op, value, percent } => {
+                    running = running.and_then(|x| step(op, x, value, percent));
+                    running.clone()
+                }
+                TapeLine::Total => {
+                    let result = running.clone();
+                    if let Ok(result) = result {
+                        item_count += 1;
+                        item = Some(item_count);
+                        previous = result;
+                        subtotal += result;
+                        grand_total += result;
+                    }
+                    running = Ok(0.0);
+                    result
+                }
+                TapeLine::Subtotal => Ok(std::mem::take(&mut subtotal)),
+            };
+            values.push(value);
+            items.push(item);
+        }
+        TapeTotals {
+            values,
+            items,
+            grand_total,
+            item_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plain arithmetic; `percent` takes the value as a percentage.
+    fn step(op: Operation, running: f64, value: f64, percent: bool) -> Result<f64, String> {
+        let value = if percent { running * value / 100.0 } else { value };
+        match op {
+            Operation::Add => Ok(running + value),
+            Operation::Subtract => Ok(running - value),
+            Operation::Multiply => Ok(running * value),
+            Operation::Divide if value == 0.0 => Err("Cannot divide by zero".to_string()),
+            Operation::Divide => Ok(running / value),
+            _ => Err("Unsupported".to_string()),
+        }
+    }
+
+    fn add(value: f64) -> TapeLine {
+        TapeLine::Step { op: Operation::Add, value, percent: false }
+    }
+
+    fn tape(lines: Vec<TapeLine>) -> Tape {
+        Tape { recording: true, lines }
+    }
+
+    #[test]
+    fn editing_a_number_recomputes_later_results() {
+        let mut tape = tape(vec![
+            TapeLine::Start(10.0),
+            add(5.0),
+            TapeLine::Total,
+            TapeLine::Start(2.0),
+            TapeLine::Step { op: Operation::Multiply, value: 4.0, percent: false },
+            TapeLine::Total,
+        ]);
+        let totals = tape.recompute(step);
+        assert_eq!(totals.values[2], Ok(15.0));
+        assert_eq!(totals.grand_total, 23.0);
+        assert_eq!(totals.items, vec![None, None, Some(1), None, None, Some(2)]);
+
+        *tape.lines[0].value_mut().unwrap() = 20.0;
+        *tape.lines[4].value_mut().unwrap() = 10.0;
+        let totals = tape.recompute(step);
+        assert_eq!(totals.values[1], Ok(25.0));
+        assert_eq!(totals.values[5], Ok(20.0));
+        assert_eq!(totals.grand_total, 45.0);
+        assert_eq!(tape.lines[2].value_mut(), None);
+    }
+
+    #[test]
+    fn carried_calculations_follow_an_edited_total() {
+        let mut tape = tape(vec![
+            TapeLine::Start(100.0),
+            add(20.0),
+            TapeLine::Total,
+            TapeLine::Carry,
+            TapeLine::Step { op: Operation::Subtract, value: 10.0, percent: true },
+            TapeLine::Total,
+        ]);
+        let totals = tape.recompute(step);
+        assert_eq!(totals.values[3], Ok(120.0));
+        assert_eq!(totals.values[5], Ok(108.0));
+        assert_eq!(totals.last_total(&tape), Some(108.0));
+
+        *tape.lines[1].value_mut().unwrap() = 100.0;
+        let totals = tape.recompute(step);
+        assert_eq!(totals.values[3], Ok(200.0));
+        assert_eq!(totals.values[5], Ok(180.0));
+        assert_eq!(totals.grand_total, 380.0);
+    }
+
+    #[test]
+    fn subtotals_restart_after_each_subtotal_line() {
+        let tape = tape(vec![
+            TapeLine::Start(1.0),
+            TapeLine::Total,
+            TapeLine::Start(2.0),
+            TapeLine::Total,
+            TapeLine::Subtotal,
+            TapeLine::Start(4.0),
+            TapeLine::Total,
+            TapeLine::Subtotal,
+            TapeLine::Subtotal,
+        ]);
+        let totals = tape.recompute(step);
+        assert_eq!(totals.values[4], Ok(3.0));
+        assert_eq!(totals.values[7], Ok(4.0));
+        assert_eq!(totals.values[8], Ok(0.0));
+        assert_eq!(totals.grand_total, 7.0);
+        assert_eq!(totals.item_count, 3);
+        // The tape does not end with a calculation
+        assert_eq!(totals.last_total(&tape), None);
+    }
+
+    #[test]
+    fn failed_calculations_are_left_out_of_the_totals() {
+        let mut tape = tape(vec![
+            TapeLine::Start(5.0),
+            TapeLine::Step { op: Operation::Divide, value: 0.0, percent: false },
+            add(1.0),
+            TapeLine::Total,
+            TapeLine::Start(7.0),
+            TapeLine::Total,
+            TapeLine::Subtotal,
+        ]);
+        let totals = tape.recompute(step);
+        let error = Err("Cannot divide by zero".to_string());
+        assert_eq!(totals.values[2], error);
+        assert_eq!(totals.values[3], error);
+        assert_eq!(totals.items[3], None);
+        assert_eq!(totals.items[5], Some(1));
+        assert_eq!(totals.values[6], Ok(7.0));
+        assert_eq!(totals.item_count, 1);
+        assert_eq!(totals.grand_total, 7.0);
+
+        // Fixing the divisor brings the item back
+        *tape.lines[1].value_mut().unwrap() = 5.0;
+        let totals = tape.recompute(step);
+        assert_eq!(totals.values[3], Ok(2.0));
+        assert_eq!(totals.item_count, 2);
+        assert_eq!(totals.grand_total, 9.0);
+    }
+}
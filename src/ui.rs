@@ -1,6 +1,26 @@
 use crate::app::CalcsPlus;
-use crate::calculator::{CalculatorMode, Operation};
-use eframe::egui::{self, Color32, RichText, Stroke, Vec2};
+use crate::business::RateKey;
+use crate::calculus::Calculus;
+use crate::calculator::{AngleUnit, CalculatorMode, DisplayFormat, NumericPolicy, Operation};
+use crate::complex::ComplexForm;
+use crate::dates::{self, Date, DurationUnit};
+use crate::distribution::{Distribution, Family, Function};
+use crate::expression::Expr;
+use crate::finance::{CashFlowMeasure, DepreciationMethod, TvmVariable};
+use crate::graph::{self, Curve, CurveKind, FeatureKind};
+use crate::integer::{IntegerFunction, ModConvention};
+use crate::regression::{Fit, RegressionModel, MAX_POLYNOMIAL_DEGREE};
+use crate::solver::{self, Method, Start};
+use crate::statistics::Statistic;
+use crate::tape::TapeLine;
+use crate::locale::{Grouping, NumberLocale};
+use crate::matrix::{MatrixOp, MatrixOutcome, MAX_DIMENSION};
+use crate::uncertainty::PrecisionMode;
+use crate::units::{Category, Conversion};
+use eframe::egui::{self, Color32, RichText, Sense, Stroke, Vec2};
+use egui_plot::{
+    CoordinatesFormatter, Corner, Legend, Line, MarkerShape, Plot, PlotBounds, PlotPoint, PlotPoints, Points, Text,
+};
 
 const BUTTON_SIZE: Vec2 = Vec2::new(65.0, 45.0);
 const LARGE_BUTTON_SIZE: Vec2 = Vec2::new(135.0, 45.0);
@@ -8,6 +28,8 @@ const DISPLAY_HEIGHT: f32 = 70.0;
 const SPACING: f32 = 4.0;
 
 pub fn draw_calculator(ctx: &egui::Context, app: &mut CalcsPlus) {
+    handle_keyboard(ctx, app);
+    
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.vertical_centered(|ui| {
             ui.add_space(8.0);
@@ -17,21 +39,93 @@ pub fn draw_calculator(ctx: &egui::Context, app: &mut CalcsPlus) {
             
             ui.add_space(8.0);
             
+            // The graph tab replaces the display and keypad
+            if app.is_showing_graph() {
+                draw_graph_view(ui, app);
+                return;
+            }
+            
             // Display area
             draw_display(ui, app);
             
             ui.add_space(8.0);
             
+            // Show settings panel if enabled
+            if app.is_showing_settings() {
+                draw_settings_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
             // Show history panel if enabled
             if app.is_showing_history() {
                 draw_history_panel(ui, app);
                 ui.add_space(8.0);
             }
             
+            // Tape of recorded calculations
+            if app.get_tape().recording {
+                draw_tape_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // Probability distributions panel
+            if app.get_mode() == CalculatorMode::Scientific && app.is_showing_distributions() {
+                draw_distribution_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // Derivative, integral, Σ, Π and limit panel
+            if app.get_mode() == CalculatorMode::Scientific && app.is_showing_calculus() {
+                draw_calculus_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // Equation and polynomial solver panel
+            if app.get_mode() == CalculatorMode::Scientific && app.is_showing_solver() {
+                draw_solver_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // Number theory panel
+            if app.get_mode() == CalculatorMode::Scientific && app.is_showing_integer() {
+                draw_integer_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // Statistics data panel
+            if app.get_mode() == CalculatorMode::Statistics {
+                draw_data_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // Matrix editor, operations and results
+            if app.get_mode() == CalculatorMode::Matrix {
+                draw_matrix_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // TVM, cash flow, depreciation and interest worksheets
+            if app.get_mode() == CalculatorMode::Financial {
+                draw_finance_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
+            // Unit conversion and currency panels
+            if app.get_mode() == CalculatorMode::Units {
+                draw_conversion_panel(ui, app);
+                ui.add_space(8.0);
+                draw_currency_panel(ui, app);
+                ui.add_space(8.0);
+            }
+            
             // Button layout based on mode
             match app.get_mode() {
-                CalculatorMode::Standard => draw_standard_buttons(ui, app),
+                CalculatorMode::Standard | CalculatorMode::Matrix | CalculatorMode::Units => draw_standard_buttons(ui, app),
                 CalculatorMode::Scientific => draw_scientific_buttons(ui, app),
+                CalculatorMode::Statistics => draw_statistics_buttons(ui, app),
+                CalculatorMode::Financial => draw_standard_buttons(ui, app),
+                // Dates have their own layout instead of a keypad
+                CalculatorMode::Dates => draw_date_panel(ui, app),
             }
             
             ui.add_space(8.0);
@@ -39,6 +133,52 @@ pub fn draw_calculator(ctx: &egui::Context, app: &mut CalcsPlus) {
     });
 }
 
+fn handle_keyboard(ctx: &egui::Context, app: &mut CalcsPlus) {
+    // Leave typing to focused widgets such as the settings fields
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    
+    let events = ctx.input(|i| i.events.clone());
+    let locale = app.get_locale();
+    for event in events {
+        match event {
+            egui::Event::Copy => ctx.copy_text(app.get_display()),
+            egui::Event::Paste(text) => app.paste(&text),
+            egui::Event::Text(text) => {
+                for ch in text.chars() {
+                    match ch {
+                        '0'..='9' => app.input_digit(ch),
+                        '+' => app.set_operation(Operation::Add),
+                        '-' => app.set_operation(Operation::Subtract),
+                        '*' => app.set_operation(Operation::Multiply),
+                        '/' => app.set_operation(Operation::Divide),
+                        '^' => app.set_operation(Operation::Power),
+                        '%' => app.percent(),
+                        '=' => app.calculate_result(),
+                        'e' | 'E' => app.input_exponent(),
+                        'i' => app.input_imaginary(),
+                        '°' | '\'' | '"' => app.input_dms(),
+                        '±' => app.input_uncertainty(),
+                        _ if locale.is_decimal_key(ch) => app.input_decimal(),
+                        _ => {}
+                    }
+                }
+            }
+            egui::Event::Key { key, pressed: true, .. } => match key {
+                // Enter adds data points in statistics mode
+                egui::Key::Enter if app.get_mode() == CalculatorMode::Statistics => app.data_add(),
+                egui::Key::Enter => app.calculate_result(),
+                egui::Key::Backspace => app.backspace(),
+                egui::Key::Delete => app.clear_entry(),
+                egui::Key::Escape => app.clear(),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
 fn draw_title_bar(ui: &mut egui::Ui, app: &mut CalcsPlus) {
     ui.horizontal(|ui| {
         ui.label(
@@ -48,107 +188,1810 @@ fn draw_title_bar(ui: &mut egui::Ui, app: &mut CalcsPlus) {
                 .strong()
         );
         
-        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-            // History toggle button
-            if ui.button(
-                RichText::new("📋")
-                    .size(18.0)
-            ).clicked() {
-                app.toggle_history();
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            // History toggle button
+            if ui.button(
+                RichText::new("📋")
+                    .size(18.0)
+            ).clicked() {
+                app.toggle_history();
+            }
+            
+            // Tape toggle: records calculations with a grand total
+            if ui.selectable_label(app.get_tape().recording, RichText::new("Tape").size(14.0))
+                .on_hover_text("Adding-machine tape with grand total")
+                .clicked()
+            {
+                app.toggle_tape();
+            }
+            
+            // Settings toggle button
+            if ui.button(
+                RichText::new("⚙")
+                    .size(18.0)
+            ).clicked() {
+                app.toggle_settings();
+            }
+            
+            // Distributions toggle, scientific mode only
+            if app.get_mode() == CalculatorMode::Scientific
+                && ui.button(RichText::new("P(x)").size(14.0))
+                    .on_hover_text("Probability distributions")
+                    .clicked()
+            {
+                app.toggle_distributions();
+            }
+            
+            // Calculus toggle, scientific mode only
+            if app.get_mode() == CalculatorMode::Scientific
+                && ui.button(RichText::new("∫dx").size(14.0))
+                    .on_hover_text("Derivatives, integrals, sums, products and limits")
+                    .clicked()
+            {
+                app.toggle_calculus();
+            }
+            
+            // Solver toggle, scientific mode only
+            if app.get_mode() == CalculatorMode::Scientific
+                && ui.button(RichText::new("x=?").size(14.0))
+                    .on_hover_text("Equation solver")
+                    .clicked()
+            {
+                app.toggle_solver();
+            }
+            
+            // Number theory toggle, scientific mode only
+            if app.get_mode() == CalculatorMode::Scientific
+                && ui.button(RichText::new("ℤ").size(14.0))
+                    .on_hover_text("Number theory on big integers")
+                    .clicked()
+            {
+                app.toggle_integer();
+            }
+            
+            // Graph tab, next to the mode selector
+            if ui.selectable_label(
+                app.is_showing_graph(),
+                RichText::new("📈 Graph").size(14.0)
+            ).clicked() {
+                app.toggle_graph();
+            }
+            
+            // Mode selector
+            ui.menu_button(
+                RichText::new(app.get_mode().label())
+                    .size(14.0)
+                    .color(Color32::from_rgb(100, 149, 237)),
+                |ui| {
+                    for mode in CalculatorMode::ALL {
+                        if ui.selectable_label(app.get_mode() == mode, mode.label()).clicked() {
+                            app.set_mode(mode);
+                            ui.close_menu();
+                        }
+                    }
+                }
+            );
+        });
+    });
+}
+
+fn draw_display(ui: &mut egui::Ui, app: &CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(248, 248, 255))
+        .stroke(Stroke::new(2.0, Color32::from_rgb(200, 200, 200)))
+        .inner_margin(egui::Margin::same(15.0))
+        .rounding(8.0);
+    
+    frame.show(ui, |ui| {
+        ui.set_min_height(DISPLAY_HEIGHT);
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+            // Error message or main display
+            if let Some(error) = app.get_error() {
+                ui.label(
+                    RichText::new(error)
+                        .size(20.0)
+                        .color(Color32::from_rgb(220, 20, 60))
+                        .monospace()
+                );
+            } else {
+                let mut response = ui.add(
+                    egui::Label::new(
+                        RichText::new(app.get_display())
+                            .size(32.0)
+                            .color(Color32::from_rgb(25, 25, 112))
+                            .monospace()
+                            .strong()
+                    )
+                    .sense(egui::Sense::click())
+                );
+                if let Some(full) = app.get_full_precision() {
+                    response = response.on_hover_text(full);
+                }
+                response.context_menu(|ui| draw_copy_menu(ui, app));
+            }
+        });
+        
+        // Memory, angle unit and complex mode indicators, IEEE condition notice
+        let show_angle = app.get_mode() == CalculatorMode::Scientific;
+        if app.has_memory() || app.get_notice().is_some() || show_angle {
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
+                if show_angle {
+                    ui.label(
+                        RichText::new(app.get_angle_unit().label())
+                            .size(12.0)
+                            .color(Color32::from_rgb(100, 149, 237))
+                    );
+                    if app.is_complex_mode() {
+                        ui.label(
+                            RichText::new("ℂ")
+                                .size(12.0)
+                                .color(Color32::from_rgb(100, 149, 237))
+                        );
+                    }
+                }
+                if app.has_memory() {
+                    ui.label(
+                        RichText::new("M")
+                            .size(14.0)
+                            .color(Color32::from_rgb(255, 140, 0))
+                            .strong()
+                    ).on_hover_text(app.get_memory_display());
+                }
+                if let Some(notice) = app.get_notice() {
+                    ui.label(
+                        RichText::new(notice)
+                            .size(12.0)
+                            .color(Color32::from_rgb(205, 133, 0))
+                    );
+                }
+            });
+        }
+    });
+}
+
+fn draw_copy_menu(ui: &mut egui::Ui, app: &CalcsPlus) {
+    if ui.button("Copy result").clicked() {
+        ui.ctx().copy_text(app.get_display());
+        ui.close_menu();
+    }
+    if let Some(full) = app.get_full_precision() {
+        if ui.button("Copy full precision").clicked() {
+            ui.ctx().copy_text(full);
+            ui.close_menu();
+        }
+    }
+    if ui.button("Copy expression").clicked() {
+        ui.ctx().copy_text(app.get_expression());
+        ui.close_menu();
+    }
+    ui.separator();
+    ui.label(
+        RichText::new("Ctrl+V pastes numbers or expressions")
+            .size(11.0)
+            .color(Color32::GRAY)
+    );
+}
+
+fn draw_settings_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.label(
+            RichText::new("Settings")
+                .size(14.0)
+                .strong()
+                .color(Color32::from_rgb(70, 130, 180))
+        );
+        ui.separator();
+        
+        ui.horizontal(|ui| {
+            ui.label("Special values:");
+            let mut policy = app.get_numeric_policy();
+            for option in [NumericPolicy::Strict, NumericPolicy::Ieee] {
+                ui.radio_value(&mut policy, option, option.label());
+            }
+            if policy != app.get_numeric_policy() {
+                app.set_numeric_policy(policy);
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Precision:");
+            let mut mode = app.get_precision_mode();
+            egui::ComboBox::from_id_source("precision_mode")
+                .selected_text(mode.label())
+                .show_ui(ui, |ui| {
+                    for option in PrecisionMode::ALL {
+                        ui.selectable_value(&mut mode, option, option.label());
+                    }
+                });
+            if mode != app.get_precision_mode() {
+                app.set_precision_mode(mode);
+            }
+        })
+        .response
+        .on_hover_text("Track significant figures of typed numbers, or enter measurements as 5.2 ± 0.1 with linear error propagation");
+        
+        ui.horizontal(|ui| {
+            ui.label("Number format:");
+            let current = app.get_display_format();
+            let mut format = current;
+            egui::ComboBox::from_id_source("display_format")
+                .selected_text(current.label())
+                .show_ui(ui, |ui| {
+                    for option in DisplayFormat::ALL {
+                        if ui.selectable_label(current.same_kind(option), option.label()).clicked()
+                            && !current.same_kind(option)
+                        {
+                            format = option;
+                        }
+                    }
+                });
+            if let Some(mut digits) = format.digits() {
+                let (range, suffix) = match format {
+                    DisplayFormat::Significant(_) => (1..=15, " digits"),
+                    DisplayFormat::Fraction(_) => (1..=9, " denominator digits"),
+                    DisplayFormat::Dms(_) => (0..=9, " decimals on seconds"),
+                    _ => (0..=15, " digits"),
+                };
+                ui.add(egui::DragValue::new(&mut digits).range(range).suffix(suffix));
+                format = format.with_digits(digits);
+            }
+            if format != current {
+                app.set_display_format(format);
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Angles:");
+            let mut unit = app.get_angle_unit();
+            for option in AngleUnit::ALL {
+                ui.radio_value(&mut unit, option, option.label());
+            }
+            if unit != app.get_angle_unit() {
+                app.set_angle_unit(unit);
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            let mut complex_mode = app.is_complex_mode();
+            if ui.checkbox(&mut complex_mode, "Complex numbers").changed() {
+                app.set_complex_mode(complex_mode);
+            }
+            let mut form = app.get_complex_form();
+            for option in [ComplexForm::Rectangular, ComplexForm::Polar] {
+                ui.radio_value(&mut form, option, option.label());
+            }
+            if form != app.get_complex_form() {
+                app.set_complex_form(form);
+            }
+        });
+        
+        let current = app.get_locale();
+        let mut locale = current;
+        ui.horizontal(|ui| {
+            ui.label("Grouping:");
+            egui::ComboBox::from_id_source("grouping")
+                .selected_text(locale.grouping.label())
+                .show_ui(ui, |ui| {
+                    for option in Grouping::ALL {
+                        ui.selectable_value(&mut locale.grouping, option, option.label());
+                    }
+                });
+            egui::ComboBox::from_id_source("group_separator")
+                .selected_text(separator_label(&NumberLocale::GROUP_SEPARATORS, locale.group_separator))
+                .show_ui(ui, |ui| {
+                    for (ch, label) in NumberLocale::GROUP_SEPARATORS {
+                        ui.selectable_value(&mut locale.group_separator, ch, label);
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Decimal separator:");
+            for (ch, label) in NumberLocale::DECIMAL_SEPARATORS {
+                ui.radio_value(&mut locale.decimal_separator, ch, label);
+            }
+        });
+        if locale != current {
+            app.set_locale(locale);
+        }
+        
+        ui.separator();
+        ui.label("Tax rates (TAX+ / TAX−):");
+        let business = app.business_mut();
+        let mut remove = None;
+        for (index, tax) in business.tax_rates.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut business.selected_tax, index, "");
+                ui.add(egui::TextEdit::singleline(&mut tax.name).desired_width(100.0));
+                ui.add(egui::DragValue::new(&mut tax.rate).speed(0.1).range(0.0..=100.0).suffix("%"));
+                if ui.small_button("✕").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            business.remove_tax_rate(index);
+        }
+        ui.horizontal(|ui| {
+            if ui.small_button("+ Rate").clicked() {
+                business.add_tax_rate();
+            }
+            ui.label("Discount (DISC):");
+            ui.add(egui::DragValue::new(&mut business.discount_rate).speed(0.1).range(0.0..=100.0).suffix("%"));
+        });
+    });
+}
+
+fn separator_label(options: &[(char, &'static str)], separator: char) -> &'static str {
+    options
+        .iter()
+        .find(|(ch, _)| *ch == separator)
+        .map(|(_, label)| *label)
+        .unwrap_or("Custom")
+}
+
+fn draw_history_panel(ui: &mut egui::Ui, app: &CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.set_max_height(150.0);
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new("History")
+                        .size(14.0)
+                        .strong()
+                        .color(Color32::from_rgb(70, 130, 180))
+                );
+                if ui.small_button("Export").on_hover_text("Copy history as text").clicked() {
+                    ui.ctx().copy_text(app.export_history());
+                }
+            });
+            ui.separator();
+            
+            for entry in app.get_history() {
+                ui.label(
+                    RichText::new(entry)
+                        .size(12.0)
+                        .color(Color32::from_rgb(60, 60, 60))
+                        .monospace()
+                );
+            }
+        });
+    });
+}
+
+/// The tape: each calculation's steps with editable numbers, results
+/// counted as items, subtotal marks and the grand total. Editing a number
+/// recomputes everything after it.
+fn draw_tape_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        let totals = app.tape_totals();
+        let shown: Vec<String> = totals
+            .values
+            .iter()
+            .map(|value| match value {
+                Ok(value) => app.format_number(*value),
+                Err(err) => err.clone(),
+            })
+            .collect();
+        let grand_total = app.format_number(totals.grand_total);
+        
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Tape")
+                    .size(14.0)
+                    .strong()
+                    .color(Color32::from_rgb(70, 130, 180))
+            );
+            if ui.small_button("GT").on_hover_text("Show the grand total").clicked() {
+                app.recall_grand_total();
+            }
+            if ui.small_button("◇").on_hover_text("Mark a subtotal").clicked() {
+                app.mark_subtotal();
+            }
+            if ui.small_button("Clear").clicked() {
+                app.clear_tape();
+            }
+        });
+        ui.separator();
+        
+        let mut remove = None;
+        egui::ScrollArea::vertical().max_height(160.0).stick_to_bottom(true).show(ui, |ui| {
+            egui::Grid::new("tape").spacing(Vec2::new(8.0, 2.0)).show(ui, |ui| {
+                for (index, line) in app.tape_mut().lines.iter_mut().enumerate() {
+                    let (mark, is_result) = match *line {
+                        TapeLine::Start(_) => (String::new(), false),
+                        TapeLine::Carry => ("↳".to_string(), false),
+                        TapeLine::Step { op, percent, .. } => {
+                            (format!("{}{}", op.to_symbol().trim(), if percent { " %" } else { "" }), false)
+                        }
+                        TapeLine::Total => ("=".to_string(), true),
+                        TapeLine::Subtotal => ("◇".to_string(), true),
+                    };
+                    ui.label(RichText::new(mark).monospace());
+                    match line.value_mut() {
+                        Some(value) => {
+                            ui.add(egui::DragValue::new(value).speed(0.1).max_decimals(10));
+                        }
+                        None => {
+                            let text = RichText::new(&shown[index]).monospace();
+                            ui.label(if is_result { text.strong() } else { text.weak() });
+                        }
+                    }
+                    match totals.items[index] {
+                        Some(item) => ui.label(RichText::new(format!("#{}", item)).weak()),
+                        None => ui.label(""),
+                    };
+                    if ui.small_button("✕").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(index) = remove {
+            app.tape_mut().lines.remove(index);
+        }
+        
+        ui.separator();
+        ui.label(
+            RichText::new(format!(
+                "GT {} · {} item{}",
+                grand_total,
+                totals.item_count,
+                if totals.item_count == 1 { "" } else { "s" }
+            ))
+            .strong()
+        );
+    });
+}
+
+fn draw_data_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!("Data ({})", app.get_data().len()))
+                    .size(14.0)
+                    .strong()
+                    .color(Color32::from_rgb(70, 130, 180))
+            );
+            if ui.small_button("Import CSV").clicked() {
+                app.toggle_import();
+            }
+            if ui.small_button("Clear").clicked() {
+                app.data_clear();
+            }
+        });
+        
+        let mut import = false;
+        if let Some(text) = app.import_text_mut() {
+            ui.add(
+                egui::TextEdit::multiline(text)
+                    .hint_text("Paste CSV or one value per line")
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY)
+            );
+            import = ui.button("Import").clicked();
+        }
+        if import {
+            app.finish_import();
+        }
+        ui.separator();
+        
+        let mut edit = None;
+        let mut remove = None;
+        egui::ScrollArea::vertical().max_height(110.0).show(ui, |ui| {
+            for (index, &point) in app.get_data().iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("{}", index + 1))
+                            .size(12.0)
+                            .color(Color32::from_rgb(60, 60, 60))
+                            .monospace()
+                    );
+                    let mut edited = point;
+                    let mut changed = ui.add(egui::DragValue::new(&mut edited.x).speed(0.1).prefix("x ")).changed();
+                    if let Some(y) = &mut edited.y {
+                        changed |= ui.add(egui::DragValue::new(y).speed(0.1).prefix("y ")).changed();
+                    }
+                    if changed {
+                        edit = Some((index, edited));
+                    }
+                    if ui.small_button("✕").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+        });
+        if let Some((index, point)) = edit {
+            app.data_set(index, point);
+        }
+        if let Some(index) = remove {
+            app.data_remove(index);
+        }
+        
+        if app.get_data().iter().any(|point| point.y.is_some()) {
+            ui.separator();
+            draw_regression(ui, app);
+        }
+    });
+}
+
+/// Named matrices with grid editors, the operation buttons, and the last
+/// result. Result matrices can be stored as new named matrices.
+fn draw_matrix_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Matrices")
+                    .size(14.0)
+                    .strong()
+                    .color(Color32::from_rgb(70, 130, 180))
+            );
+            if ui.small_button("+ Matrix").clicked() {
+                app.add_matrix();
+            }
+        });
+        
+        let mut remove = None;
+        egui::ScrollArea::vertical().max_height(180.0).show(ui, |ui| {
+            for (index, named) in app.matrices_mut().iter_mut().enumerate() {
+                ui.push_id(index, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut named.name).desired_width(40.0));
+                        let (mut rows, mut cols) = (named.matrix.rows(), named.matrix.cols());
+                        let mut resized = ui.add(egui::DragValue::new(&mut rows).range(1..=MAX_DIMENSION)).changed();
+                        ui.label("×");
+                        resized |= ui.add(egui::DragValue::new(&mut cols).range(1..=MAX_DIMENSION)).changed();
+                        if resized {
+                            named.matrix.resize(rows, cols);
+                        }
+                        if ui.small_button("✕").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                    egui::Grid::new("entries").spacing(Vec2::splat(4.0)).show(ui, |ui| {
+                        for i in 0..named.matrix.rows() {
+                            for j in 0..named.matrix.cols() {
+                                ui.add(egui::DragValue::new(&mut named.matrix[(i, j)]).speed(0.1));
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+        });
+        if let Some(index) = remove {
+            app.remove_matrix(index);
+        }
+        ui.separator();
+        
+        let (mut a, mut b) = app.get_matrix_operands();
+        ui.horizontal(|ui| {
+            let names: Vec<String> = app.get_matrices().iter().map(|m| m.name.clone()).collect();
+            for (label, selected) in [("A", &mut a), ("B", &mut b)] {
+                ui.label(label);
+                egui::ComboBox::from_id_source(label)
+                    .selected_text(names.get(*selected).map_or("—", |name| name.as_str()))
+                    .show_ui(ui, |ui| {
+                        for (index, name) in names.iter().enumerate() {
+                            ui.selectable_value(selected, index, name);
+                        }
+                    });
+            }
+        });
+        if (a, b) != app.get_matrix_operands() {
+            app.set_matrix_operands((a, b));
+        }
+        let mut apply = None;
+        ui.horizontal_wrapped(|ui| {
+            for op in MatrixOp::ALL {
+                if ui.button(op.label()).clicked() {
+                    apply = Some(op);
+                }
+            }
+        });
+        if let Some(op) = apply {
+            app.apply_matrix_op(op);
+        }
+        
+        let mut store = None;
+        let mut push = None;
+        match app.get_matrix_outcome() {
+            Some(MatrixOutcome::Matrices(results)) => {
+                ui.separator();
+                for (index, (name, matrix)) in results.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(name).size(12.0).strong());
+                        if ui.small_button("Store").on_hover_text("Keep as a named matrix").clicked() {
+                            store = Some(index);
+                        }
+                    });
+                    egui::Grid::new(("result", index)).spacing(Vec2::new(12.0, 2.0)).show(ui, |ui| {
+                        for i in 0..matrix.rows() {
+                            for j in 0..matrix.cols() {
+                                ui.label(RichText::new(app.format_number(matrix[(i, j)])).monospace());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                }
+            }
+            Some(MatrixOutcome::Values(label, values)) => {
+                ui.separator();
+                ui.label(RichText::new(label).size(12.0).strong());
+                ui.horizontal_wrapped(|ui| {
+                    for (index, &value) in values.iter().enumerate() {
+                        if ui.button(app.format_value(value)).on_hover_text("Show this value").clicked() {
+                            push = Some(index);
+                        }
+                    }
+                });
+            }
+            Some(MatrixOutcome::Scalar(..)) | None => {}
+        }
+        if let Some(index) = store {
+            app.store_matrix_result(index);
+        }
+        if let Some(index) = push {
+            app.push_matrix_value(index);
+        }
+    });
+}
+
+/// Unit conversion: a category, the units to convert between, and a
+/// button that converts the displayed number.
+fn draw_conversion_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.label(
+            RichText::new("Unit Conversion")
+                .size(14.0)
+                .strong()
+                .color(Color32::from_rgb(70, 130, 180))
+        );
+        
+        let mut convert = false;
+        let mut swap = false;
+        let conversion = app.conversion_mut();
+        let mut category = conversion.category;
+        egui::ComboBox::from_id_source("unit_category")
+            .selected_text(category.label())
+            .show_ui(ui, |ui| {
+                for option in Category::ALL {
+                    ui.selectable_value(&mut category, option, option.label());
+                }
+            });
+        if category != conversion.category {
+            *conversion = Conversion::for_category(category);
+        }
+        
+        ui.horizontal(|ui| {
+            for (label, unit) in [("from_unit", &mut conversion.from), ("to_unit", &mut conversion.to)] {
+                egui::ComboBox::from_id_source(label)
+                    .selected_text(unit.as_str())
+                    .show_ui(ui, |ui| {
+                        for option in category.units() {
+                            if ui.selectable_label(unit == option, *option).clicked() {
+                                *unit = option.to_string();
+                            }
+                        }
+                    });
+                if label == "from_unit" {
+                    swap = ui.button("⇄").on_hover_text("Swap units").clicked();
+                }
+            }
+            convert = ui.button("Convert").clicked();
+        });
+        
+        if swap {
+            app.swap_conversion_units();
+        }
+        if convert {
+            app.convert_units();
+        }
+    });
+}
+
+/// Financial worksheets: time value of money with amortization, cash-flow
+/// NPV/IRR/MIRR, depreciation schedules and compound interest. STO takes
+/// the displayed number; CPT solves a TVM variable from the others.
+fn draw_finance_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.label(
+            RichText::new("Financial")
+                .size(14.0)
+                .strong()
+                .color(Color32::from_rgb(70, 130, 180))
+        );
+        
+        let mut action = None;
+        let settings = app.finance_mut();
+        ui.collapsing("Time value of money", |ui| {
+            egui::Grid::new("tvm").spacing(Vec2::new(6.0, 4.0)).show(ui, |ui| {
+                for variable in TvmVariable::ALL {
+                    let tvm = &mut settings.tvm;
+                    let value = match variable {
+                        TvmVariable::Periods => &mut tvm.periods,
+                        TvmVariable::Rate => &mut tvm.rate,
+                        TvmVariable::PresentValue => &mut tvm.present_value,
+                        TvmVariable::Payment => &mut tvm.payment,
+                        TvmVariable::FutureValue => &mut tvm.future_value,
+                    };
+                    ui.label(variable.label());
+                    ui.add(egui::DragValue::new(value).speed(1.0).max_decimals(6));
+                    if ui.small_button("STO").on_hover_text("Store the displayed number").clicked() {
+                        action = Some(FinanceAction::Store(variable));
+                    }
+                    if ui.small_button("CPT").on_hover_text("Compute from the other values").clicked() {
+                        action = Some(FinanceAction::Solve(variable));
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("P/Y");
+                ui.add(egui::DragValue::new(&mut settings.tvm.periods_per_year).range(1.0..=365.0));
+                ui.checkbox(&mut settings.tvm.begin, "BEGIN");
+                if ui.button("Amortize").clicked() {
+                    action = Some(FinanceAction::Amortize);
+                }
+            });
+        });
+        
+        ui.collapsing("Cash flows", |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.cash_flows)
+                    .hint_text("CF0, CF1, CF2, …")
+                    .desired_width(f32::INFINITY)
+            );
+            ui.horizontal(|ui| {
+                ui.label("Discount %");
+                ui.add(egui::DragValue::new(&mut settings.discount_rate).speed(0.1));
+                ui.label("Finance %");
+                ui.add(egui::DragValue::new(&mut settings.finance_rate).speed(0.1));
+                ui.label("Reinvest %");
+                ui.add(egui::DragValue::new(&mut settings.reinvest_rate).speed(0.1));
+            });
+            ui.horizontal(|ui| {
+                for measure in CashFlowMeasure::ALL {
+                    if ui.button(measure.label()).clicked() {
+                        action = Some(FinanceAction::CashFlow(measure));
+                    }
+                }
+            });
+        });
+        
+        ui.collapsing("Depreciation", |ui| {
+            egui::ComboBox::from_id_source("depreciation")
+                .selected_text(settings.depreciation.label())
+                .show_ui(ui, |ui| {
+                    for method in DepreciationMethod::ALL {
+                        ui.selectable_value(&mut settings.depreciation, method, method.label());
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label("Cost");
+                ui.add(egui::DragValue::new(&mut settings.cost).speed(10.0));
+                ui.label("Salvage");
+                ui.add(egui::DragValue::new(&mut settings.salvage).speed(10.0));
+                ui.label("Life");
+                ui.add(egui::DragValue::new(&mut settings.life).range(1..=100).suffix(" y"));
+            });
+            ui.horizontal(|ui| {
+                if settings.depreciation == DepreciationMethod::DecliningBalance {
+                    ui.label("Factor");
+                    ui.add(egui::DragValue::new(&mut settings.declining_factor).speed(0.1).range(0.1..=10.0));
+                }
+                if ui.button("Schedule").clicked() {
+                    action = Some(FinanceAction::Depreciate);
+                }
+            });
+        });
+        
+        ui.collapsing("Compound interest", |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Principal");
+                ui.add(egui::DragValue::new(&mut settings.principal).speed(10.0));
+                ui.label("Rate %");
+                ui.add(egui::DragValue::new(&mut settings.annual_rate).speed(0.1));
+                ui.label("Years");
+                ui.add(egui::DragValue::new(&mut settings.years).speed(0.5).range(0.0..=1000.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Compounded");
+                egui::ComboBox::from_id_source("compounding")
+                    .selected_text(compounding_label(settings.compounding))
+                    .show_ui(ui, |ui| {
+                        for per_year in [1, 2, 4, 12, 52, 365, 0] {
+                            ui.selectable_value(&mut settings.compounding, per_year, compounding_label(per_year));
+                        }
+                    });
+                if ui.button("=").clicked() {
+                    action = Some(FinanceAction::Compound);
+                }
+            });
+        });
+        
+        match action {
+            Some(FinanceAction::Store(variable)) => app.store_tvm(variable),
+            Some(FinanceAction::Solve(variable)) => app.solve_tvm(variable),
+            Some(FinanceAction::Amortize) => app.amortize(),
+            Some(FinanceAction::CashFlow(measure)) => app.cash_flow_measure(measure),
+            Some(FinanceAction::Depreciate) => app.depreciate(),
+            Some(FinanceAction::Compound) => app.compound_interest(),
+            None => {}
+        }
+        
+        draw_finance_table(ui, app);
+    });
+}
+
+/// Buttons of the financial panel, applied after the inputs are drawn.
+enum FinanceAction {
+    Store(TvmVariable),
+    Solve(TvmVariable),
+    Amortize,
+    CashFlow(CashFlowMeasure),
+    Depreciate,
+    Compound,
+}
+
+fn compounding_label(per_year: u32) -> String {
+    match per_year {
+        0 => "continuously".to_string(),
+        1 => "yearly".to_string(),
+        2 => "half-yearly".to_string(),
+        4 => "quarterly".to_string(),
+        12 => "monthly".to_string(),
+        52 => "weekly".to_string(),
+        365 => "daily".to_string(),
+        n => format!("{} times a year", n),
+    }
+}
+
+/// The last amortization or depreciation schedule, with CSV export.
+fn draw_finance_table(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let Some(table) = app.get_finance_table() else {
+        return;
+    };
+    let mut export = false;
+    let mut close = false;
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(&table.title).strong());
+        export = ui.small_button("Export CSV").on_hover_text("Copy the table").clicked();
+        close = ui.small_button("✕").clicked();
+    });
+    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+        egui::Grid::new("finance_table").striped(true).spacing(Vec2::new(12.0, 2.0)).show(ui, |ui| {
+            for header in table.headers {
+                ui.label(RichText::new(*header).strong());
+            }
+            ui.end_row();
+            for row in &table.rows {
+                for (column, &value) in row.iter().enumerate() {
+                    // The first column counts periods or years
+                    let text = if column == 0 { format!("{}", value) } else { app.format_number(value) };
+                    ui.label(RichText::new(text).monospace());
+                }
+                ui.end_row();
+            }
+        });
+    });
+    if export {
+        if let Some(csv) = app.finance_table_csv() {
+            ui.ctx().copy_text(csv);
+        }
+    }
+    if close {
+        app.close_finance_table();
+    }
+}
+
+/// Date arithmetic: differences, adding durations, business days with a
+/// holiday list, weekday/ISO week details and Unix timestamps.
+fn draw_date_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.label(
+            RichText::new("Dates")
+                .size(14.0)
+                .strong()
+                .color(Color32::from_rgb(70, 130, 180))
+        );
+        
+        let mut action = None;
+        let settings = app.dates_mut();
+        egui::Grid::new("date_inputs").spacing(Vec2::new(8.0, 6.0)).show(ui, |ui| {
+            ui.label("Start:");
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut settings.start).hint_text("YYYY-MM-DD [HH:MM]").desired_width(150.0));
+                if ui.small_button("Today").clicked() {
+                    settings.start = Date::today().to_string();
+                }
+                if ui.button("Info").on_hover_text("Weekday, ISO week, day of year and Unix time").clicked() {
+                    action = Some(DateAction::Describe);
+                }
+            });
+            ui.end_row();
+            
+            ui.label("End:");
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut settings.end).hint_text("YYYY-MM-DD").desired_width(150.0));
+                if ui.small_button("Today").clicked() {
+                    settings.end = Date::today().to_string();
+                }
+                if ui.button("Difference").clicked() {
+                    action = Some(DateAction::Difference);
+                }
+            });
+            ui.end_row();
+            
+            ui.label("Duration:");
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut settings.amount).range(0..=100_000));
+                egui::ComboBox::from_id_source("duration_unit")
+                    .selected_text(settings.unit.label())
+                    .show_ui(ui, |ui| {
+                        for unit in DurationUnit::ALL {
+                            ui.selectable_value(&mut settings.unit, unit, unit.label());
+                        }
+                    });
+                if ui.button("+").on_hover_text("Start plus duration").clicked() {
+                    action = Some(DateAction::Shift(1));
+                }
+                if ui.button("−").on_hover_text("Start minus duration").clicked() {
+                    action = Some(DateAction::Shift(-1));
+                }
+            });
+            ui.end_row();
+            
+            ui.label("Unix time:");
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut settings.timestamp).hint_text("seconds").desired_width(150.0));
+                if ui.button("→ Date").clicked() {
+                    action = Some(DateAction::Timestamp);
+                }
+            });
+            ui.end_row();
+        });
+        
+        ui.collapsing("Holidays", |ui| {
+            ui.add(
+                egui::TextEdit::multiline(&mut settings.holidays)
+                    .hint_text("One date per line, # for comments")
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY)
+            );
+        });
+        
+        match action {
+            Some(DateAction::Difference) => app.date_difference(),
+            Some(DateAction::Shift(sign)) => app.date_shift(sign),
+            Some(DateAction::Describe) => app.describe_date(),
+            Some(DateAction::Timestamp) => app.timestamp_to_date(),
+            None => {}
+        }
+        
+        if !app.get_date_results().is_empty() {
+            ui.separator();
+            for line in app.get_date_results() {
+                ui.label(RichText::new(line).size(13.0).monospace());
+            }
+        }
+    });
+}
+
+/// Buttons of the date panel, applied after the inputs are drawn.
+enum DateAction {
+    Difference,
+    Shift(i64),
+    Describe,
+    Timestamp,
+}
+
+/// Exchange rates against a base currency, editable in place or imported
+/// as CSV/JSON, and a converter between any two of the currencies. Rates
+/// older than the staleness limit are flagged.
+fn draw_currency_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Currency")
+                    .size(14.0)
+                    .strong()
+                    .color(Color32::from_rgb(70, 130, 180))
+            );
+            if ui.small_button("Import").on_hover_text("Paste CSV or JSON rates").clicked() {
+                app.toggle_currency_import();
+            }
+            if ui.small_button("Export CSV").on_hover_text("Copy the rate table").clicked() {
+                ui.ctx().copy_text(app.get_rate_table().export_csv());
+            }
+            if ui.small_button("+ Rate").clicked() {
+                app.add_rate();
+            }
+        });
+        
+        let mut import = false;
+        if let Some(text) = app.currency_import_mut() {
+            ui.add(
+                egui::TextEdit::multiline(text)
+                    .hint_text("code,rate,updated per line, or {\"base\": …, \"rates\": {…}}")
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY)
+            );
+            import = ui.button("Import").clicked();
+        }
+        if import {
+            app.finish_currency_import();
+        }
+        
+        let now = dates::now();
+        let table = app.rate_table_mut();
+        ui.horizontal(|ui| {
+            ui.label("Base:");
+            ui.add(egui::TextEdit::singleline(&mut table.base).desired_width(40.0));
+            ui.label("Stale after");
+            ui.add(egui::DragValue::new(&mut table.stale_after_days).range(1..=365).suffix(" days"));
+        });
+        if let Some(imported) = table.imported {
+            ui.label(
+                RichText::new(format!("Last import {}", Date::from_timestamp(imported)))
+                    .size(11.0)
+                    .color(Color32::GRAY)
+            );
+        }
+        
+        let mut remove = None;
+        let stale: Vec<Option<i64>> = table.rates.iter().map(|rate| table.stale_age(&rate.code, now)).collect();
+        egui::ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+            for (index, rate) in table.rates.iter_mut().enumerate() {
+                ui.push_id(index, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut rate.code).desired_width(40.0));
+                        let edited = ui.add(egui::DragValue::new(&mut rate.per_base).speed(0.001).range(0.0..=f64::MAX)).changed();
+                        if edited {
+                            rate.updated = now;
+                        }
+                        let date = Date::from_timestamp(rate.updated);
+                        match stale[index] {
+                            Some(age) => ui.label(RichText::new(format!("⚠ {} ({} days)", date, age)).color(Color32::from_rgb(200, 120, 0))),
+                            None => ui.label(RichText::new(date.to_string()).color(Color32::GRAY)),
+                        };
+                        if ui.small_button("✕").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                });
+            }
+        });
+        if let Some(index) = remove {
+            app.remove_rate(index);
+        }
+        
+        let codes = app.get_rate_table().codes();
+        let (mut from, mut to) = app.get_currency_pair();
+        let mut convert = false;
+        let mut swap = false;
+        ui.horizontal(|ui| {
+            for (label, code) in [("from_currency", &mut from), ("to_currency", &mut to)] {
+                egui::ComboBox::from_id_source(label)
+                    .selected_text(code.as_str())
+                    .show_ui(ui, |ui| {
+                        for option in &codes {
+                            ui.selectable_value(code, option.clone(), option);
+                        }
+                    });
+                if label == "from_currency" {
+                    swap = ui.button("⇄").on_hover_text("Swap currencies").clicked();
+                }
+            }
+            convert = ui.button("Convert").clicked();
+        });
+        if swap {
+            std::mem::swap(&mut from, &mut to);
+        }
+        app.set_currency_pair((from, to));
+        if convert {
+            app.convert_currency();
+        }
+    });
+}
+
+/// Model selection and the current fit for paired data. Clicking a value
+/// pushes it into the display.
+fn draw_regression(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Regression").size(12.0).strong());
+        let mut model = app.get_regression_model();
+        egui::ComboBox::from_id_source("regression_model")
+            .selected_text(model.label())
+            .show_ui(ui, |ui| {
+                for option in RegressionModel::ALL {
+                    let selected = model.same_kind(option);
+                    if ui.selectable_label(selected, option.label()).clicked() && !selected {
+                        model = option;
+                    }
+                }
+            });
+        if let RegressionModel::Polynomial(degree) = &mut model {
+            ui.add(egui::DragValue::new(degree).range(1..=MAX_POLYNOMIAL_DEGREE).prefix("degree "));
+        }
+        if model != app.get_regression_model() {
+            app.set_regression_model(model);
+        }
+    });
+    
+    let fit = match app.get_fit() {
+        Ok(fit) => fit,
+        Err(err) => {
+            ui.label(RichText::new(err).size(12.0).color(Color32::from_rgb(220, 20, 60)));
+            return;
+        }
+    };
+    ui.label(
+        RichText::new(fit.equation(|c| app.format_number(c)))
+            .size(12.0)
+            .monospace()
+    );
+    let mut values: Vec<(String, f64)> = fit
+        .coefficients
+        .iter()
+        .enumerate()
+        .map(|(index, &c)| (coefficient_name(&fit, index), c))
+        .collect();
+    if let Some(r) = fit.r {
+        values.push(("r".to_string(), r));
+    }
+    values.push(("R²".to_string(), fit.r_squared));
+    
+    let mut pushed = None;
+    ui.horizontal_wrapped(|ui| {
+        for (name, value) in &values {
+            let text = format!("{} = {}", name, app.format_number(*value));
+            if ui
+                .add(egui::Label::new(RichText::new(text).size(12.0).monospace()).sense(Sense::click()))
+                .on_hover_text("Click to use this value")
+                .clicked()
+            {
+                pushed = Some((name.clone(), *value));
+            }
+        }
+    });
+    if let Some((name, value)) = pushed {
+        app.push_result(&name, value);
+    }
+}
+
+/// `a`, `b` for two-parameter models, `a₀`…`aₙ` for polynomials.
+fn coefficient_name(fit: &Fit, index: usize) -> String {
+    match fit.model {
+        RegressionModel::Polynomial(_) => {
+            let subscript = char::from_u32(0x2080 + index as u32).unwrap_or('?');
+            format!("a{}", subscript)
+        }
+        _ => ["a", "b"][index].to_string(),
+    }
+}
+
+/// Family and parameters of a probability distribution; the pdf/pmf, cdf
+/// and inv buttons apply to the displayed value.
+fn draw_distribution_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        let mut distribution = app.get_distribution();
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Distribution")
+                    .size(14.0)
+                    .strong()
+                    .color(Color32::from_rgb(70, 130, 180))
+            );
+            egui::ComboBox::from_id_source("distribution_family")
+                .selected_text(distribution.family.label())
+                .show_ui(ui, |ui| {
+                    for family in Family::ALL {
+                        if ui.selectable_label(distribution.family == family, family.label()).clicked()
+                            && distribution.family != family
+                        {
+                            distribution = Distribution::standard(family);
+                        }
+                    }
+                });
+        });
+        
+        ui.horizontal(|ui| {
+            let names = distribution.family.parameters();
+            for (name, value) in names.iter().zip(distribution.params.iter_mut()) {
+                ui.label(*name);
+                ui.add(egui::DragValue::new(value).speed(0.1));
+            }
+        });
+        if distribution != app.get_distribution() {
+            app.set_distribution(distribution);
+        }
+        
+        ui.horizontal(|ui| {
+            for function in Function::ALL {
+                let hint = match function {
+                    Function::Density => "Density (or probability) at the displayed x",
+                    Function::Cumulative => "P(X ≤ x) at the displayed x",
+                    Function::Inverse => "x with P(X ≤ x) equal to the displayed probability",
+                };
+                if ui.button(function.suffix(distribution.family)).on_hover_text(hint).clicked() {
+                    app.apply_distribution(function);
+                }
+            }
+        });
+    });
+}
+
+/// Derivative, integral, Σ, Π or limit of an expression; the result
+/// replaces the display and its error estimate shows as a notice.
+fn draw_calculus_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        let mut evaluate = false;
+        let settings = app.calculus_mut();
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Calculus")
+                    .size(14.0)
+                    .strong()
+                    .color(Color32::from_rgb(70, 130, 180))
+            );
+            for operation in Calculus::ALL {
+                ui.selectable_value(&mut settings.operation, operation, operation.symbol());
+            }
+        });
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut settings.expression)
+                .hint_text("Expression, e.g. sin(x)/x")
+                .desired_width(f32::INFINITY)
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            evaluate = true;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Variable");
+            ui.add(egui::TextEdit::singleline(&mut settings.variable).desired_width(30.0));
+            let (lower, upper) = match settings.operation {
+                Calculus::Derivative => ("at", None),
+                Calculus::Limit => ("→", None),
+                Calculus::Integral | Calculus::Sum | Calculus::Product => ("from", Some("to")),
+            };
+            ui.label(lower);
+            ui.add(egui::TextEdit::singleline(&mut settings.lower).desired_width(50.0));
+            if let Some(upper) = upper {
+                ui.label(upper);
+                ui.add(egui::TextEdit::singleline(&mut settings.upper).desired_width(50.0));
+            }
+            if ui.button("=").clicked() {
+                evaluate = true;
+            }
+        });
+        if evaluate {
+            app.apply_calculus();
+        }
+    });
+}
+
+/// Numeric solver for `f(x) = g(x)` and an all-roots polynomial solver.
+/// The equation's root replaces the display; polynomial roots are listed
+/// and shown when clicked.
+/// Number theory on exact big integers. Fields accept integer expressions
+/// such as `2^127 - 1`; an empty a uses the displayed number.
+fn draw_integer_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.label(
+            RichText::new("Number Theory")
+                .size(14.0)
+                .strong()
+                .color(Color32::from_rgb(70, 130, 180))
+        );
+        ui.separator();
+        
+        let settings = app.integer_mut();
+        egui::Grid::new("integer_fields").num_columns(2).show(ui, |ui| {
+            for (label, text, hint) in [
+                ("a", &mut settings.a, "displayed number"),
+                ("b", &mut settings.b, ""),
+                ("m", &mut settings.modulus, "modulus"),
+            ] {
+                ui.label(label);
+                ui.add(egui::TextEdit::singleline(text).hint_text(hint).desired_width(260.0));
+                ui.end_row();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("mod/div:");
+            egui::ComboBox::from_id_source("mod_convention")
+                .selected_text(settings.convention.label())
+                .show_ui(ui, |ui| {
+                    for convention in ModConvention::ALL {
+                        ui.selectable_value(&mut settings.convention, convention, convention.label());
+                    }
+                });
+        });
+        
+        let rows: [&[(IntegerFunction, &str)]; 3] = [
+            &[
+                (IntegerFunction::Gcd, "gcd(a, b)"),
+                (IntegerFunction::Lcm, "lcm(a, b)"),
+                (IntegerFunction::Mod(ModConvention::Floored), "a mod b"),
+                (IntegerFunction::Div(ModConvention::Floored), "a div b"),
+            ],
+            &[
+                (IntegerFunction::IsPrime, "prime?"),
+                (IntegerFunction::Factor, "factor"),
+                (IntegerFunction::NextPrime, "next prime"),
+                (IntegerFunction::PreviousPrime, "prev prime"),
+            ],
+            &[
+                (IntegerFunction::Totient, "φ(a)"),
+                (IntegerFunction::ModPow, "a^b mod m"),
+                (IntegerFunction::ModInverse, "a⁻¹ mod m"),
+            ],
+        ];
+        for row in rows {
+            ui.horizontal(|ui| {
+                for &(function, label) in row {
+                    if ui.button(label).clicked() {
+                        app.apply_integer(function);
+                    }
+                }
+            });
+        }
+        
+        if let Some(result) = app.get_integer_result() {
+            ui.separator();
+            ui.add(egui::Label::new(RichText::new(result).monospace()).wrap());
+        }
+    });
+}
+
+fn draw_solver_panel(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let frame = egui::Frame::none()
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
+    
+    frame.show(ui, |ui| {
+        ui.label(
+            RichText::new("Solve")
+                .size(14.0)
+                .strong()
+                .color(Color32::from_rgb(70, 130, 180))
+        );
+        let mut solve = false;
+        let settings = app.solver_mut();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut settings.equation)
+                .hint_text("f(x) = g(x), e.g. cos(x) = x")
+                .desired_width(f32::INFINITY)
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            solve = true;
+        }
+        
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("solver_method")
+                .selected_text(settings.method.label())
+                .show_ui(ui, |ui| {
+                    for method in Method::ALL {
+                        ui.selectable_value(&mut settings.method, method, method.label());
+                    }
+                });
+            for start in [Start::Auto, Start::Guess(0.0), Start::Bracket(-10.0, 10.0)] {
+                let selected = std::mem::discriminant(&settings.start) == std::mem::discriminant(&start);
+                if ui.selectable_label(selected, start.label()).clicked() && !selected {
+                    settings.start = start;
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            match &mut settings.start {
+                Start::Auto => {}
+                Start::Guess(x) => {
+                    ui.label("x₀");
+                    ui.add(egui::DragValue::new(x).speed(0.1));
+                }
+                Start::Bracket(a, b) => {
+                    ui.label("a");
+                    ui.add(egui::DragValue::new(a).speed(0.1));
+                    ui.label("b");
+                    ui.add(egui::DragValue::new(b).speed(0.1));
+                }
+            }
+            if ui.button("Solve").clicked() {
+                solve = true;
+            }
+        });
+        if solve {
+            app.solve_equation();
+        }
+        
+        ui.separator();
+        let mut roots = false;
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut app.solver_mut().coefficients)
+                    .hint_text(format!("Coefficients aₙ … a₀ (degree ≤ {})", solver::MAX_POLYNOMIAL_DEGREE))
+                    .desired_width(220.0)
+            );
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                roots = true;
             }
-            
-            // Mode toggle button
-            let mode_text = match app.get_mode() {
-                CalculatorMode::Standard => "Standard",
-                CalculatorMode::Scientific => "Scientific",
-            };
-            
-            if ui.button(
-                RichText::new(mode_text)
-                    .size(14.0)
-                    .color(Color32::from_rgb(100, 149, 237))
-            ).clicked() {
-                app.toggle_mode();
+            if ui.button("Roots").clicked() {
+                roots = true;
+            }
+        });
+        if roots {
+            app.solve_polynomial();
+        }
+        let mut push = None;
+        ui.horizontal_wrapped(|ui| {
+            for (index, &root) in app.get_roots().iter().enumerate() {
+                if ui.button(app.format_value(root)).on_hover_text("Show this root").clicked() {
+                    push = Some(index);
+                }
             }
         });
+        if let Some(index) = push {
+            app.push_root(index);
+        }
     });
 }
 
-fn draw_display(ui: &mut egui::Ui, app: &CalcsPlus) {
+/// Curve list, marker and axis settings, and the plot itself.
+fn draw_graph_view(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let locale = app.get_locale();
     let frame = egui::Frame::none()
-        .fill(Color32::from_rgb(248, 248, 255))
-        .stroke(Stroke::new(2.0, Color32::from_rgb(200, 200, 200)))
-        .inner_margin(egui::Margin::same(15.0))
-        .rounding(8.0);
+        .fill(Color32::from_rgb(250, 250, 250))
+        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
+        .inner_margin(egui::Margin::same(10.0))
+        .rounding(5.0);
     
+    let ctx = app.graph_context();
+    let angle = app.get_angle_unit();
+    let mut export = None;
     frame.show(ui, |ui| {
-        ui.set_min_height(DISPLAY_HEIGHT);
-        ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
-            // Error message or main display
-            if let Some(error) = app.get_error() {
-                ui.label(
-                    RichText::new(error)
-                        .size(20.0)
-                        .color(Color32::from_rgb(220, 20, 60))
-                        .monospace()
-                );
-            } else {
-                ui.label(
-                    RichText::new(app.get_display())
-                        .size(32.0)
-                        .color(Color32::from_rgb(25, 25, 112))
-                        .monospace()
-                        .strong()
-                );
+        let graph = app.graph_mut();
+        let x_range = [graph.axes.x_min, graph.axes.x_max];
+        let mut remove = None;
+        for (index, curve) in graph.curves.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                draw_curve_editor(ui, curve, angle);
+                ui.horizontal(|ui| {
+                    if ui.small_button("Export CSV").on_hover_text("Copy the sampled points").clicked() {
+                        export = Some(curve.export_csv(&locale, &ctx, x_range));
+                    }
+                    if ui.small_button("✕").clicked() {
+                        remove = Some(index);
+                    }
+                    if let Err(err) = curve.segments(&locale, &ctx, x_range) {
+                        if !curve.expression.trim().is_empty() || curve.kind == CurveKind::Table {
+                            ui.label(RichText::new(err).size(12.0).color(Color32::from_rgb(220, 20, 60)));
+                        }
+                    }
+                });
+            });
+        }
+        if let Some(index) = remove {
+            graph.curves.remove(index);
+        }
+        
+        ui.horizontal(|ui| {
+            ui.menu_button("+ Curve", |ui| {
+                for kind in CurveKind::ALL {
+                    if ui.button(kind.label()).clicked() {
+                        graph.add_curve(kind, angle);
+                        ui.close_menu();
+                    }
+                }
+            });
+            if ui.button("Reset view").clicked() {
+                graph.reset_view();
             }
+            ui.checkbox(&mut graph.markers.roots, "Roots");
+            ui.checkbox(&mut graph.markers.extrema, "Extrema");
+            ui.checkbox(&mut graph.markers.intersections, "∩");
         });
+        if let Some(status) = &graph.status {
+            ui.label(RichText::new(status).size(12.0).color(Color32::from_rgb(100, 100, 100)));
+        }
         
-        // Memory indicator
-        if app.has_memory() {
-            ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
-                ui.label(
-                    RichText::new("M")
-                        .size(14.0)
-                        .color(Color32::from_rgb(255, 140, 0))
-                        .strong()
-                );
+        egui::CollapsingHeader::new("Axes").show(ui, |ui| {
+            let axes = graph.axes.clone();
+            ui.horizontal(|ui| {
+                ui.label("x");
+                ui.add(egui::DragValue::new(&mut graph.axes.x_min).speed(0.1));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut graph.axes.x_max).speed(0.1));
+            });
+            ui.horizontal(|ui| {
+                ui.label("y");
+                ui.add(egui::DragValue::new(&mut graph.axes.y_min).speed(0.1));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut graph.axes.y_max).speed(0.1));
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut graph.axes.grid, "Grid");
+                ui.checkbox(&mut graph.axes.equal_aspect, "Equal scale");
+            });
+            if graph.axes != axes {
+                graph.reset_view();
+            }
+        });
+    });
+    match export {
+        Some(Ok(csv)) => {
+            ui.ctx().copy_text(csv);
+            app.graph_mut().status = Some("Curve points copied as CSV".to_string());
+        }
+        Some(Err(err)) => app.graph_mut().status = Some(format!("Cannot export: {}", err)),
+        None => {}
+    }
+    ui.add_space(8.0);
+    
+    let applied = draw_plot(ui, app);
+    app.graph_mut().view_applied |= applied;
+}
+
+/// Color, visibility, kind and the definition of one curve.
+fn draw_curve_editor(ui: &mut egui::Ui, curve: &mut Curve, angle: AngleUnit) {
+    ui.horizontal(|ui| {
+        egui::color_picker::color_edit_button_srgb(ui, &mut curve.color);
+        ui.checkbox(&mut curve.visible, "");
+        egui::ComboBox::from_id_source("curve_kind")
+            .selected_text(curve.kind.label())
+            .width(90.0)
+            .show_ui(ui, |ui| {
+                for kind in CurveKind::ALL {
+                    ui.selectable_value(&mut curve.kind, kind, kind.label());
+                }
             });
+        match curve.kind {
+            CurveKind::Function => {
+                ui.add(egui::TextEdit::singleline(&mut curve.expression).hint_text("y = f(x)"));
+            }
+            CurveKind::Polar => {
+                ui.add(egui::TextEdit::singleline(&mut curve.expression).hint_text("r = f(θ)"));
+            }
+            CurveKind::Parametric => {
+                ui.add(egui::TextEdit::singleline(&mut curve.expression).hint_text("x = f(t)"));
+            }
+            CurveKind::Table => {}
         }
     });
+    match curve.kind {
+        CurveKind::Function => {}
+        CurveKind::Parametric | CurveKind::Polar => {
+            ui.horizontal(|ui| {
+                if curve.kind == CurveKind::Parametric {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut curve.y_expression)
+                            .hint_text("y = g(t)")
+                            .desired_width(140.0)
+                    );
+                }
+                let (name, suffix) = match curve.kind {
+                    CurveKind::Polar => ("θ", angle.suffix()),
+                    _ => ("t", ""),
+                };
+                ui.label(name);
+                ui.add(egui::DragValue::new(&mut curve.parameter[0]).speed(0.1).suffix(suffix));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut curve.parameter[1]).speed(0.1).suffix(suffix));
+            });
+        }
+        CurveKind::Table => {
+            ui.add(
+                egui::TextEdit::multiline(&mut curve.table)
+                    .hint_text("x, y per line (paste from a spreadsheet)")
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY)
+            );
+        }
+    }
 }
 
-fn draw_history_panel(ui: &mut egui::Ui, app: &CalcsPlus) {
-    let frame = egui::Frame::none()
-        .fill(Color32::from_rgb(250, 250, 250))
-        .stroke(Stroke::new(1.0, Color32::from_rgb(220, 220, 220)))
-        .inner_margin(egui::Margin::same(10.0))
-        .rounding(5.0);
+/// Draws the visible curves over the current view, with analysis markers
+/// and a trace of every curve at the pointer. Returns whether the
+/// configured axis ranges were applied this frame.
+fn draw_plot(ui: &mut egui::Ui, app: &CalcsPlus) -> bool {
+    let graph = app.get_graph();
+    let locale = app.get_locale();
+    let ctx = app.graph_context();
+    let visible: Vec<&Curve> = graph.curves.iter().filter(|curve| curve.visible).collect();
+    let color = |curve: &Curve| {
+        let [r, g, b] = curve.color;
+        Color32::from_rgb(r, g, b)
+    };
+    // Parsed y = f(x) curves, which also get markers and a trace
+    let functions: Vec<(Expr, Color32)> = visible
+        .iter()
+        .filter(|curve| curve.kind == CurveKind::Function)
+        .filter_map(|curve| Some((curve.parse(&locale).ok()?, color(curve))))
+        .collect();
+    let axes = &graph.axes;
+    let markers = graph.markers;
+    let reset = !graph.view_applied;
+    let fmt = |value: f64| app.format_number(value);
     
-    frame.show(ui, |ui| {
-        ui.set_max_height(150.0);
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            ui.label(
-                RichText::new("History")
-                    .size(14.0)
-                    .strong()
-                    .color(Color32::from_rgb(70, 130, 180))
-            );
-            ui.separator();
-            
-            for entry in app.get_history() {
-                ui.label(
-                    RichText::new(entry)
-                        .size(12.0)
+    let mut plot = Plot::new("graph")
+        .legend(Legend::default())
+        .show_grid(axes.grid)
+        .x_axis_label("x")
+        .y_axis_label("y")
+        .allow_double_click_reset(false)
+        .coordinates_formatter(
+            Corner::LeftBottom,
+            CoordinatesFormatter::new(move |point, _| format!("x = {}  y = {}", fmt(point.x), fmt(point.y)))
+        )
+        .label_formatter(move |name, point| {
+            if name.is_empty() {
+                String::new()
+            } else {
+                format!("{}\nx = {}\ny = {}", name, fmt(point.x), fmt(point.y))
+            }
+        });
+    if axes.equal_aspect {
+        plot = plot.data_aspect(1.0);
+    }
+    
+    plot.show(ui, |plot_ui| {
+        if reset {
+            plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                [axes.x_min, axes.y_min],
+                [axes.x_max, axes.y_max],
+            ));
+        }
+        let bounds = plot_ui.plot_bounds();
+        let (x_min, x_max) = (bounds.min()[0], bounds.max()[0]);
+        let mut features = Vec::new();
+        
+        for curve in &visible {
+            let Ok(segments) = curve.segments(&locale, &ctx, [x_min, x_max]) else {
+                continue;
+            };
+            let name = match curve.kind {
+                CurveKind::Table => format!("Table {}", segments.concat().len()),
+                _ => curve.expression.clone(),
+            };
+            for segment in segments {
+                if curve.kind == CurveKind::Table {
+                    plot_ui.points(Points::new(segment).radius(3.0).color(color(curve)).name(&name));
+                } else {
+                    plot_ui.line(Line::new(PlotPoints::new(segment)).color(color(curve)).width(2.0).name(&name));
+                }
+            }
+        }
+        for (expr, _) in &functions {
+            let mut function = graph::Function::new(expr, &ctx);
+            if markers.roots {
+                features.extend(function.roots(x_min, x_max));
+            }
+            if markers.extrema {
+                features.extend(function.extrema(x_min, x_max));
+            }
+        }
+        if markers.intersections {
+            for (i, (f, _)) in functions.iter().enumerate() {
+                for (g, _) in &functions[i + 1..] {
+                    let mut f = graph::Function::new(f, &ctx);
+                    let mut g = graph::Function::new(g, &ctx);
+                    features.extend(graph::intersections(&mut f, &mut g, x_min, x_max));
+                }
+            }
+        }
+        for kind in [FeatureKind::Root, FeatureKind::Minimum, FeatureKind::Maximum, FeatureKind::Intersection] {
+            let points: Vec<[f64; 2]> = features
+                .iter()
+                .filter(|feature| feature.kind == kind)
+                .map(|feature| [feature.x, feature.y])
+                .collect();
+            if !points.is_empty() {
+                plot_ui.points(
+                    Points::new(points)
+                        .name(kind.label())
+                        .shape(MarkerShape::Circle)
+                        .radius(4.0)
                         .color(Color32::from_rgb(60, 60, 60))
-                        .monospace()
                 );
             }
-        });
+        }
+        
+        // Trace: each curve's value at the pointer's x
+        if let Some(pointer) = plot_ui.pointer_coordinate() {
+            for (expr, color) in &functions {
+                let y = graph::Function::new(expr, &ctx).eval(pointer.x);
+                if y.is_finite() {
+                    plot_ui.points(Points::new([pointer.x, y]).radius(4.0).color(*color));
+                    plot_ui.text(
+                        Text::new(PlotPoint::new(pointer.x, y), format!("  ({}, {})", fmt(pointer.x), fmt(y)))
+                            .anchor(egui::Align2::LEFT_BOTTOM)
+                            .color(*color)
+                    );
+                }
+            }
+        }
     });
+    reset
 }
 
 fn draw_standard_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
     ui.spacing_mut().item_spacing = Vec2::splat(SPACING);
+    draw_precision_row(ui, app);
     
     // Memory and Clear buttons row
     ui.horizontal(|ui| {
@@ -181,17 +2024,7 @@ fn draw_standard_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
             app.backspace();
         }
         if calc_button(ui, "±", BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
-            if let Ok(mut val) = app.get_display().parse::<f64>() {
-                val = -val;
-                app.clear_entry();
-                for ch in val.to_string().chars() {
-                    if ch == '.' {
-                        app.input_decimal();
-                    } else {
-                        app.input_digit(ch);
-                    }
-                }
-            }
+            app.toggle_sign();
         }
     });
     
@@ -211,6 +2044,35 @@ fn draw_standard_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
         }
     });
     
+    // Business row: percent change, margin, tax and discount
+    ui.horizontal(|ui| {
+        if calc_button(ui, "Δ%", BUTTON_SIZE, Color32::from_rgb(173, 216, 230))
+            .on_hover_text("Percent change: old Δ% new =")
+            .clicked()
+        {
+            app.set_operation(Operation::PercentChange);
+        }
+        if calc_button(ui, "MU", BUTTON_SIZE, Color32::from_rgb(173, 216, 230))
+            .on_hover_text("Selling price at a margin: cost MU margin% =")
+            .clicked()
+        {
+            app.set_operation(Operation::Margin);
+        }
+        let business = app.get_business();
+        let tax = business.tax().map_or("No tax rate configured".to_string(), |tax| {
+            format!("{} {}%", tax.name, app.format_number(tax.rate))
+        });
+        let discount = format!("Discount {}%", app.format_number(business.discount_rate));
+        for (key, hover) in [(RateKey::AddTax, &tax), (RateKey::RemoveTax, &tax), (RateKey::Discount, &discount)] {
+            if calc_button(ui, key.label(), BUTTON_SIZE, Color32::from_rgb(230, 230, 250))
+                .on_hover_text(hover.as_str())
+                .clicked()
+            {
+                app.apply_rate_key(key);
+            }
+        }
+    });
+    
     // Number row 7-9
     ui.horizontal(|ui| {
         if calc_button(ui, "7", BUTTON_SIZE, Color32::WHITE).clicked() {
@@ -262,7 +2124,7 @@ fn draw_standard_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
     // Bottom row
     ui.horizontal(|ui| {
         if calc_button(ui, "%", BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
-            app.perform_unary_operation(Operation::Percentage);
+            app.percent();
         }
         if calc_button(ui, "0", BUTTON_SIZE, Color32::WHITE).clicked() {
             app.input_digit('0');
@@ -278,6 +2140,7 @@ fn draw_standard_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
 
 fn draw_scientific_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
     ui.spacing_mut().item_spacing = Vec2::splat(SPACING);
+    draw_precision_row(ui, app);
     
     // First row - Memory and clear
     ui.horizontal(|ui| {
@@ -310,17 +2173,7 @@ fn draw_scientific_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
             app.backspace();
         }
         if calc_button(ui, "±", BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
-            if let Ok(mut val) = app.get_display().parse::<f64>() {
-                val = -val;
-                app.clear_entry();
-                for ch in val.to_string().chars() {
-                    if ch == '.' {
-                        app.input_decimal();
-                    } else {
-                        app.input_digit(ch);
-                    }
-                }
-            }
+            app.toggle_sign();
         }
         if calc_button(ui, "÷", BUTTON_SIZE, Color32::from_rgb(173, 216, 230)).clicked() {
             app.set_operation(Operation::Divide);
@@ -405,8 +2258,272 @@ fn draw_scientific_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
     // Bottom rows
     ui.horizontal(|ui| {
         if calc_button(ui, "%", BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
-            app.perform_unary_operation(Operation::Percentage);
+            app.percent();
+        }
+        if calc_button(ui, "1", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('1');
+        }
+        if calc_button(ui, "2", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('2');
+        }
+        if calc_button(ui, "3", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('3');
+        }
+        let complex_mode = app.is_complex_mode();
+        if ui.add_enabled_ui(complex_mode, |ui| {
+            calc_button(ui, "i", BUTTON_SIZE, Color32::from_rgb(221, 160, 221))
+        }).inner.clicked() {
+            app.input_imaginary();
+        }
+    });
+    
+    ui.horizontal(|ui| {
+        if calc_button(ui, "EXP", BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
+            app.input_exponent();
+        }
+        if calc_button(ui, "0", LARGE_BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('0');
+        }
+        if calc_button(ui, ".", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_decimal();
+        }
+        let domain = if app.is_complex_mode() { "ℂ" } else { "ℝ" };
+        if calc_button(ui, domain, BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
+            app.set_complex_mode(!app.is_complex_mode());
+        }
+    });
+
+    // Drawer with rounding, numeric utility and random keys
+    let arrow = if app.is_showing_more_functions() { "▴" } else { "▾" };
+    if ui.button(format!("More functions {}", arrow)).clicked() {
+        app.toggle_more_functions();
+    }
+    if app.is_showing_more_functions() {
+        draw_more_functions(ui, app);
+    }
+}
+
+/// The ± key in uncertainty mode, above the keypad.
+fn draw_precision_row(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    if app.get_precision_mode() != PrecisionMode::Uncertainty {
+        return;
+    }
+    ui.horizontal(|ui| {
+        if calc_button(ui, "±σ", BUTTON_SIZE, Color32::from_rgb(173, 216, 230))
+            .on_hover_text("Measurement entry: 5.2 ±σ 0.1 is 5.2 ± 0.1")
+            .clicked()
+        {
+            app.input_uncertainty();
+        }
+        ui.label(RichText::new("Uncertainties propagate to first order").size(11.0).color(Color32::GRAY));
+    });
+}
+
+fn draw_more_functions(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    let unary_rows = [
+        [
+            ("⌊x⌋", Operation::Floor, "Floor: round down"),
+            ("⌈x⌉", Operation::Ceil, "Ceiling: round up"),
+            ("round", Operation::Round, "Round to the nearest integer, halves away from zero"),
+            ("trunc", Operation::Trunc, "Integer part: round towards zero"),
+            ("|x|", Operation::Abs, "Absolute value"),
+        ],
+        [
+            ("sign", Operation::Sign, "−1, 0 or 1"),
+            ("frac", Operation::FractionalPart, "Fractional part, x − trunc(x)"),
+            ("rnd n", Operation::RoundDigits, "Round x to n decimal places; enter x, rnd n, then n ="),
+            ("sig n", Operation::RoundSignificant, "Round x to n significant figures; enter x, sig n, then n ="),
+            ("rand", Operation::Random, "Uniform random number in [0, 1)"),
+        ],
+    ];
+    for row in unary_rows {
+        ui.horizontal(|ui| {
+            for (label, op, hover) in row {
+                if calc_button(ui, label, BUTTON_SIZE, Color32::from_rgb(221, 160, 221))
+                    .on_hover_text(hover)
+                    .clicked()
+                {
+                    match op {
+                        Operation::RoundDigits | Operation::RoundSignificant => app.set_operation(op),
+                        _ => app.perform_unary_operation(op),
+                    }
+                }
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        if calc_button(ui, "a b/c", BUTTON_SIZE, Color32::from_rgb(230, 230, 250))
+            .on_hover_text("Fraction entry: 3 a b/c 4 is 3/4; 1 a b/c 3 a b/c 4 is 1 3/4")
+            .clicked()
+        {
+            app.input_fraction();
+        }
+        if calc_button(ui, "F↔D", BUTTON_SIZE, Color32::from_rgb(230, 230, 250))
+            .on_hover_text("Show results as fractions or decimals")
+            .clicked()
+        {
+            app.toggle_display_format(DisplayFormat::Fraction(3));
+        }
+        if calc_button(ui, "°'\"", BUTTON_SIZE, Color32::from_rgb(230, 230, 250))
+            .on_hover_text("Angle entry: 12 °'\" 34 °'\" 56 is 12°34'56\", stored as decimal degrees")
+            .clicked()
+        {
+            app.input_dms();
+        }
+        if calc_button(ui, "DMS↔D", BUTTON_SIZE, Color32::from_rgb(230, 230, 250))
+            .on_hover_text("Show results in degrees, minutes and seconds or decimal degrees")
+            .clicked()
+        {
+            app.toggle_display_format(DisplayFormat::Dms(1));
+        }
+    });
+
+    ui.horizontal(|ui| {
+        if calc_button(ui, "randint", BUTTON_SIZE, Color32::from_rgb(221, 160, 221))
+            .on_hover_text("Random integer from a to b inclusive; enter a, randint, then b =")
+            .clicked()
+        {
+            app.set_operation(Operation::RandomInteger);
+        }
+        let mut seeded = app.get_random_seed().is_some();
+        if ui.checkbox(&mut seeded, "Seed").on_hover_text("Repeat the same random sequence").changed() {
+            app.set_random_seed(seeded.then_some(0));
+        }
+        if let Some(mut seed) = app.get_random_seed() {
+            if ui.add(egui::DragValue::new(&mut seed)).changed() {
+                app.set_random_seed(Some(seed));
+            }
+            if ui.button("↺").on_hover_text("Restart the sequence from the seed").clicked() {
+                app.restart_random();
+            }
+        }
+    });
+}
+
+fn draw_statistics_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
+    ui.spacing_mut().item_spacing = Vec2::splat(SPACING);
+    
+    // Memory row
+    ui.horizontal(|ui| {
+        if calc_button(ui, "MC", BUTTON_SIZE, Color32::from_rgb(255, 182, 193)).clicked() {
+            app.memory_clear();
+        }
+        if calc_button(ui, "MR", BUTTON_SIZE, Color32::from_rgb(255, 182, 193)).clicked() {
+            app.memory_recall();
+        }
+        if calc_button(ui, "M+", BUTTON_SIZE, Color32::from_rgb(255, 182, 193)).clicked() {
+            app.memory_add();
+        }
+        if calc_button(ui, "M-", BUTTON_SIZE, Color32::from_rgb(255, 182, 193)).clicked() {
+            app.memory_subtract();
+        }
+        if calc_button(ui, "MS", BUTTON_SIZE, Color32::from_rgb(255, 182, 193)).clicked() {
+            app.memory_store();
+        }
+    });
+    
+    // Summary statistics rows; results are pushed into the display
+    let stat_rows = [
+        [Statistic::Count, Statistic::Sum, Statistic::SumOfSquares, Statistic::Mean, Statistic::Median],
+        [Statistic::SampleStdDev, Statistic::PopulationStdDev, Statistic::SampleVariance, Statistic::PopulationVariance, Statistic::Mode],
+        [Statistic::Min, Statistic::Max, Statistic::Q1, Statistic::Q3, Statistic::Percentile(0.0)],
+    ];
+    for row in stat_rows {
+        ui.horizontal(|ui| {
+            for stat in row {
+                let label = match stat {
+                    Statistic::Percentile(_) => "P%".to_string(),
+                    other => other.label(),
+                };
+                let button = calc_button(ui, &label, BUTTON_SIZE, Color32::from_rgb(221, 160, 221));
+                let button = match stat {
+                    Statistic::Percentile(_) => button.on_hover_text("Percentile at the displayed rank (0–100)"),
+                    _ => button,
+                };
+                if button.clicked() {
+                    app.push_statistic(stat);
+                }
+            }
+        });
+    }
+    
+    // Paired data and regression row
+    ui.horizontal(|ui| {
+        if calc_button(ui, "x,y", BUTTON_SIZE, Color32::from_rgb(100, 149, 237))
+            .on_hover_text("Hold the displayed value as x; enter y, then Σ+")
+            .clicked()
+        {
+            app.data_pair();
+        }
+        if calc_button(ui, "ŷ", BUTTON_SIZE, Color32::from_rgb(221, 160, 221))
+            .on_hover_text("Predict y for the displayed x")
+            .clicked()
+        {
+            app.push_prediction();
+        }
+        if calc_button(ui, "a", BUTTON_SIZE, Color32::from_rgb(221, 160, 221)).clicked() {
+            app.push_fit_value("a", |fit| fit.coefficients.first().copied());
+        }
+        if calc_button(ui, "b", BUTTON_SIZE, Color32::from_rgb(221, 160, 221)).clicked() {
+            app.push_fit_value("b", |fit| fit.coefficients.get(1).copied());
+        }
+        if calc_button(ui, "r", BUTTON_SIZE, Color32::from_rgb(221, 160, 221)).clicked() {
+            app.push_fit_value("r", |fit| fit.r);
+        }
+    });
+    
+    // Clear row
+    ui.horizontal(|ui| {
+        if calc_button(ui, "CE", BUTTON_SIZE, Color32::from_rgb(255, 204, 204)).clicked() {
+            app.clear_entry();
+        }
+        if calc_button(ui, "C", BUTTON_SIZE, Color32::from_rgb(255, 204, 204)).clicked() {
+            app.clear();
+        }
+        if calc_button(ui, "⌫", BUTTON_SIZE, Color32::from_rgb(255, 204, 204)).clicked() {
+            app.backspace();
+        }
+        if calc_button(ui, "CD", BUTTON_SIZE, Color32::from_rgb(255, 204, 204))
+            .on_hover_text("Clear data")
+            .clicked()
+        {
+            app.data_clear();
         }
+    });
+    
+    // Number rows
+    ui.horizontal(|ui| {
+        if calc_button(ui, "7", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('7');
+        }
+        if calc_button(ui, "8", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('8');
+        }
+        if calc_button(ui, "9", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('9');
+        }
+        if calc_button(ui, "EXP", BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
+            app.input_exponent();
+        }
+    });
+    
+    ui.horizontal(|ui| {
+        if calc_button(ui, "4", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('4');
+        }
+        if calc_button(ui, "5", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('5');
+        }
+        if calc_button(ui, "6", BUTTON_SIZE, Color32::WHITE).clicked() {
+            app.input_digit('6');
+        }
+        if calc_button(ui, "±", BUTTON_SIZE, Color32::from_rgb(230, 230, 250)).clicked() {
+            app.toggle_sign();
+        }
+    });
+    
+    ui.horizontal(|ui| {
         if calc_button(ui, "1", BUTTON_SIZE, Color32::WHITE).clicked() {
             app.input_digit('1');
         }
@@ -416,18 +2533,21 @@ fn draw_scientific_buttons(ui: &mut egui::Ui, app: &mut CalcsPlus) {
         if calc_button(ui, "3", BUTTON_SIZE, Color32::WHITE).clicked() {
             app.input_digit('3');
         }
-        ui.add_space(BUTTON_SIZE.x + SPACING);
+        if calc_button(ui, "Σ+", BUTTON_SIZE, Color32::from_rgb(100, 149, 237))
+            .on_hover_text("Add the displayed value to the data set")
+            .clicked()
+        {
+            app.data_add();
+        }
     });
     
     ui.horizontal(|ui| {
-        ui.add_space(BUTTON_SIZE.x + SPACING);
         if calc_button(ui, "0", LARGE_BUTTON_SIZE, Color32::WHITE).clicked() {
             app.input_digit('0');
         }
         if calc_button(ui, ".", BUTTON_SIZE, Color32::WHITE).clicked() {
             app.input_decimal();
         }
-        ui.add_space(BUTTON_SIZE.x + SPACING);
     });
 }
 
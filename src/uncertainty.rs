@@ -0,0 +1,355 @@
+use crate::calculator::{self, decimal_exponent, format_number, format_with, DisplayFormat, NumericOptions, Operation};
+use crate::complex::Complex;
+use std::fmt;
+
+/// What keypad entries carry besides their value.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum PrecisionMode {
+    #[default]
+    Off,
+    /// Typed numbers keep the significant figures they were typed with.
+    SignificantFigures,
+    /// Numbers are entered as `5.2 ± 0.1` with the ± key.
+    Uncertainty,
+}
+
+impl PrecisionMode {
+    pub const ALL: [PrecisionMode; 3] =
+        [PrecisionMode::Off, PrecisionMode::SignificantFigures, PrecisionMode::Uncertainty];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PrecisionMode::Off => "Off",
+            PrecisionMode::SignificantFigures => "Significant figures",
+            PrecisionMode::Uncertainty => "Uncertainty (±)",
+        }
+    }
+}
+
+/// How precisely a measured value is known.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Precision {
+    /// A counted or defined number.
+    Exact,
+    /// Standard uncertainty, propagated linearly.
+    Uncertainty(f64),
+    /// Significant figures, propagated with the rules used by hand.
+    Figures(u32),
+}
+
+/// A value with its precision, e.g. `5.2 ± 0.1` or `2.50` to three
+/// significant figures.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Measured {
+    pub value: f64,
+    pub precision: Precision,
+}
+
+impl Measured {
+    pub fn exact(value: f64) -> Self {
+        Self { value, precision: Precision::Exact }
+    }
+
+    /// The standard uncertainty; significant figures count as half a unit
+    /// in the last place.
+    fn uncertainty(self) -> f64 {
+        match self.precision {
+            Precision::Exact => 0.0,
+            Precision::Uncertainty(sigma) => sigma,
+            Precision::Figures(_) => 0.5 * 10f64.powi(self.last_place().unwrap_or(0)),
+        }
+    }
+
+    /// Power of ten of the last significant digit.
+    fn last_place(self) -> Option<i32> {
+        match self.precision {
+            Precision::Figures(figures) => Some(decimal_exponent(self.value) - figures as i32 + 1),
+            _ => None,
+        }
+    }
+
+    fn figures(self) -> Option<u32> {
+        match self.precision {
+            Precision::Figures(figures) => Some(figures),
+            _ => None,
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        Self { value: -self.value, ..self }
+    }
+}
+
+/// Applies a keypad operation to measured values. An uncertainty on
+/// either operand is propagated to first order,
+/// `σ² = (∂f/∂a·σa)² + (∂f/∂b·σb)²`, treating the operands as independent.
+/// Otherwise significant figures follow the usual rules: the fewest
+/// figures for products, quotients and functions, the coarsest decimal
+/// place for sums and differences, and as many decimals as the operand
+/// has figures for logarithms.
+pub fn calculate(op: Operation, a: Measured, b: Option<Measured>, options: NumericOptions) -> Result<Measured, String> {
+    if op == Operation::PlusMinus {
+        let sigma = b.ok_or_else(|| "± requires two operands".to_string())?.value.abs();
+        return Ok(Measured {
+            value: a.value,
+            precision: Precision::Uncertainty(a.uncertainty().hypot(sigma)),
+        });
+    }
+    let value = calculator::calculate(op, a.value, b.map(|b| b.value), options)?;
+    let uncertain = [Some(a), b]
+        .into_iter()
+        .flatten()
+        .any(|m| matches!(m.precision, Precision::Uncertainty(_)));
+    let precision = if matches!(op, Operation::Random | Operation::RandomInteger) {
+        Precision::Exact
+    } else if uncertain {
+        let (da, db) = partials(op, a.value, b.map_or(0.0, |b| b.value), value, options);
+        // Exact operands contribute nothing, even where the derivative is infinite
+        let term = |derivative: f64, m: Measured| match m.uncertainty() {
+            0.0 => 0.0,
+            sigma => derivative * sigma,
+        };
+        let sigma = term(da, a).hypot(b.map_or(0.0, |b| term(db, b)));
+        if !sigma.is_finite() {
+            return Err(format!("{} is not differentiable here; the uncertainty cannot be propagated", op.to_symbol().trim()));
+        }
+        Precision::Uncertainty(sigma.abs())
+    } else {
+        figures(op, a, b, value)
+    };
+    Ok(Measured { value, precision })
+}
+
+/// Partial derivatives of `op` at `(a, b)`; `value` is the result there.
+fn partials(op: Operation, a: f64, b: f64, value: f64, options: NumericOptions) -> (f64, f64) {
+    let radians = options.angle.to_radians(1.0);
+    match op {
+        Operation::Add => (1.0, 1.0),
+        Operation::Subtract => (1.0, -1.0),
+        Operation::Multiply => (b, a),
+        Operation::Divide => (1.0 / b, -a / (b * b)),
+        Operation::Power => (b * a.powf(b - 1.0), value * a.ln()),
+        Operation::SquareRoot => (0.5 / value, 0.0),
+        Operation::Square => (2.0 * a, 0.0),
+        Operation::Reciprocal => (-1.0 / (a * a), 0.0),
+        Operation::Percentage => (0.01, 0.0),
+        Operation::PercentChange => (-100.0 * b / (a * a), 100.0 / a),
+        Operation::Margin => {
+            let kept = 1.0 - b / 100.0;
+            (1.0 / kept, a / (100.0 * kept * kept))
+        }
+        Operation::Sin => (radians * (a * radians).cos(), 0.0),
+        Operation::Cos => (-radians * (a * radians).sin(), 0.0),
+        Operation::Tan => (radians / (a * radians).cos().powi(2), 0.0),
+        Operation::Log => (1.0 / (a * std::f64::consts::LN_10), 0.0),
+        Operation::Ln => (1.0 / a, 0.0),
+        Operation::Factorial => {
+            // Central difference of Γ(a + 1)
+            let h = 1e-5 * a.abs().max(1.0);
+            let gamma = |x: f64| Complex::from(x + 1.0).gamma().re;
+            ((gamma(a + h) - gamma(a - h)) / (2.0 * h), 0.0)
+        }
+        Operation::Abs | Operation::Sign if a == 0.0 => (f64::INFINITY, 0.0),
+        Operation::Abs => (a.signum(), 0.0),
+        Operation::FractionalPart => (1.0, 0.0),
+        // Steps are flat almost everywhere, and the rounding digits are counts
+        Operation::Floor
+        | Operation::Ceil
+        | Operation::Round
+        | Operation::Trunc
+        | Operation::Sign
+        | Operation::RoundDigits
+        | Operation::RoundSignificant
+        | Operation::Random
+        | Operation::RandomInteger => (0.0, 0.0),
+        Operation::PlusMinus => (1.0, 0.0),
+    }
+}
+
+fn figures(op: Operation, a: Measured, b: Option<Measured>, value: f64) -> Precision {
+    let fewest = |operands: &[Option<Measured>]| operands.iter().flatten().filter_map(|m| m.figures()).min();
+    let figures = match op {
+        Operation::Add | Operation::Subtract => {
+            let place = [Some(a), b].into_iter().flatten().filter_map(Measured::last_place).max();
+            place.map(|place| (decimal_exponent(value) - place + 1).max(1) as u32)
+        }
+        Operation::Log | Operation::Ln => a.figures().map(|n| (decimal_exponent(value) + 1 + n as i32).max(1) as u32),
+        // The exponent and the digits to round to are exact counts
+        Operation::Power | Operation::RoundDigits => a.figures(),
+        Operation::RoundSignificant => a.figures().map(|n| n.min(b.map_or(n, |b| b.value.max(1.0) as u32))),
+        _ => fewest(&[Some(a), b]),
+    };
+    figures.map_or(Precision::Exact, Precision::Figures)
+}
+
+impl fmt::Display for Measured {
+    /// An uncertainty is shown to one significant figure, or two when it
+    /// starts with 1 and a non-zero digit, and the value to the same
+    /// decimal place, so `5.2 ± 0.1` stays as typed. Significant
+    /// figures switch to scientific notation where trailing zeros of a
+    /// whole number would be ambiguous.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.precision {
+            Precision::Exact => write!(f, "{}", format_number(self.value)),
+            _ if !self.value.is_finite() => write!(f, "{}", format_number(self.value)),
+            Precision::Figures(figures) if self.value != 0.0 && decimal_exponent(self.value) >= figures as i32 => {
+                write!(f, "{:.*e}", figures as usize - 1, self.value)
+            }
+            Precision::Figures(figures) => write!(f, "{}", format_with(self.value, DisplayFormat::Significant(figures.min(255) as u8))),
+            Precision::Uncertainty(sigma) if sigma == 0.0 || !sigma.is_finite() => {
+                write!(f, "{} ± {}", format_number(self.value), format_number(sigma))
+            }
+            Precision::Uncertainty(sigma) => {
+                let leading = (sigma / 10f64.powi(decimal_exponent(sigma) - 1)).round();
+                let place = decimal_exponent(sigma) - if (11.0..20.0).contains(&leading) { 1 } else { 0 };
+                let unit = 10f64.powi(place);
+                let (value, sigma) = ((self.value / unit).round() * unit, (sigma / unit).round() * unit);
+                if (-6..=15).contains(&place) && self.value.abs() < 1e15 {
+                    let decimals = (-place).max(0) as usize;
+                    write!(f, "{:.*} ± {:.*}", decimals, value, decimals, sigma)
+                } else {
+                    let exponent = decimal_exponent(if self.value != 0.0 { self.value } else { sigma });
+                    let scale = 10f64.powi(exponent);
+                    let decimals = (exponent - place).max(0) as usize;
+                    write!(f, "({:.*} ± {:.*})e{}", decimals, self.value / scale, decimals, sigma / scale, exponent)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::AngleUnit;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9 * expected.abs().max(1.0), "{} != {}", actual, expected);
+    }
+
+    fn measured(value: f64, sigma: f64) -> Measured {
+        Measured { value, precision: Precision::Uncertainty(sigma) }
+    }
+
+    fn typed(value: f64, figures: u32) -> Measured {
+        Measured { value, precision: Precision::Figures(figures) }
+    }
+
+    fn sigma(m: Measured) -> f64 {
+        match m.precision {
+            Precision::Uncertainty(sigma) => sigma,
+            precision => panic!("{:?} has no uncertainty", precision),
+        }
+    }
+
+    fn apply(op: Operation, a: Measured, b: Option<Measured>) -> Measured {
+        calculate(op, a, b, NumericOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn products_add_relative_uncertainties_in_quadrature() {
+        let product = apply(Operation::Multiply, measured(5.2, 0.1), Some(measured(3.0, 0.2)));
+        assert_close(product.value, 15.6);
+        // √((3.0·0.1)² + (5.2·0.2)²) ≈ 1.08
+        assert_close(sigma(product), 0.3f64.hypot(1.04));
+        assert_eq!(product.to_string(), "15.6 ± 1.1");
+
+        let scaled = apply(Operation::Multiply, measured(5.2, 0.1), Some(Measured::exact(2.0)));
+        assert_close(sigma(scaled), 0.2);
+    }
+
+    #[test]
+    fn plus_minus_enters_a_measurement() {
+        let entered = apply(Operation::PlusMinus, Measured::exact(5.2), Some(Measured::exact(-0.1)));
+        assert_eq!(entered, measured(5.2, 0.1));
+        assert_eq!(entered.to_string(), "5.2 ± 0.1");
+        // A second ± adds in quadrature
+        assert_close(sigma(apply(Operation::PlusMinus, measured(5.0, 0.3), Some(Measured::exact(0.4)))), 0.5);
+    }
+
+    #[test]
+    fn partial_derivatives() {
+        let options = NumericOptions::default();
+        let (da, db) = partials(Operation::Divide, 6.0, 3.0, 2.0, options);
+        assert_close(da, 1.0 / 3.0);
+        assert_close(db, -6.0 / 9.0);
+        let (da, db) = partials(Operation::Power, 2.0, 3.0, 8.0, options);
+        assert_close(da, 12.0);
+        assert_close(db, 8.0 * 2f64.ln());
+        // Degrees scale the derivative by π/180
+        let (da, _) = partials(Operation::Sin, 60.0, 0.0, 0.0, options);
+        assert_close(da, std::f64::consts::PI / 360.0);
+        let radians = NumericOptions { angle: AngleUnit::Radians, ..options };
+        assert_close(partials(Operation::Cos, 0.5, 0.0, 0.0, radians).0, -0.5f64.sin());
+        // Γ′(4) = 3!·ψ(4) = 6·(11/6 − γ)
+        let (da, _) = partials(Operation::Factorial, 3.0, 0.0, 6.0, options);
+        assert!((da - 6.0 * (11.0 / 6.0 - 0.577_215_664_901_532_9)).abs() < 1e-4, "{}", da);
+        assert_eq!(partials(Operation::Round, 2.4, 0.0, 2.0, options), (0.0, 0.0));
+    }
+
+    #[test]
+    fn non_differentiable_points_are_reported() {
+        let error = calculate(Operation::Abs, measured(0.0, 0.1), None, NumericOptions::default()).unwrap_err();
+        assert!(error.contains("not differentiable"), "{}", error);
+        // An exact operand has nothing to propagate
+        assert_eq!(apply(Operation::Abs, Measured::exact(0.0), None), Measured::exact(0.0));
+    }
+
+    #[test]
+    fn products_keep_the_fewest_figures() {
+        let product = apply(Operation::Multiply, typed(2.50, 3), Some(typed(3.1, 2)));
+        assert_eq!(product.precision, Precision::Figures(2));
+        assert_eq!(product.to_string(), "7.8");
+        // Exact numbers do not limit the figures
+        assert_eq!(apply(Operation::Multiply, typed(2.50, 3), Some(Measured::exact(4.0))).precision, Precision::Figures(3));
+        assert_eq!(apply(Operation::Multiply, Measured::exact(2.5), Some(Measured::exact(4.0))).precision, Precision::Exact);
+    }
+
+    #[test]
+    fn sums_keep_the_coarsest_decimal_place() {
+        let sum = apply(Operation::Add, typed(12.3, 3), Some(typed(1.234, 4)));
+        assert_eq!(sum.precision, Precision::Figures(3));
+        assert_eq!(sum.to_string(), "13.5");
+        // Cancellation leaves a single figure at the tenths
+        let difference = apply(Operation::Subtract, typed(12.3, 3), Some(typed(12.25, 4)));
+        assert_eq!(difference.precision, Precision::Figures(1));
+        let sum = apply(Operation::Add, typed(950.0, 2), Some(typed(70.0, 1)));
+        assert_eq!(sum.precision, Precision::Figures(3));
+        assert_eq!(sum.to_string(), "1.02e3");
+    }
+
+    #[test]
+    fn logarithms_keep_as_many_decimals_as_the_argument_has_figures() {
+        let log = apply(Operation::Log, typed(2.50, 3), None);
+        assert_eq!(log.precision, Precision::Figures(3));
+        assert_eq!(log.to_string(), "0.398");
+        let ln = apply(Operation::Ln, typed(250.0, 2), None);
+        assert_eq!(ln.precision, Precision::Figures(3));
+        assert_eq!(ln.to_string(), "5.52");
+    }
+
+    #[test]
+    fn exponents_and_rounding_digits_are_exact_counts() {
+        let power = apply(Operation::Power, typed(2.5, 2), Some(typed(3.0, 1)));
+        assert_eq!(power.precision, Precision::Figures(2));
+        assert_eq!(power.to_string(), "16");
+        let rounded = apply(Operation::RoundDigits, typed(1.23456, 6), Some(typed(2.0, 1)));
+        assert_eq!(rounded.precision, Precision::Figures(6));
+        let rounded = apply(Operation::RoundSignificant, typed(1.23456, 6), Some(Measured::exact(3.0)));
+        assert_eq!(rounded.precision, Precision::Figures(3));
+        assert_eq!(apply(Operation::Random, typed(3.0, 1), None).precision, Precision::Exact);
+    }
+
+    #[test]
+    fn display_rounds_the_value_to_the_uncertainty() {
+        assert_eq!(measured(1.23456, 0.0149).to_string(), "1.235 ± 0.015");
+        assert_eq!(measured(5.23, 0.104).to_string(), "5.2 ± 0.1");
+        assert_eq!(measured(15.63, 0.3).to_string(), "15.6 ± 0.3");
+        assert_eq!(measured(123_456.0, 2_000.0).to_string(), "123000 ± 2000");
+        assert_eq!(measured(1.5e-9, 2e-10).to_string(), "(1.5 ± 0.2)e-9");
+        assert_eq!(measured(5.2, 0.0).to_string(), "5.2 ± 0");
+        // Trailing zeros of a whole number would be ambiguous
+        assert_eq!(typed(2500.0, 2).to_string(), "2.5e3");
+        assert_eq!(typed(2500.0, 4).to_string(), "2500");
+    }
+}
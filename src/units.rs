@@ -0,0 +1,705 @@
+use crate::calculator::{self, NumericOptions, Operation};
+use crate::expression::{self, Context};
+use crate::locale::NumberLocale;
+use crate::value::Value;
+
+/// Exponents of the base dimensions: length, mass, time, current,
+/// temperature, amount, luminous intensity, information and money.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct Dimension([i8; 9]);
+
+const BASE_SYMBOLS: [&str; 9] = ["m", "kg", "s", "A", "K", "mol", "cd", "bit", "¤"];
+
+const fn dimension(length: i8, mass: i8, time: i8, current: i8, temperature: i8) -> Dimension {
+    Dimension([length, mass, time, current, temperature, 0, 0, 0, 0])
+}
+
+const LENGTH: Dimension = dimension(1, 0, 0, 0, 0);
+const MASS: Dimension = dimension(0, 1, 0, 0, 0);
+const TIME: Dimension = dimension(0, 0, 1, 0, 0);
+const CURRENT: Dimension = dimension(0, 0, 0, 1, 0);
+const TEMPERATURE: Dimension = dimension(0, 0, 0, 0, 1);
+const AMOUNT: Dimension = Dimension([0, 0, 0, 0, 0, 1, 0, 0, 0]);
+const LUMINOSITY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 1, 0, 0]);
+const INFORMATION: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0, 1, 0]);
+const MONEY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0, 0, 1]);
+const AREA: Dimension = dimension(2, 0, 0, 0, 0);
+const VOLUME: Dimension = dimension(3, 0, 0, 0, 0);
+const SPEED: Dimension = dimension(1, 0, -1, 0, 0);
+const FREQUENCY: Dimension = dimension(0, 0, -1, 0, 0);
+const FORCE: Dimension = dimension(1, 1, -2, 0, 0);
+const ENERGY: Dimension = dimension(2, 1, -2, 0, 0);
+const POWER: Dimension = dimension(2, 1, -3, 0, 0);
+const PRESSURE: Dimension = dimension(-1, 1, -2, 0, 0);
+const VOLTAGE: Dimension = dimension(2, 1, -3, -1, 0);
+const RESISTANCE: Dimension = dimension(2, 1, -3, -2, 0);
+
+impl Dimension {
+    fn is_dimensionless(self) -> bool {
+        self.0.iter().all(|&exponent| exponent == 0)
+    }
+
+    fn combine(self, other: Dimension, sign: i8) -> Dimension {
+        let mut result = self;
+        for (exponent, other) in result.0.iter_mut().zip(other.0) {
+            *exponent += sign * other;
+        }
+        result
+    }
+
+    /// The dimension raised to `power`, when every exponent stays whole.
+    fn power(self, power: f64) -> Option<Dimension> {
+        let mut result = self;
+        for exponent in result.0.iter_mut() {
+            let scaled = *exponent as f64 * power;
+            if scaled.fract() != 0.0 || scaled.abs() > i8::MAX as f64 {
+                return None;
+            }
+            *exponent = scaled as i8;
+        }
+        Some(result)
+    }
+
+    /// SI base units, e.g. `kg·m^2/s^2`.
+    fn base_text(self) -> String {
+        let part = |symbol: &str, exponent: i8| match exponent {
+            1 => symbol.to_string(),
+            _ => format!("{}^{}", symbol, exponent),
+        };
+        let numerator: Vec<String> = BASE_SYMBOLS
+            .iter()
+            .zip(self.0)
+            .filter(|(_, exponent)| *exponent > 0)
+            .map(|(symbol, exponent)| part(symbol, exponent))
+            .collect();
+        let denominator: Vec<String> = BASE_SYMBOLS
+            .iter()
+            .zip(self.0)
+            .filter(|(_, exponent)| *exponent < 0)
+            .map(|(symbol, exponent)| part(symbol, -exponent))
+            .collect();
+        let numerator = if numerator.is_empty() { "1".to_string() } else { numerator.join("·") };
+        match denominator.len() {
+            0 => numerator,
+            1 => format!("{}/{}", numerator, denominator[0]),
+            _ => format!("{}/({})", numerator, denominator.join("·")),
+        }
+    }
+}
+
+/// Which prefixes a unit accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Prefixes {
+    None,
+    Si,
+    /// SI and binary (Ki, Mi, …) prefixes, for data sizes.
+    Binary,
+}
+
+struct UnitDefinition {
+    /// Symbols and names; the short symbols (three characters or fewer)
+    /// take prefixes.
+    names: &'static [&'static str],
+    /// Size of one unit in SI base units.
+    factor: f64,
+    /// SI value of the unit's zero point, for °C and °F.
+    offset: f64,
+    dimension: Dimension,
+    prefixes: Prefixes,
+}
+
+const fn unit(names: &'static [&'static str], factor: f64, dimension: Dimension, prefixes: Prefixes) -> UnitDefinition {
+    UnitDefinition { names, factor, offset: 0.0, dimension, prefixes }
+}
+
+const US_GALLON: f64 = 3.785_411_784e-3;
+
+const UNITS: &[UnitDefinition] = &[
+    unit(&["m", "meter", "metre", "meters", "metres"], 1.0, LENGTH, Prefixes::Si),
+    unit(&["in", "inch", "inches"], 0.0254, LENGTH, Prefixes::None),
+    unit(&["ft", "foot", "feet"], 0.3048, LENGTH, Prefixes::None),
+    unit(&["yd", "yard", "yards"], 0.9144, LENGTH, Prefixes::None),
+    unit(&["mi", "mile", "miles"], 1609.344, LENGTH, Prefixes::None),
+    unit(&["nmi"], 1852.0, LENGTH, Prefixes::None),
+    unit(&["au"], 149_597_870_700.0, LENGTH, Prefixes::None),
+    unit(&["ly"], 9_460_730_472_580_800.0, LENGTH, Prefixes::None),
+    unit(&["g", "gram", "grams"], 1e-3, MASS, Prefixes::Si),
+    unit(&["tonne", "tonnes"], 1000.0, MASS, Prefixes::None),
+    unit(&["lb", "lbs", "pound", "pounds"], 0.453_592_37, MASS, Prefixes::None),
+    unit(&["oz", "ounce", "ounces"], 0.028_349_523_125, MASS, Prefixes::None),
+    unit(&["st", "stone"], 6.350_293_18, MASS, Prefixes::None),
+    unit(&["s", "sec", "second", "seconds"], 1.0, TIME, Prefixes::Si),
+    unit(&["min", "minute", "minutes"], 60.0, TIME, Prefixes::None),
+    unit(&["h", "hr", "hour", "hours"], 3600.0, TIME, Prefixes::None),
+    unit(&["d", "day", "days"], 86_400.0, TIME, Prefixes::None),
+    unit(&["wk", "week", "weeks"], 604_800.0, TIME, Prefixes::None),
+    // Julian year
+    unit(&["yr", "year", "years"], 31_557_600.0, TIME, Prefixes::None),
+    unit(&["A", "amp", "ampere"], 1.0, CURRENT, Prefixes::Si),
+    unit(&["K", "kelvin"], 1.0, TEMPERATURE, Prefixes::Si),
+    UnitDefinition {
+        names: &["°C", "degC", "celsius"],
+        factor: 1.0,
+        offset: 273.15,
+        dimension: TEMPERATURE,
+        prefixes: Prefixes::None,
+    },
+    UnitDefinition {
+        names: &["°F", "degF", "fahrenheit"],
+        factor: 5.0 / 9.0,
+        offset: 459.67 * 5.0 / 9.0,
+        dimension: TEMPERATURE,
+        prefixes: Prefixes::None,
+    },
+    unit(&["°R", "degR", "rankine"], 5.0 / 9.0, TEMPERATURE, Prefixes::None),
+    unit(&["mol", "mole"], 1.0, AMOUNT, Prefixes::Si),
+    unit(&["cd", "candela"], 1.0, LUMINOSITY, Prefixes::Si),
+    unit(&["bit", "bits"], 1.0, INFORMATION, Prefixes::Binary),
+    unit(&["B", "byte", "bytes"], 8.0, INFORMATION, Prefixes::Binary),
+    unit(&["ha", "hectare", "hectares"], 1e4, AREA, Prefixes::None),
+    unit(&["acre", "acres"], 4_046.856_422_4, AREA, Prefixes::None),
+    unit(&["L", "l", "liter", "litre", "liters", "litres"], 1e-3, VOLUME, Prefixes::Si),
+    unit(&["gal", "gallon", "gallons"], US_GALLON, VOLUME, Prefixes::None),
+    unit(&["qt", "quart", "quarts"], US_GALLON / 4.0, VOLUME, Prefixes::None),
+    unit(&["pt", "pint", "pints"], US_GALLON / 8.0, VOLUME, Prefixes::None),
+    unit(&["cup", "cups"], US_GALLON / 16.0, VOLUME, Prefixes::None),
+    unit(&["floz"], US_GALLON / 128.0, VOLUME, Prefixes::None),
+    unit(&["tbsp"], US_GALLON / 256.0, VOLUME, Prefixes::None),
+    unit(&["tsp"], US_GALLON / 768.0, VOLUME, Prefixes::None),
+    unit(&["mph"], 0.447_04, SPEED, Prefixes::None),
+    unit(&["kn", "knot", "knots"], 1852.0 / 3600.0, SPEED, Prefixes::None),
+    unit(&["Hz", "hertz"], 1.0, FREQUENCY, Prefixes::Si),
+    unit(&["rpm"], 1.0 / 60.0, FREQUENCY, Prefixes::None),
+    unit(&["N", "newton", "newtons"], 1.0, FORCE, Prefixes::Si),
+    unit(&["lbf"], 4.448_221_615_260_5, FORCE, Prefixes::None),
+    unit(&["kgf"], 9.806_65, FORCE, Prefixes::None),
+    unit(&["J", "joule", "joules"], 1.0, ENERGY, Prefixes::Si),
+    unit(&["cal", "calorie", "calories"], 4.184, ENERGY, Prefixes::Si),
+    unit(&["Wh"], 3600.0, ENERGY, Prefixes::Si),
+    unit(&["eV"], 1.602_176_634e-19, ENERGY, Prefixes::Si),
+    unit(&["BTU", "btu"], 1_055.055_852_62, ENERGY, Prefixes::None),
+    unit(&["W", "watt", "watts"], 1.0, POWER, Prefixes::Si),
+    unit(&["hp", "horsepower"], 745.699_871_582_270_2, POWER, Prefixes::None),
+    unit(&["Pa", "pascal", "pascals"], 1.0, PRESSURE, Prefixes::Si),
+    unit(&["bar"], 1e5, PRESSURE, Prefixes::Si),
+    unit(&["atm"], 101_325.0, PRESSURE, Prefixes::None),
+    unit(&["psi"], 6_894.757_293_168_361, PRESSURE, Prefixes::None),
+    unit(&["mmHg"], 133.322_387_415, PRESSURE, Prefixes::None),
+    unit(&["torr", "Torr"], 101_325.0 / 760.0, PRESSURE, Prefixes::None),
+    unit(&["V", "volt", "volts"], 1.0, VOLTAGE, Prefixes::Si),
+    unit(&["Ω", "ohm", "ohms"], 1.0, RESISTANCE, Prefixes::Si),
+];
+
+const SI_PREFIXES: &[(&str, f64)] = &[
+    ("da", 1e1),
+    ("Y", 1e24),
+    ("Z", 1e21),
+    ("E", 1e18),
+    ("P", 1e15),
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("h", 1e2),
+    ("d", 1e-1),
+    ("c", 1e-2),
+    ("m", 1e-3),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("u", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+    ("f", 1e-15),
+    ("a", 1e-18),
+    ("z", 1e-21),
+    ("y", 1e-24),
+];
+
+const BINARY_PREFIXES: &[(&str, f64)] = &[
+    ("Ki", 1024.0),
+    ("Mi", 1_048_576.0),
+    ("Gi", 1_073_741_824.0),
+    ("Ti", 1_099_511_627_776.0),
+    ("Pi", 1_125_899_906_842_624.0),
+];
+
+/// The unit shown with a quantity and its size in SI base units.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct ShownUnit {
+    text: String,
+    factor: f64,
+    offset: f64,
+}
+
+/// A value with a physical dimension, kept in SI base units and shown in
+/// the unit it was entered or converted to.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Quantity {
+    value: f64,
+    dimension: Dimension,
+    unit: ShownUnit,
+    /// A unit on its own, such as `km` in `5 km`. A number times a bare
+    /// °C or °F is an absolute temperature.
+    bare: bool,
+}
+
+impl Quantity {
+    /// The value in the shown unit.
+    pub fn shown_value(&self) -> f64 {
+        (self.value - self.unit.offset) / self.unit.factor
+    }
+
+    pub fn unit_text(&self) -> &str {
+        &self.unit.text
+    }
+
+    /// The quantity with its shown value negated, so that `-40 °F` is a
+    /// temperature below the unit's zero rather than a product with an
+    /// offset unit.
+    pub fn negate(&self) -> Quantity {
+        self.with_value(-self.shown_value() * self.unit.factor + self.unit.offset)
+    }
+
+    fn with_value(&self, value: f64) -> Quantity {
+        Quantity {
+            value,
+            bare: false,
+            ..self.clone()
+        }
+    }
+}
+
+/// Looks up a unit symbol or name, with an optional prefix on short symbols
+/// (`km`, `µs`, `kWh`, `MiB`). Symbols are case-sensitive; longer names
+/// are not.
+pub fn lookup(name: &str) -> Option<Quantity> {
+    let shown = |definition: &UnitDefinition, scale: f64| Quantity {
+        value: scale * definition.factor + definition.offset,
+        dimension: definition.dimension,
+        unit: ShownUnit {
+            text: name.to_string(),
+            factor: scale * definition.factor,
+            offset: definition.offset,
+        },
+        bare: true,
+    };
+    if let Some(definition) = UNITS.iter().find(|unit| unit.names.contains(&name)) {
+        return Some(shown(definition, 1.0));
+    }
+    if name.chars().count() > 3 {
+        let lower = name.to_lowercase();
+        if let Some(definition) = UNITS
+            .iter()
+            .find(|unit| unit.names.iter().any(|n| n.chars().count() > 3 && n.to_lowercase() == lower))
+        {
+            return Some(shown(definition, 1.0));
+        }
+    }
+    UNITS.iter().find_map(|definition| {
+        let prefixes: &[&[(&str, f64)]] = match definition.prefixes {
+            Prefixes::None => return None,
+            Prefixes::Si => &[SI_PREFIXES],
+            Prefixes::Binary => &[SI_PREFIXES, BINARY_PREFIXES],
+        };
+        prefixes.iter().flat_map(|list| list.iter()).find_map(|&(prefix, scale)| {
+            let symbol = name.strip_prefix(prefix)?;
+            let short = symbol.chars().count() <= 3 && definition.names.contains(&symbol);
+            short.then(|| shown(definition, scale))
+        })
+    })
+}
+
+/// A currency as a unit, valued in the rate table's base currency, so
+/// `100 USD to EUR` and `20 EUR/h` work like any other unit.
+pub fn currency(code: &str, per_base: f64) -> Quantity {
+    Quantity {
+        value: 1.0 / per_base,
+        dimension: MONEY,
+        unit: ShownUnit {
+            text: code.to_uppercase(),
+            factor: 1.0 / per_base,
+            offset: 0.0,
+        },
+        bare: true,
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Quantity(quantity) => quantity.unit.text.clone(),
+        Value::Scalar(_) => "a plain number".to_string(),
+        Value::Vector(_) => "a vector".to_string(),
+        Value::Measured(_) => "a value with uncertainty".to_string(),
+    }
+}
+
+/// Quantities whose unit has an offset (°C, °F) only support addition,
+/// subtraction and conversion.
+fn no_offset(quantity: &Quantity) -> Result<(), String> {
+    if quantity.unit.offset == 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} values can only be added, subtracted or converted", quantity.unit.text))
+    }
+}
+
+/// A unit text that can take an exponent or divisor without parentheses.
+fn is_simple(text: &str) -> bool {
+    !text.contains(['·', '/', '^'])
+}
+
+fn multiply_text(a: &str, b: &str) -> String {
+    if a == b && is_simple(a) {
+        return format!("{}^2", a);
+    }
+    let a = if a.contains('/') { format!("({})", a) } else { a.to_string() };
+    format!("{}·{}", a, b)
+}
+
+fn divide_text(a: &str, b: &str) -> String {
+    if !b.contains(['·', '/']) {
+        format!("{}/{}", a, b)
+    } else {
+        format!("{}/({})", a, b)
+    }
+}
+
+/// `text` raised to `exponent`, folding into an existing power: `m^2`
+/// squared is `m^4` and its square root is `m`.
+fn power_text(text: &str, exponent: f64) -> String {
+    let (base, exponent) = match text.rsplit_once('^') {
+        Some((base, existing)) if is_simple(base) => match existing.parse::<f64>() {
+            Ok(existing) => (base, existing * exponent),
+            Err(_) => (text, exponent),
+        },
+        _ => (text, exponent),
+    };
+    if exponent == 1.0 {
+        base.to_string()
+    } else if is_simple(base) {
+        format!("{}^{}", base, calculator::full_precision(exponent))
+    } else {
+        format!("({})^{}", base, calculator::full_precision(exponent))
+    }
+}
+
+/// Collapses a quantity whose units cancelled to a plain number.
+fn simplify(quantity: Quantity) -> Value {
+    if quantity.dimension.is_dimensionless() {
+        Value::Scalar(quantity.value)
+    } else {
+        Value::Quantity(quantity)
+    }
+}
+
+fn power(quantity: &Quantity, exponent: f64, options: NumericOptions) -> Result<Value, String> {
+    no_offset(quantity)?;
+    let dimension = quantity.dimension.power(exponent).ok_or_else(|| {
+        let exponent = calculator::full_precision(exponent);
+        format!("Cannot raise {} to the power {}", quantity.unit.text, exponent)
+    })?;
+    let text = power_text(&quantity.unit.text, exponent);
+    Ok(simplify(Quantity {
+        value: calculator::calculate(Operation::Power, quantity.value, Some(exponent), options)?,
+        dimension,
+        unit: ShownUnit {
+            text,
+            factor: quantity.unit.factor.powf(exponent),
+            offset: 0.0,
+        },
+        bare: false,
+    }))
+}
+
+/// Applies a keypad operation where at least one operand carries units.
+/// Sums need matching dimensions; products and powers combine them.
+pub fn calculate(op: Operation, a: &Value, b: Option<&Value>, options: NumericOptions) -> Result<Value, String> {
+    let apply = |x: f64, y: f64| calculator::calculate(op, x, Some(y), options);
+    match (a, b) {
+        (Value::Vector(_), _) | (_, Some(Value::Vector(_))) => Err("Vectors cannot carry units".to_string()),
+        (Value::Quantity(q), None) => match op {
+            Operation::Square => power(q, 2.0, options),
+            Operation::SquareRoot => power(q, 0.5, options),
+            Operation::Reciprocal => power(q, -1.0, options),
+            Operation::Percentage => {
+                no_offset(q)?;
+                Ok(Value::Quantity(q.with_value(q.value / 100.0)))
+            }
+            _ => Err(format!("{} needs a plain number, not {}", op.to_symbol().trim(), q.unit.text)),
+        },
+        (Value::Quantity(x), Some(Value::Quantity(y))) => match op {
+            Operation::Add | Operation::Subtract => {
+                if x.dimension != y.dimension {
+                    return Err(format!("Incompatible units: {} and {}", x.unit.text, y.unit.text));
+                }
+                let value = apply(x.value, y.value)?;
+                let both_offset = x.unit.offset != 0.0 && y.unit.offset != 0.0;
+                match op {
+                    Operation::Add if both_offset => Err(format!(
+                        "Cannot add two {} temperatures; add a difference in K instead",
+                        x.unit.text
+                    )),
+                    // A difference of two temperatures is a plain interval
+                    Operation::Subtract if both_offset => Ok(Value::Quantity(Quantity {
+                        value,
+                        dimension: x.dimension,
+                        unit: ShownUnit { text: x.dimension.base_text(), factor: 1.0, offset: 0.0 },
+                        bare: false,
+                    })),
+                    Operation::Add if y.unit.offset != 0.0 => Ok(Value::Quantity(y.with_value(value))),
+                    _ => Ok(Value::Quantity(x.with_value(value))),
+                }
+            }
+            Operation::Multiply | Operation::Divide => {
+                no_offset(x)?;
+                no_offset(y)?;
+                let (sign, text, factor) = if op == Operation::Multiply {
+                    (1, multiply_text(&x.unit.text, &y.unit.text), x.unit.factor * y.unit.factor)
+                } else {
+                    (-1, divide_text(&x.unit.text, &y.unit.text), x.unit.factor / y.unit.factor)
+                };
+                Ok(simplify(Quantity {
+                    value: apply(x.value, y.value)?,
+                    dimension: x.dimension.combine(y.dimension, sign),
+                    unit: ShownUnit { text, factor, offset: 0.0 },
+                    bare: false,
+                }))
+            }
+            Operation::Power => Err("Exponents must be plain numbers".to_string()),
+            _ => Err(format!("{} is not defined for units", op.to_symbol().trim())),
+        },
+        (Value::Scalar(s), Some(Value::Quantity(q))) => match op {
+            // A number times a bare unit is a measurement in that unit,
+            // which places temperatures relative to the unit's zero
+            Operation::Multiply if q.bare => Ok(Value::Quantity(q.with_value(s * q.unit.factor + q.unit.offset))),
+            Operation::Multiply => {
+                no_offset(q)?;
+                Ok(Value::Quantity(q.with_value(apply(*s, q.value)?)))
+            }
+            Operation::Divide => {
+                no_offset(q)?;
+                Ok(Value::Quantity(Quantity {
+                    value: apply(*s, q.value)?,
+                    dimension: q.dimension.combine(q.dimension, -2),
+                    unit: ShownUnit { text: divide_text("1", &q.unit.text), factor: 1.0 / q.unit.factor, offset: 0.0 },
+                    bare: false,
+                }))
+            }
+            Operation::Power => Err("Exponents must be plain numbers".to_string()),
+            _ => Err(format!("Incompatible units: {} and {}", describe(a), q.unit.text)),
+        },
+        (Value::Quantity(q), Some(Value::Scalar(s))) => match op {
+            Operation::Multiply | Operation::Divide => {
+                no_offset(q)?;
+                Ok(Value::Quantity(q.with_value(apply(q.value, *s)?)))
+            }
+            Operation::Power => power(q, *s, options),
+            _ => Err(format!("Incompatible units: {} and {}", q.unit.text, describe(&Value::Scalar(*s)))),
+        },
+        (Value::Scalar(_) | Value::Measured(_), _) | (_, Some(Value::Measured(_))) => {
+            unreachable!("called only when an operand has units and none is measured")
+        }
+    }
+}
+
+/// Expresses `source` in the units of `target`, as in `60 mph to m/s`.
+pub fn convert(source: &Value, target: &Value) -> Result<Value, String> {
+    let Value::Quantity(target) = target else {
+        return Err("Convert to a unit, e.g. `to km`".to_string());
+    };
+    match source {
+        Value::Quantity(source) if source.dimension == target.dimension => Ok(Value::Quantity(Quantity {
+            value: source.value,
+            dimension: source.dimension,
+            unit: target.unit.clone(),
+            bare: false,
+        })),
+        _ => Err(format!("Cannot convert {} to {}", describe(source), target.unit.text)),
+    }
+}
+
+/// Groups of interchangeable units offered by the conversion mode. Units
+/// are written as expressions, so compound units like `km/h` work too.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Category {
+    #[default]
+    Length,
+    Mass,
+    Time,
+    Temperature,
+    Area,
+    Volume,
+    Speed,
+    Energy,
+    Power,
+    Pressure,
+    Force,
+    Data,
+    Frequency,
+}
+
+impl Category {
+    pub const ALL: [Category; 13] = [
+        Category::Length,
+        Category::Mass,
+        Category::Time,
+        Category::Temperature,
+        Category::Area,
+        Category::Volume,
+        Category::Speed,
+        Category::Energy,
+        Category::Power,
+        Category::Pressure,
+        Category::Force,
+        Category::Data,
+        Category::Frequency,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Length => "Length",
+            Category::Mass => "Mass",
+            Category::Time => "Time",
+            Category::Temperature => "Temperature",
+            Category::Area => "Area",
+            Category::Volume => "Volume",
+            Category::Speed => "Speed",
+            Category::Energy => "Energy",
+            Category::Power => "Power",
+            Category::Pressure => "Pressure",
+            Category::Force => "Force",
+            Category::Data => "Data size",
+            Category::Frequency => "Frequency",
+        }
+    }
+
+    pub fn units(self) -> &'static [&'static str] {
+        match self {
+            Category::Length => &["m", "km", "cm", "mm", "µm", "nm", "in", "ft", "yd", "mi", "nmi", "au", "ly"],
+            Category::Mass => &["kg", "g", "mg", "tonne", "lb", "oz", "st"],
+            Category::Time => &["s", "ms", "µs", "ns", "min", "h", "d", "wk", "yr"],
+            Category::Temperature => &["°C", "°F", "K", "°R"],
+            Category::Area => &["m^2", "km^2", "cm^2", "ha", "acre", "ft^2", "in^2", "mi^2"],
+            Category::Volume => &["L", "mL", "m^3", "cm^3", "gal", "qt", "pt", "cup", "floz", "tbsp", "tsp", "ft^3", "in^3"],
+            Category::Speed => &["m/s", "km/h", "mph", "kn", "ft/s"],
+            Category::Energy => &["J", "kJ", "cal", "kcal", "Wh", "kWh", "eV", "BTU"],
+            Category::Power => &["W", "kW", "MW", "hp"],
+            Category::Pressure => &["Pa", "kPa", "hPa", "bar", "mbar", "atm", "psi", "mmHg", "torr"],
+            Category::Force => &["N", "kN", "lbf", "kgf"],
+            Category::Data => &["bit", "B", "kB", "MB", "GB", "TB", "KiB", "MiB", "GiB", "TiB"],
+            Category::Frequency => &["Hz", "kHz", "MHz", "GHz", "rpm"],
+        }
+    }
+}
+
+/// Inputs of the conversion mode, kept with the app state.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Conversion {
+    pub category: Category,
+    pub from: String,
+    pub to: String,
+}
+
+impl Default for Conversion {
+    fn default() -> Self {
+        Self::for_category(Category::default())
+    }
+}
+
+impl Conversion {
+    /// The first two units of a category.
+    pub fn for_category(category: Category) -> Self {
+        let units = category.units();
+        Self {
+            category,
+            from: units[0].to_string(),
+            to: units[1].to_string(),
+        }
+    }
+
+    /// Converts `value` from one unit to the other, returning the quantity
+    /// in the target unit.
+    pub fn convert(&self, value: f64, options: NumericOptions) -> Result<Quantity, String> {
+        let ctx = Context::new(options);
+        let locale = NumberLocale::default();
+        let from = expression::evaluate_value(&self.from, &locale, &ctx)?;
+        let to = expression::evaluate_value(&self.to, &locale, &ctx)?;
+        let source = match from {
+            Value::Quantity(_) => calculate(Operation::Multiply, &Value::Scalar(value), Some(&from), options)?,
+            _ => return Err(format!("Unknown unit '{}'", self.from)),
+        };
+        match convert(&source, &to)? {
+            Value::Quantity(quantity) => Ok(quantity),
+            _ => unreachable!("conversion always yields a quantity"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> Result<Value, String> {
+        expression::evaluate_value(text, &NumberLocale::default(), &Context::new(NumericOptions::default()))
+    }
+
+    /// The value in its shown unit and the unit's text.
+    fn shown(text: &str) -> (f64, String) {
+        match eval(text) {
+            Ok(Value::Quantity(quantity)) => (quantity.shown_value(), quantity.unit_text().to_string()),
+            other => panic!("{}: {:?}", text, other),
+        }
+    }
+
+    fn assert_shown(text: &str, value: f64, unit: &str) {
+        let (actual, actual_unit) = shown(text);
+        assert!((actual - value).abs() <= 1e-12 * value.abs().max(1.0), "{}: {} vs {}", text, actual, value);
+        assert_eq!(actual_unit, unit, "{}", text);
+    }
+
+    #[test]
+    fn temperatures_convert_with_their_offsets() {
+        assert_shown("100 °C to °F", 212.0, "°F");
+        assert_shown("-40 °F to °C", -40.0, "°C");
+        assert_shown("32 degF to K", 273.15, "K");
+        assert_shown("0 K to °C", -273.15, "°C");
+        let conversion = Conversion { category: Category::Temperature, from: "°C".to_string(), to: "°F".to_string() };
+        assert!((conversion.convert(37.0, NumericOptions::default()).unwrap().shown_value() - 98.6).abs() <= 1e-12);
+    }
+
+    #[test]
+    fn temperature_differences_are_intervals() {
+        // 30 °C − 20 °C is 10 kelvin, not 283.15 °C
+        assert_shown("30 °C - 20 °C", 10.0, "K");
+        assert_shown("20 °C + 5 K", 25.0, "°C");
+        assert_eq!(
+            eval("20 °C + 5 °C").unwrap_err(),
+            "Cannot add two °C temperatures; add a difference in K instead"
+        );
+        assert_eq!(eval("2 * (20 °C)").unwrap_err(), "°C values can only be added, subtracted or converted");
+    }
+
+    #[test]
+    fn mismatched_dimensions_are_rejected() {
+        assert_eq!(eval("5 m + 2 s").unwrap_err(), "Incompatible units: m and s");
+        assert_eq!(eval("5 kg to m").unwrap_err(), "Cannot convert kg to m");
+        assert_eq!(eval("3 to m").unwrap_err(), "Cannot convert a plain number to m");
+        assert!(eval("5 m + 2").is_err());
+        assert_eq!(eval("2 ^ (3 m)").unwrap_err(), "Exponents must be plain numbers");
+    }
+
+    #[test]
+    fn compound_units_combine_and_cancel() {
+        assert_shown("60 mph to m/s", 26.8224, "m/s");
+        assert_shown("100 km/h to m/s", 100.0 / 3.6, "m/s");
+        assert_shown("3 kN * 2 m to J", 6000.0, "J");
+        assert_shown("1 kWh to MJ", 3.6, "MJ");
+        assert_shown("(3 m)^2", 9.0, "m^2");
+        assert_shown("sqrt(16 m^2)", 4.0, "m");
+        assert_shown("10 m / (2 s)", 5.0, "m/s");
+        assert_shown("-(3 m) + 5 m", 2.0, "m");
+        // Units that cancel leave a plain number
+        assert_eq!(eval("6 km / 3 m").unwrap(), Value::Scalar(2000.0));
+        assert_eq!(eval("1 h / 1 min").unwrap(), Value::Scalar(60.0));
+    }
+}
@@ -0,0 +1,281 @@
+use crate::calculator::{self, NumericOptions, Operation};
+use crate::uncertainty::{self, Measured};
+use crate::units::{self, Quantity};
+
+/// Result of evaluating an expression: a real number, a vector of reals,
+/// a quantity with units or a measurement with its precision.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Value {
+    Scalar(f64),
+    Vector(Vec<f64>),
+    Quantity(Quantity),
+    Measured(Measured),
+}
+
+impl Value {
+    /// The number, or an error where a vector appears in a scalar context.
+    pub fn scalar(&self) -> Result<f64, String> {
+        match self {
+            Value::Scalar(value) => Ok(*value),
+            Value::Vector(_) => Err("Expected a number, not a vector".to_string()),
+            Value::Quantity(quantity) => Err(format!("Expected a number, not a value in {}", quantity.unit_text())),
+            Value::Measured(_) => Err("Expected a number, not a value with uncertainty".to_string()),
+        }
+    }
+
+    /// A number as an exact measurement, for combining with measured values.
+    fn measured(&self) -> Result<Measured, String> {
+        match self {
+            Value::Scalar(value) => Ok(Measured::exact(*value)),
+            Value::Measured(measured) => Ok(*measured),
+            _ => Err("Uncertainties combine with plain numbers only, not vectors or units".to_string()),
+        }
+    }
+
+    fn vector(&self, function: &str) -> Result<&[f64], String> {
+        match self {
+            Value::Vector(components) => Ok(components),
+            _ => Err(format!("{} takes vectors", function)),
+        }
+    }
+
+    pub fn negate(&self) -> Result<Value, String> {
+        match self {
+            Value::Scalar(value) => Ok(Value::Scalar(-value)),
+            Value::Vector(components) => Ok(Value::Vector(components.iter().map(|x| -x).collect())),
+            Value::Quantity(quantity) => Ok(Value::Quantity(quantity.negate())),
+            Value::Measured(measured) => Ok(Value::Measured(measured.negate())),
+        }
+    }
+}
+
+/// Applies a keypad operation component by component. A scalar paired
+/// with a vector applies to every component; two vectors must have the
+/// same length. Quantities follow the unit rules in `units::calculate`,
+/// and measurements propagate their precision with
+/// `uncertainty::calculate`.
+pub fn calculate(op: Operation, a: &Value, b: Option<&Value>, options: NumericOptions) -> Result<Value, String> {
+    let apply = |x: f64, y: Option<f64>| calculator::calculate(op, x, y, options);
+    if op == Operation::PlusMinus || matches!(a, Value::Measured(_)) || matches!(b, Some(Value::Measured(_))) {
+        let a = a.measured()?;
+        let b = b.map(Value::measured).transpose()?;
+        return uncertainty::calculate(op, a, b, options).map(Value::Measured);
+    }
+    match (a, b) {
+        (Value::Quantity(_), _) | (_, Some(Value::Quantity(_))) => units::calculate(op, a, b, options),
+        (Value::Scalar(x), None) => apply(*x, None).map(Value::Scalar),
+        (Value::Scalar(x), Some(Value::Scalar(y))) => apply(*x, Some(*y)).map(Value::Scalar),
+        (Value::Vector(xs), None) => xs.iter().map(|&x| apply(x, None)).collect::<Result<_, _>>().map(Value::Vector),
+        (Value::Vector(xs), Some(Value::Scalar(y))) => {
+            xs.iter().map(|&x| apply(x, Some(*y))).collect::<Result<_, _>>().map(Value::Vector)
+        }
+        (Value::Scalar(x), Some(Value::Vector(ys))) => {
+            ys.iter().map(|&y| apply(*x, Some(y))).collect::<Result<_, _>>().map(Value::Vector)
+        }
+        (Value::Vector(xs), Some(Value::Vector(ys))) => {
+            same_length(xs, ys)?;
+            xs.iter()
+                .zip(ys)
+                .map(|(&x, &y)| apply(x, Some(y)))
+                .collect::<Result<_, _>>()
+                .map(Value::Vector)
+        }
+        (Value::Measured(_), _) | (_, Some(Value::Measured(_))) => unreachable!("handled above"),
+    }
+}
+
+fn same_length(a: &[f64], b: &[f64]) -> Result<(), String> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(format!("Vectors have different lengths ({} and {})", a.len(), b.len()))
+    }
+}
+
+/// Expression functions on vectors, e.g. `dot([1, 2], [3, 4])`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VectorFunction {
+    Dot,
+    Cross,
+    Norm,
+    /// The vector scaled to length 1.
+    Unit,
+    /// Angle between two vectors, in the angle unit setting.
+    Angle,
+    /// Projection of the first vector onto the second.
+    Projection,
+}
+
+impl VectorFunction {
+    pub fn lookup(name: &str) -> Option<VectorFunction> {
+        match name {
+            "dot" => Some(VectorFunction::Dot),
+            "cross" => Some(VectorFunction::Cross),
+            "norm" => Some(VectorFunction::Norm),
+            "unit" | "normalize" => Some(VectorFunction::Unit),
+            "angle" => Some(VectorFunction::Angle),
+            "proj" => Some(VectorFunction::Projection),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            VectorFunction::Dot => "dot",
+            VectorFunction::Cross => "cross",
+            VectorFunction::Norm => "norm",
+            VectorFunction::Unit => "unit",
+            VectorFunction::Angle => "angle",
+            VectorFunction::Projection => "proj",
+        }
+    }
+
+    pub fn call(self, args: &[Value], options: NumericOptions) -> Result<Value, String> {
+        let name = self.name();
+        match (self, args) {
+            (VectorFunction::Norm, [v]) => Ok(Value::Scalar(norm(v.vector(name)?))),
+            (VectorFunction::Unit, [v]) => {
+                let v = v.vector(name)?;
+                let length = norm(v);
+                if length == 0.0 {
+                    return Err("Cannot normalize the zero vector".to_string());
+                }
+                Ok(Value::Vector(v.iter().map(|x| x / length).collect()))
+            }
+            (VectorFunction::Norm | VectorFunction::Unit, _) => Err(format!("{} takes 1 argument", name)),
+            (_, [a, b]) => {
+                let (a, b) = (a.vector(name)?, b.vector(name)?);
+                same_length(a, b)?;
+                match self {
+                    VectorFunction::Dot => Ok(Value::Scalar(dot(a, b))),
+                    VectorFunction::Cross => match (a, b) {
+                        ([a1, a2, a3], [b1, b2, b3]) => Ok(Value::Vector(vec![
+                            a2 * b3 - a3 * b2,
+                            a3 * b1 - a1 * b3,
+                            a1 * b2 - a2 * b1,
+                        ])),
+                        _ => Err("cross takes two 3-component vectors".to_string()),
+                    },
+                    VectorFunction::Angle => {
+                        let lengths = norm(a) * norm(b);
+                        if lengths == 0.0 {
+                            return Err("The angle with a zero vector is undefined".to_string());
+                        }
+                        // Rounding can push the cosine just outside [−1, 1]
+                        let cosine = (dot(a, b) / lengths).clamp(-1.0, 1.0);
+                        Ok(Value::Scalar(options.angle.radians_to_unit(cosine.acos())))
+                    }
+                    VectorFunction::Projection => {
+                        let length_squared = dot(b, b);
+                        if length_squared == 0.0 {
+                            return Err("Cannot project onto the zero vector".to_string());
+                        }
+                        let factor = dot(a, b) / length_squared;
+                        Ok(Value::Vector(b.iter().map(|x| factor * x).collect()))
+                    }
+                    VectorFunction::Norm | VectorFunction::Unit => unreachable!("handled above"),
+                }
+            }
+            _ => Err(format!("{} takes 2 arguments", name)),
+        }
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().fold(0.0f64, |acc, x| acc.hypot(*x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::AngleUnit;
+
+    fn vector(components: &[f64]) -> Value {
+        Value::Vector(components.to_vec())
+    }
+
+    fn call(function: &str, args: &[Value]) -> Result<Value, String> {
+        VectorFunction::lookup(function).unwrap().call(args, NumericOptions::default())
+    }
+
+    #[test]
+    fn scalars_broadcast_over_vectors() {
+        let options = NumericOptions::default();
+        let v = vector(&[1.0, 2.0, 3.0]);
+        let two = Value::Scalar(2.0);
+        assert_eq!(calculate(Operation::Multiply, &v, Some(&two), options), Ok(vector(&[2.0, 4.0, 6.0])));
+        assert_eq!(calculate(Operation::Subtract, &two, Some(&v), options), Ok(vector(&[1.0, 0.0, -1.0])));
+        assert_eq!(calculate(Operation::Add, &v, Some(&v), options), Ok(vector(&[2.0, 4.0, 6.0])));
+        assert_eq!(calculate(Operation::Square, &v, None, options), Ok(vector(&[1.0, 4.0, 9.0])));
+        assert_eq!(v.negate(), Ok(vector(&[-1.0, -2.0, -3.0])));
+        // A failing component fails the whole vector
+        let zeros = vector(&[1.0, 0.0]);
+        assert_eq!(
+            calculate(Operation::Divide, &two, Some(&zeros), options),
+            Err("Cannot divide by zero".to_string())
+        );
+        assert_eq!(v.scalar(), Err("Expected a number, not a vector".to_string()));
+    }
+
+    #[test]
+    fn vectors_must_have_the_same_length() {
+        let error = Err("Vectors have different lengths (2 and 3)".to_string());
+        let (a, b) = (vector(&[1.0, 2.0]), vector(&[1.0, 2.0, 3.0]));
+        assert_eq!(calculate(Operation::Add, &a, Some(&b), NumericOptions::default()), error);
+        assert_eq!(call("dot", &[a, b]), error);
+    }
+
+    #[test]
+    fn products_and_lengths() {
+        let (x, y) = (vector(&[1.0, 0.0, 0.0]), vector(&[0.0, 1.0, 0.0]));
+        assert_eq!(call("dot", &[vector(&[1.0, 2.0]), vector(&[3.0, 4.0])]), Ok(Value::Scalar(11.0)));
+        assert_eq!(call("cross", &[x.clone(), y.clone()]), Ok(vector(&[0.0, 0.0, 1.0])));
+        assert_eq!(call("cross", &[y, x]), Ok(vector(&[0.0, 0.0, -1.0])));
+        assert_eq!(
+            call("cross", &[vector(&[1.0, 2.0]), vector(&[3.0, 4.0])]),
+            Err("cross takes two 3-component vectors".to_string())
+        );
+        assert_eq!(call("norm", &[vector(&[3.0, 4.0])]), Ok(Value::Scalar(5.0)));
+        assert_eq!(call("unit", &[vector(&[3.0, 4.0])]), Ok(vector(&[0.6, 0.8])));
+        assert_eq!(call("dot", &[Value::Scalar(1.0), vector(&[1.0])]), Err("dot takes vectors".to_string()));
+        assert_eq!(call("norm", &[]), Err("norm takes 1 argument".to_string()));
+        assert_eq!(call("angle", &[vector(&[1.0])]), Err("angle takes 2 arguments".to_string()));
+    }
+
+    #[test]
+    fn angles_follow_the_angle_unit() {
+        let (a, b) = (vector(&[1.0, 0.0]), vector(&[1.0, 1.0]));
+        let angle = |unit: AngleUnit| {
+            let options = NumericOptions { angle: unit, ..NumericOptions::default() };
+            match VectorFunction::Angle.call(&[a.clone(), b.clone()], options) {
+                Ok(Value::Scalar(angle)) => angle,
+                other => panic!("{:?}", other),
+            }
+        };
+        assert!((angle(AngleUnit::Degrees) - 45.0).abs() < 1e-12);
+        assert!((angle(AngleUnit::Radians) - std::f64::consts::FRAC_PI_4).abs() < 1e-15);
+        assert!((angle(AngleUnit::Gradians) - 50.0).abs() < 1e-12);
+        // Parallel vectors give exactly 0 despite rounding in the cosine
+        let v = vector(&[0.1, 0.2, 0.3]);
+        assert_eq!(call("angle", &[v.clone(), v]), Ok(Value::Scalar(0.0)));
+        assert_eq!(
+            call("angle", &[vector(&[0.0, 0.0]), vector(&[1.0, 0.0])]),
+            Err("The angle with a zero vector is undefined".to_string())
+        );
+    }
+
+    #[test]
+    fn zero_vectors_cannot_be_normalized_or_projected_onto() {
+        assert_eq!(call("proj", &[vector(&[2.0, 3.0]), vector(&[1.0, 0.0])]), Ok(vector(&[2.0, 0.0])));
+        assert_eq!(
+            call("proj", &[vector(&[2.0, 3.0]), vector(&[0.0, 0.0])]),
+            Err("Cannot project onto the zero vector".to_string())
+        );
+        assert_eq!(call("unit", &[vector(&[0.0, 0.0])]), Err("Cannot normalize the zero vector".to_string()));
+        assert_eq!(call("normalize", &[vector(&[0.0, 5.0])]), Ok(vector(&[0.0, 1.0])));
+    }
+}